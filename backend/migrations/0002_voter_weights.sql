@@ -0,0 +1,16 @@
+-- 0002_voter_weights.sql
+-- Weight registry for weighted (shareholder / delegate) polls
+
+-- Each row is a weight that takes effect at effective_from; the latest row at cast time applies.
+CREATE TABLE IF NOT EXISTS voter_weights (
+    id SERIAL PRIMARY KEY,
+    poll_id TEXT NOT NULL,            -- References polls.poll_id
+    voter_id TEXT NOT NULL,           -- References voters.voter_id
+    weight DOUBLE PRECISION NOT NULL CHECK (weight >= 0),
+    effective_from TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (poll_id) REFERENCES polls(poll_id),
+    FOREIGN KEY (voter_id) REFERENCES voters(voter_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_voter_weights_lookup ON voter_weights (poll_id, voter_id, effective_from);
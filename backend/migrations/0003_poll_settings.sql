@@ -0,0 +1,4 @@
+-- 0003_poll_settings.sql
+-- Per-poll configuration for specialised poll types (quadratic budgets, etc.)
+
+ALTER TABLE polls ADD COLUMN IF NOT EXISTS settings JSONB NOT NULL DEFAULT '{}';
@@ -0,0 +1,13 @@
+-- 0004_chain_records.sql
+-- Non-ballot records appended to a poll's chain (e.g. write-in adjudications)
+
+-- Replayed alongside votes, in creation order, when chains are rebuilt at startup.
+CREATE TABLE IF NOT EXISTS chain_records (
+    id SERIAL PRIMARY KEY,
+    poll_id TEXT NOT NULL,            -- References polls.poll_id
+    record JSONB NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (poll_id) REFERENCES polls(poll_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_chain_records_poll ON chain_records (poll_id, created_at);
@@ -0,0 +1,8 @@
+-- 0005_vote_versions.sql
+-- Revoting: each recast ballot is a new version that supersedes the voter's previous one
+
+ALTER TABLE votes DROP CONSTRAINT IF EXISTS votes_poll_id_voter_id_key;
+ALTER TABLE votes ADD COLUMN IF NOT EXISTS version INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE votes ADD COLUMN IF NOT EXISTS supersedes INTEGER REFERENCES votes(id);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_votes_version ON votes (poll_id, voter_id, version);
@@ -0,0 +1,4 @@
+-- 0006_vote_demographics.sql
+-- Age bracket and region of the voter at cast time, kept off-chain for polls that report demographics
+
+ALTER TABLE votes ADD COLUMN IF NOT EXISTS demographics JSONB;
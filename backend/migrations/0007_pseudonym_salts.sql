@@ -0,0 +1,11 @@
+-- 0007_pseudonym_salts.sql
+-- Per-poll salts for the keyed pseudonyms that stand in for voter ids on the public chain
+
+-- A poll's pseudonym key is derived from the server secret and its current salt; replacing the
+-- salt rotates every pseudonym in the poll.
+CREATE TABLE IF NOT EXISTS pseudonym_salts (
+    poll_id TEXT PRIMARY KEY,         -- References polls.poll_id
+    salt TEXT NOT NULL,
+    rotated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (poll_id) REFERENCES polls(poll_id)
+);
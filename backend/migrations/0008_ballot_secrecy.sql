@@ -0,0 +1,40 @@
+-- 0008_ballot_secrecy.sql
+-- Separate the voter roll from the ballot box: who voted is kept apart from how they voted
+
+-- Who has voted in each poll. Keyed by voter, with no row ids and only the hour of the first
+-- ballot, so rows cannot be matched to ballots by insertion order or time.
+CREATE TABLE IF NOT EXISTS participation (
+    poll_id TEXT NOT NULL,            -- References polls.poll_id
+    voter_id TEXT NOT NULL,           -- References voters.voter_id
+    ballots_cast INTEGER NOT NULL DEFAULT 1,
+    voted_hour TIMESTAMP WITH TIME ZONE NOT NULL,
+    PRIMARY KEY (poll_id, voter_id)   -- One participation per poll per voter
+);
+
+-- The ballot box. Ballots carry a random id and no voter id; `chained_at` is the timestamp of
+-- the public block holding the ballot, which orders ballots when the chain is rebuilt.
+CREATE TABLE IF NOT EXISTS ballots (
+    ballot_id TEXT PRIMARY KEY,
+    poll_id TEXT NOT NULL,            -- References polls.poll_id
+    vote JSONB NOT NULL,
+    demographics JSONB,
+    superseded BOOLEAN NOT NULL DEFAULT FALSE,
+    chained_at BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_ballots_poll ON ballots (poll_id, chained_at);
+
+INSERT INTO participation (poll_id, voter_id, ballots_cast, voted_hour)
+SELECT poll_id, voter_id, MAX(version), date_trunc('hour', MIN(COALESCE(created_at, NOW())))
+FROM votes
+GROUP BY poll_id, voter_id
+ON CONFLICT DO NOTHING;
+
+INSERT INTO ballots (ballot_id, poll_id, vote, demographics, superseded, chained_at)
+SELECT gen_random_uuid()::TEXT, poll_id, vote, demographics,
+       version < MAX(version) OVER (PARTITION BY poll_id, voter_id),
+       (EXTRACT(EPOCH FROM COALESCE(created_at, NOW())) * 1000)::BIGINT
+FROM votes
+WHERE vote IS NOT NULL;
+
+DROP TABLE votes;
@@ -0,0 +1,24 @@
+-- 0009_ballot_credentials.sql
+-- Blind-signed ballot credentials for polls that accept anonymous ballots
+
+-- Each poll's RSA signing key; the private exponent never leaves the server.
+CREATE TABLE IF NOT EXISTS credential_keys (
+    poll_id TEXT PRIMARY KEY,         -- References polls.poll_id
+    key JSONB NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (poll_id) REFERENCES polls(poll_id)
+);
+
+-- Voters who have been issued a credential; one per poll per voter.
+CREATE TABLE IF NOT EXISTS credential_issuance (
+    poll_id TEXT NOT NULL,            -- References polls.poll_id
+    voter_id TEXT NOT NULL,           -- References voters.voter_id
+    PRIMARY KEY (poll_id, voter_id)
+);
+
+-- Nullifiers of spent credentials, so each credential casts one ballot.
+CREATE TABLE IF NOT EXISTS spent_credentials (
+    poll_id TEXT NOT NULL,            -- References polls.poll_id
+    nullifier TEXT NOT NULL,
+    PRIMARY KEY (poll_id, nullifier)
+);
@@ -0,0 +1,10 @@
+-- 0010_encryption_keys.sql
+-- Election keys for polls whose ballots are encrypted with exponential ElGamal
+
+-- Each encrypted poll's key pair; the secret exponent only decrypts the final tally.
+CREATE TABLE IF NOT EXISTS encryption_keys (
+    poll_id TEXT PRIMARY KEY,         -- References polls.poll_id
+    key JSONB NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (poll_id) REFERENCES polls(poll_id)
+);
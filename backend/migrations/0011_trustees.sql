@@ -0,0 +1,32 @@
+-- 0011_trustees.sql
+-- Threshold key ceremonies and distributed decryption for encrypted polls held by trustees
+
+-- Each trustee's transport key, coefficient commitments and proof of knowledge.
+CREATE TABLE IF NOT EXISTS trustee_commitments (
+    poll_id TEXT NOT NULL,            -- References polls.poll_id
+    trustee TEXT NOT NULL,
+    commitments JSONB NOT NULL,
+    published_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (poll_id, trustee),
+    FOREIGN KEY (poll_id) REFERENCES polls(poll_id)
+);
+
+-- Key shares between trustees, encrypted to the recipient's transport key.
+CREATE TABLE IF NOT EXISTS trustee_shares (
+    poll_id TEXT NOT NULL,            -- References polls.poll_id
+    sender TEXT NOT NULL,
+    recipient TEXT NOT NULL,
+    share JSONB NOT NULL,
+    PRIMARY KEY (poll_id, sender, recipient),
+    FOREIGN KEY (poll_id) REFERENCES polls(poll_id)
+);
+
+-- Each trustee's proven partial decryption of the closed poll's aggregate tally.
+CREATE TABLE IF NOT EXISTS partial_decryptions (
+    poll_id TEXT NOT NULL,            -- References polls.poll_id
+    trustee TEXT NOT NULL,
+    decryption JSONB NOT NULL,
+    submitted_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (poll_id, trustee),
+    FOREIGN KEY (poll_id) REFERENCES polls(poll_id)
+);
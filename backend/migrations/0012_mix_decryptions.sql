@@ -0,0 +1,12 @@
+-- 0012_mix_decryptions.sql
+-- Trustees' partial decryptions of the mixed ballots of elections decrypted after a verifiable shuffle
+
+-- Each trustee's proven partial decryption of every chunk of every mixed ballot.
+CREATE TABLE IF NOT EXISTS mix_decryptions (
+    poll_id TEXT NOT NULL,            -- References polls.poll_id
+    trustee TEXT NOT NULL,
+    decryption JSONB NOT NULL,
+    submitted_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (poll_id, trustee),
+    FOREIGN KEY (poll_id) REFERENCES polls(poll_id)
+);
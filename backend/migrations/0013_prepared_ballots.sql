@@ -0,0 +1,16 @@
+-- 0013_prepared_ballots.sql
+-- Ballots the server encrypted for voters, held until the voter casts or challenges them
+
+-- A prepared ballot with the openings of its ciphertexts. Rows are deleted when the voter casts
+-- a ballot or challenges this one, so the plaintext behind a cast ballot is not kept.
+CREATE TABLE IF NOT EXISTS prepared_ballots (
+    tracking_code TEXT PRIMARY KEY,
+    poll_id TEXT NOT NULL,            -- References polls.poll_id
+    voter_id TEXT NOT NULL,
+    ballot JSONB NOT NULL,
+    opening JSONB NOT NULL,
+    prepared_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (poll_id) REFERENCES polls(poll_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_prepared_ballots_voter ON prepared_ballots (poll_id, voter_id);
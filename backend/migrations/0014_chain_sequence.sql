@@ -0,0 +1,34 @@
+-- 0014_chain_sequence.sql
+-- Number every chained ballot and record by its block index so chains are rebuilt in order
+
+ALTER TABLE ballots ADD COLUMN IF NOT EXISTS chain_seq BIGINT;
+ALTER TABLE chain_records ADD COLUMN IF NOT EXISTS chain_seq BIGINT;
+ALTER TABLE chain_records ADD COLUMN IF NOT EXISTS chained_at BIGINT;
+
+UPDATE chain_records
+SET chained_at = (EXTRACT(EPOCH FROM COALESCE(created_at, NOW())) * 1000)::BIGINT
+WHERE chained_at IS NULL;
+
+-- Existing entries are numbered in the order the chain was rebuilt from before this migration
+CREATE TEMPORARY TABLE chain_order AS
+SELECT source, id, ROW_NUMBER() OVER (PARTITION BY poll_id ORDER BY chained_at, source, id) AS chain_seq
+FROM (
+    SELECT 0 AS source, ballot_id AS id, poll_id, chained_at FROM ballots
+    UNION ALL
+    SELECT 1 AS source, LPAD(id::TEXT, 20, '0'), poll_id, chained_at FROM chain_records
+) entries;
+
+UPDATE ballots SET chain_seq = chain_order.chain_seq
+FROM chain_order WHERE chain_order.source = 0 AND chain_order.id = ballots.ballot_id;
+
+UPDATE chain_records SET chain_seq = chain_order.chain_seq
+FROM chain_order WHERE chain_order.source = 1 AND chain_order.id = LPAD(chain_records.id::TEXT, 20, '0');
+
+DROP TABLE chain_order;
+
+ALTER TABLE ballots ALTER COLUMN chain_seq SET NOT NULL;
+ALTER TABLE chain_records ALTER COLUMN chain_seq SET NOT NULL;
+ALTER TABLE chain_records ALTER COLUMN chained_at SET NOT NULL;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_ballots_chain_seq ON ballots (poll_id, chain_seq);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_chain_records_chain_seq ON chain_records (poll_id, chain_seq);
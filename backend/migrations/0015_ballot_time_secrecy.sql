@@ -0,0 +1,16 @@
+-- 0015_ballot_time_secrecy.sql
+-- Ballots copied from the old votes table kept the voter id stored inside each vote and their
+-- exact cast time, which can be matched against when a voter was seen voting. Strip the voter
+-- id and truncate their block timestamps to the hour, as ballots and commitments are chained now.
+
+UPDATE ballots
+SET vote = vote - 'voter_id'
+WHERE jsonb_typeof(vote) = 'object' AND vote ? 'voter_id';
+
+UPDATE ballots
+SET chained_at = chained_at - chained_at % 3600000
+WHERE chained_at % 3600000 <> 0;
+
+UPDATE chain_records
+SET chained_at = chained_at - chained_at % 3600000
+WHERE record->>'record_type' = 'commitment' AND chained_at % 3600000 <> 0;
@@ -0,0 +1,145 @@
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+use serde::{Serialize, Deserialize};
+
+/// Electors per state for the 2024 and 2028 US presidential elections (2020 census), with Maine
+/// and Nebraska splitting their electors by congressional district.
+const US_ELECTORAL_COLLEGE: &str = include_str!("../data/electoral_college.json");
+
+/// How many electors a state awards and how.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum StateElectors {
+    /// Every elector goes to the statewide plurality winner, e.g. `"CA": 54`.
+    WinnerTakeAll(u32),
+    /// `at_large` electors go to the statewide winner and one or more to each district's winner,
+    /// e.g. `"ME": {"at_large": 2, "districts": {"ME-1": 1, "ME-2": 1}}`.
+    DistrictSplit {
+        at_large: u32,
+        districts: BTreeMap<String, u32>,
+    },
+}
+
+impl StateElectors {
+    pub fn total(&self) -> u32 {
+        match self {
+            StateElectors::WinnerTakeAll(electors) => *electors,
+            StateElectors::DistrictSplit { at_large, districts } => at_large + districts.values().sum::<u32>(),
+        }
+    }
+}
+
+/// Bundled elector tables a contest can refer to by name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ElectorPreset {
+    UsElectoralCollege,
+}
+
+/// The electors a contest is apportioned over, declared as `electors` on a contest: either a
+/// bundled preset such as `"us_electoral_college"` or a table of states.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ElectorConfig {
+    Preset(ElectorPreset),
+    Table(BTreeMap<String, StateElectors>),
+}
+
+impl ElectorConfig {
+    /// The elector table this configuration stands for.
+    pub fn table(&self) -> &BTreeMap<String, StateElectors> {
+        match self {
+            ElectorConfig::Preset(ElectorPreset::UsElectoralCollege) => us_electoral_college(),
+            ElectorConfig::Table(table) => table,
+        }
+    }
+}
+
+/// The bundled US Electoral College table.
+pub fn us_electoral_college() -> &'static BTreeMap<String, StateElectors> {
+    static TABLE: OnceLock<BTreeMap<String, StateElectors>> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        serde_json::from_str(US_ELECTORAL_COLLEGE).expect("bundled electoral college table is valid")
+    })
+}
+
+/// How the electors of one state (at large) or district were awarded.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnitResult {
+    /// State code for at-large electors, district code for district electors.
+    pub unit: String,
+    pub electors: u32,
+    /// The plurality winner, if there is exactly one.
+    pub winner: Option<String>,
+    /// Candidates tied for the lead, when the unit's electors are left unallocated by a tie.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tied: Vec<String>,
+}
+
+/// Electoral votes won in a contest and the projected winner.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ElectoralResult {
+    pub electoral_votes: BTreeMap<String, u32>,
+    pub total_electors: u32,
+    /// Electoral votes needed for an outright win: more than half of all electors.
+    pub majority: u32,
+    /// The candidate holding a majority of electors, if any.
+    pub projected_winner: Option<String>,
+    /// Electors not yet awarded because no votes were cast in their unit or the unit is tied.
+    pub unallocated: u32,
+    pub units: Vec<UnitResult>,
+}
+
+fn award(unit: &str, electors: u32, counts: Option<&BTreeMap<String, u64>>) -> UnitResult {
+    let top = counts.and_then(|c| c.values().copied().max()).unwrap_or(0);
+    let leading: Vec<String> = counts.into_iter()
+        .flatten()
+        .filter(|(_, &votes)| top > 0 && votes == top)
+        .map(|(candidate, _)| candidate.clone())
+        .collect();
+    let (winner, tied) = match leading.len() {
+        1 => (leading.into_iter().next(), Vec::new()),
+        _ => (None, leading),
+    };
+    UnitResult { unit: unit.to_string(), electors, winner, tied }
+}
+
+/// Apportions electors from per-state and per-district vote counts (candidate → votes).
+///
+/// Winner-take-all states award all their electors to the statewide plurality winner.
+/// District-split states award their at-large electors statewide and each district's electors to
+/// that district's plurality winner. A unit with no votes or a tie for the lead is unallocated.
+pub fn apportion(
+    table: &BTreeMap<String, StateElectors>,
+    by_state: &BTreeMap<String, BTreeMap<String, u64>>,
+    by_district: &BTreeMap<String, BTreeMap<String, u64>>,
+) -> ElectoralResult {
+    let mut units = Vec::new();
+    for (state, electors) in table {
+        match electors {
+            StateElectors::WinnerTakeAll(electors) => units.push(award(state, *electors, by_state.get(state))),
+            StateElectors::DistrictSplit { at_large, districts } => {
+                units.push(award(state, *at_large, by_state.get(state)));
+                for (district, electors) in districts {
+                    units.push(award(district, *electors, by_district.get(district)));
+                }
+            },
+        }
+    }
+
+    let total_electors: u32 = table.values().map(StateElectors::total).sum();
+    let mut electoral_votes: BTreeMap<String, u32> = BTreeMap::new();
+    let mut unallocated = 0;
+    for unit in &units {
+        match &unit.winner {
+            Some(winner) => *electoral_votes.entry(winner.clone()).or_insert(0) += unit.electors,
+            None => unallocated += unit.electors,
+        }
+    }
+    let majority = total_electors / 2 + 1;
+    let projected_winner = electoral_votes.iter()
+        .find(|(_, &votes)| votes >= majority)
+        .map(|(candidate, _)| candidate.clone());
+
+    ElectoralResult { electoral_votes, total_electors, majority, projected_winner, unallocated, units }
+}
@@ -0,0 +1,137 @@
+use chrono::{DateTime, DurationRound, TimeDelta, Utc};
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+/// Field stamped on every recorded vote with its [`BallotStatus`].
+pub const BALLOT_STATUS_FIELD: &str = "ballot_status";
+
+/// Field on a recast ballot referencing the block of the ballot it replaces.
+pub const SUPERSEDES_FIELD: &str = "supersedes";
+
+/// Field on a ballot in a poll open to revoting that links it to the voter's later recasts. The
+/// tag is derived from a random ballot handle only the voter holds, so the server cannot
+/// recompute it from the voter's id.
+pub const BALLOT_TAG_FIELD: &str = "ballot_tag";
+
+/// A fresh random ballot handle, given to the voter in their receipt to recast with.
+pub fn new_ballot_handle() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+/// The tag a voter's ballots carry in a poll: the hex SHA-256 of the poll, the voter and their
+/// ballot handle. Binding the voter means a handle recasts only its own voter's ballot.
+pub fn ballot_tag(poll_id: &str, voter_id: &str, ballot_handle: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(b"ballot-tag");
+    for part in [poll_id, voter_id, ballot_handle] {
+        hasher.update([0]);
+        hasher.update(part.as_bytes());
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// The block timestamp for a ballot cast at `at`: the start of its hour in milliseconds, so a
+/// ballot cannot be matched to a voter by when they were seen voting.
+pub fn ballot_timestamp(at: DateTime<Utc>) -> i64 {
+    at.duration_trunc(TimeDelta::hours(1)).unwrap_or(at).timestamp_millis()
+}
+
+/// Returns the block index of the ballot a recast ballot supersedes, if it is one.
+pub fn superseded_block(vote: &Value) -> Option<u64> {
+    vote.get(SUPERSEDES_FIELD)
+        .and_then(|s| s.get("block_index"))
+        .and_then(|i| i.as_u64())
+}
+
+/// How a cast ballot counts: every status counts towards turnout, only valid ballots are tallied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BallotStatus {
+    /// The ballot expresses a choice and is tallied.
+    #[default]
+    Valid,
+    /// The voter explicitly declined to choose, e.g. `{"abstain": true}`.
+    Abstain,
+    /// The ballot was returned without any selection.
+    Blank,
+    /// The ballot could not be interpreted (neither a choice nor an object of selections).
+    Spoiled,
+}
+
+impl BallotStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BallotStatus::Valid => "valid",
+            BallotStatus::Abstain => "abstain",
+            BallotStatus::Blank => "blank",
+            BallotStatus::Spoiled => "spoiled",
+        }
+    }
+
+    /// Classifies a vote as submitted by a voter.
+    pub fn classify(vote: &Value) -> Self {
+        match vote {
+            Value::String(choice) if choice.trim().is_empty() => BallotStatus::Blank,
+            Value::String(_) => BallotStatus::Valid,
+            Value::Object(_) if is_abstention(vote) => BallotStatus::Abstain,
+            Value::Object(selections) if selections.values().all(is_blank_selection) => BallotStatus::Blank,
+            Value::Object(_) => BallotStatus::Valid,
+            _ => BallotStatus::Spoiled,
+        }
+    }
+
+    /// Reads the status stamped on a recorded vote. Votes recorded before statuses were
+    /// stamped are valid.
+    pub fn of_record(vote: &Value) -> Self {
+        vote.get(BALLOT_STATUS_FIELD)
+            .cloned()
+            .and_then(|status| serde_json::from_value(status).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// Whether a selection was left empty: `null`, an empty string, list or object.
+pub fn is_blank_selection(selection: &Value) -> bool {
+    match selection {
+        Value::Null => true,
+        Value::String(s) => s.trim().is_empty(),
+        Value::Array(entries) => entries.is_empty(),
+        Value::Object(fields) => fields.is_empty(),
+        _ => false,
+    }
+}
+
+/// Whether a ballot or contest selection is an explicit abstention (`{"abstain": true}`).
+pub fn is_abstention(selection: &Value) -> bool {
+    selection.get("abstain").and_then(|a| a.as_bool()) == Some(true)
+}
+
+/// Ballots cast against how many of them were valid votes.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Turnout {
+    pub ballots_cast: u32,
+    pub valid_votes: u32,
+    pub abstained: u32,
+    pub blank: u32,
+    pub spoiled: u32,
+}
+
+impl Turnout {
+    /// Counts one ballot (or contest selection) with the given status.
+    pub fn record(&mut self, status: BallotStatus) {
+        self.add(status, 1);
+    }
+
+    /// Counts `count` ballots with the given status.
+    pub fn add(&mut self, status: BallotStatus, count: u32) {
+        self.ballots_cast += count;
+        match status {
+            BallotStatus::Valid => self.valid_votes += count,
+            BallotStatus::Abstain => self.abstained += count,
+            BallotStatus::Blank => self.blank += count,
+            BallotStatus::Spoiled => self.spoiled += count,
+        }
+    }
+}
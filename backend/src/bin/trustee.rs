@@ -0,0 +1,105 @@
+//! Trustee CLI for the key ceremony and distributed decryption of encrypted polls.
+//!
+//! The trustee's private state (polynomial coefficients, transport key and key share) stays in
+//! a local state file. Each command reads JSON fetched from the server and prints the JSON body
+//! to post back, e.g. with curl:
+//!
+//! ```text
+//! curl $API/poll/$POLL/trustees > ceremony.json
+//! trustee init alice.json ceremony.json alice | curl -d @- -H 'Content-Type: application/json' $API/poll/$POLL/trustees/alice/commitments
+//! trustee shares alice.json ceremony.json     | curl -d @- ... $API/poll/$POLL/trustees/alice/shares
+//! curl $API/poll/$POLL/trustees/alice/shares > shares.json
+//! trustee receive alice.json ceremony.json shares.json
+//! curl $API/poll/$POLL/encrypted_tally > tally.json
+//! trustee decrypt alice.json tally.json       | curl -d @- ... $API/poll/$POLL/trustees/alice/partial_decryption
+//! curl $API/poll/$POLL/mix > mix.json
+//! trustee decrypt-mix alice.json mix.json     | curl -d @- ... $API/poll/$POLL/trustees/alice/mix_decryption
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::process;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use backend::encrypted_tally::EncryptedSelections;
+use backend::mixnet::ShuffleRecord;
+use backend::trustee::{Ceremony, EncryptedShare, TrusteeState};
+
+const USAGE: &str = "\
+Usage:
+  trustee init <state.json> <ceremony.json> <trustee>        start the ceremony; prints commitments
+  trustee shares <state.json> <ceremony.json>                prints encrypted shares for the other trustees
+  trustee receive <state.json> <ceremony.json> <shares.json> checks received shares and derives the key share
+  trustee decrypt <state.json> <tally.json>                  prints a proven partial decryption of the tally
+  trustee decrypt-mix <state.json> <mix.json>                prints a proven partial decryption of every mixed ballot";
+
+fn read_json<T: DeserializeOwned>(path: &str) -> Result<T, String> {
+    let contents = fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+    serde_json::from_str(&contents).map_err(|e| format!("Cannot parse {}: {}", path, e))
+}
+
+fn write_json<T: Serialize>(path: &str, value: &T) -> Result<(), String> {
+    let contents = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    fs::write(path, contents).map_err(|e| format!("Cannot write {}: {}", path, e))
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<(), String> {
+    println!("{}", serde_json::to_string_pretty(value).map_err(|e| e.to_string())?);
+    Ok(())
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    match args {
+        [command, state_path, ceremony_path, trustee] if command == "init" => {
+            if fs::metadata(state_path).is_ok() {
+                return Err(format!("{} already exists; a trustee's polynomial must not be regenerated", state_path));
+            }
+            let ceremony: Ceremony = read_json(ceremony_path)?;
+            let index = ceremony.trustees.iter()
+                .find(|t| &t.trustee == trustee)
+                .map(|t| t.index)
+                .ok_or_else(|| format!("{} is not a trustee of poll {}", trustee, ceremony.poll_id))?;
+            let state = TrusteeState::new(&ceremony.poll_id, trustee, index, ceremony.threshold);
+            write_json(state_path, &state)?;
+            print_json(&state.commitments())
+        },
+        [command, state_path, ceremony_path] if command == "shares" => {
+            let state: TrusteeState = read_json(state_path)?;
+            let ceremony: Ceremony = read_json(ceremony_path)?;
+            print_json(&state.shares_for(&ceremony)?)
+        },
+        [command, state_path, ceremony_path, shares_path] if command == "receive" => {
+            let mut state: TrusteeState = read_json(state_path)?;
+            let ceremony: Ceremony = read_json(ceremony_path)?;
+            let shares: BTreeMap<String, EncryptedShare> = read_json(shares_path)?;
+            state.receive_shares(&ceremony, &shares)?;
+            write_json(state_path, &state)?;
+            eprintln!("All shares verified; key share saved to {}", state_path);
+            Ok(())
+        },
+        [command, state_path, tally_path] if command == "decrypt" => {
+            let state: TrusteeState = read_json(state_path)?;
+            let tally: serde_json::Value = read_json(tally_path)?;
+            let aggregates: EncryptedSelections = serde_json::from_value(tally["aggregates"].clone())
+                .map_err(|e| format!("{} has no aggregates: {}", tally_path, e))?;
+            print_json(&state.partially_decrypt(&aggregates)?)
+        },
+        [command, state_path, mix_path] if command == "decrypt-mix" => {
+            let state: TrusteeState = read_json(state_path)?;
+            let mix: serde_json::Value = read_json(mix_path)?;
+            let shuffles: Vec<ShuffleRecord> = serde_json::from_value(mix["shuffles"].clone())
+                .map_err(|e| format!("{} has no shuffles: {}", mix_path, e))?;
+            let last = shuffles.last().ok_or_else(|| format!("{} has not been mixed yet", mix_path))?;
+            print_json(&state.partially_decrypt_ballots(&last.ballots)?)
+        },
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Err(message) = run(&args) {
+        eprintln!("{}", message);
+        process::exit(1);
+    }
+}
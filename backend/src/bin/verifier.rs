@@ -0,0 +1,75 @@
+//! Offline verifier for encrypted polls.
+//!
+//! Checks a poll exported from the server without trusting it: that the chain's blocks are
+//! intact and linked, that every ballot's ciphertexts carry valid well-formedness proofs, and
+//! that any decrypted tally was decrypted from the aggregate of exactly those ballots. For
+//! mixed polls it checks every shuffle's proof instead and, once the final mix is decrypted,
+//! every trustee's partial decryption against the ceremony's verification keys and every
+//! decrypted ballot against the mix, then prints the final mix with the decrypted ballots.
+//! Every challenged ballot is opened and checked not to have been cast.
+//!
+//! ```text
+//! curl $API/poll/$POLL/details > details.json
+//! curl $API/poll/$POLL/blockchain > blockchain.json
+//! curl $API/poll/$POLL/encryption_key > key.json
+//! curl $API/poll/$POLL/trustees > trustees.json   # mixed polls only
+//! verifier details.json blockchain.json key.json [trustees.json]
+//! ```
+
+use std::fs;
+use std::process;
+use serde::de::DeserializeOwned;
+use backend::blockchain::Blockchain;
+use backend::election_blockchain::ElectionBlockchain;
+use backend::elgamal::PublicKey;
+use backend::challenge;
+use backend::encrypted_tally;
+use backend::mixnet;
+use backend::poll_manager::{Poll, PollInput};
+use backend::trustee::Ceremony;
+
+const USAGE: &str = "Usage: verifier <details.json> <blockchain.json> <encryption_key.json> [trustees.json]";
+
+fn read_json<T: DeserializeOwned>(path: &str) -> Result<T, String> {
+    let contents = fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+    serde_json::from_str(&contents).map_err(|e| format!("Cannot parse {}: {}", path, e))
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let (details_path, chain_path, key_path, ceremony_path) = match args {
+        [details, chain, key] => (details, chain, key, None),
+        [details, chain, key, ceremony] => (details, chain, key, Some(ceremony)),
+        _ => return Err(USAGE.to_string()),
+    };
+    let metadata: PollInput = read_json(details_path)?;
+    let key: PublicKey = read_json(key_path)?;
+    let mixed = metadata.settings.mixnet;
+    let poll = match metadata.poll_type.as_deref() {
+        Some("election") => Poll::Election { metadata, blockchain: ElectionBlockchain { chain: read_json(chain_path)? } },
+        _ => Poll::Normal { metadata, blockchain: Blockchain { chain: read_json(chain_path)? } },
+    };
+    let challenged = challenge::verify_challenges(&poll, &key)?;
+    eprintln!("Opened {} challenged ballots", challenged);
+    if mixed {
+        let ceremony_path = ceremony_path.ok_or_else(|| format!("Mixed polls also need the poll's trustees. {}", USAGE))?;
+        let ceremony: Ceremony = read_json(ceremony_path)?;
+        let ballots = mixnet::verify_chain(&poll, &key, &ceremony)?;
+        let Poll::Election { blockchain, .. } = &poll else { unreachable!("only elections are mixed") };
+        let mix = serde_json::json!({ "mixed_ballots": ballots, "decrypted": mixnet::decryption(blockchain) });
+        println!("{}", serde_json::to_string_pretty(&mix).map_err(|e| e.to_string())?);
+        eprintln!("Verified {} shuffles of {} ballots", mixnet::shuffles(blockchain).len(), ballots.len());
+        return Ok(());
+    }
+    let tally = encrypted_tally::verify_chain(&poll, &key)?;
+    println!("{}", serde_json::to_string_pretty(&tally).map_err(|e| e.to_string())?);
+    eprintln!("Verified {} ballots", tally.ballots);
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Err(message) = run(&args) {
+        eprintln!("{}", message);
+        process::exit(1);
+    }
+}
@@ -18,7 +18,12 @@ impl Block {
     /// Creates a new block with one initial transaction.
     pub fn new(index: u32, transaction: Value, previous_hash: String) -> Self {
         // Use millisecond precision for consistency
-        let timestamp = Utc::now().timestamp_millis();
+        Self::new_at(index, transaction, previous_hash, Utc::now().timestamp_millis())
+    }
+
+    /// Creates a new block with one initial transaction, stamped with the given time in
+    /// milliseconds (used when replaying stored votes).
+    pub fn new_at(index: u32, transaction: Value, previous_hash: String, timestamp: i64) -> Self {
         let transactions_vec = vec![transaction.clone()];
         let transactions_serialized = serde_json::to_string(&transactions_vec).unwrap_or_default();
         let hash = Self::calculate_hash(index, timestamp, &transactions_serialized, &previous_hash);
@@ -1,12 +1,24 @@
+use crate::ballot::{superseded_block, BallotStatus, Turnout, BALLOT_TAG_FIELD};
 use crate::block::Block;
+use crate::election_blockchain::RECORD_TYPE_FIELD;
+use crate::tie_break::{TieBreakRecord, TIE_BREAK_RECORD};
+use crate::timeline::{self, Bucket, TimelinePoint};
+use std::collections::{BTreeMap, HashSet};
 use serde::{Serialize, Deserialize};
 use serde_json::Value;
+use chrono::Utc;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Blockchain {
     pub chain: Vec<Block>,
 }
 
+impl Default for Blockchain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Blockchain {
     /// Creates a new blockchain with a genesis block.
     pub fn new() -> Self {
@@ -18,8 +30,13 @@ impl Blockchain {
 
     /// Adds a new block with a single transaction to the chain.
     pub fn add_block(&mut self, transaction: Value) {
+        self.add_block_at(transaction, Utc::now().timestamp_millis());
+    }
+
+    /// Adds a new block stamped with the given time in milliseconds.
+    pub fn add_block_at(&mut self, transaction: Value, timestamp: i64) {
         let previous_block = self.chain.last().unwrap();
-        let mut new_block = Block::new(previous_block.index as u32 + 1, transaction, previous_block.hash.clone());
+        let mut new_block = Block::new_at(previous_block.index as u32 + 1, transaction, previous_block.hash.clone(), timestamp);
         new_block.finalize();
         self.chain.push(new_block);
     }
@@ -36,63 +53,163 @@ impl Blockchain {
         true
     }
 
-    /// Searches for a vote by a given voter ID by scanning through the transactions of each block.
-    pub fn find_vote(&self, voter_id: &str) -> Option<(u32, String)> {
-        for block in self.chain.iter().skip(1) {
-            for transaction in &block.transactions {
-                if let Some(obj) = transaction.as_object() {
-                    if let Some(val) = obj.get("voter_id") {
-                        if val.as_str() == Some(voter_id) {
-                            return Some((block.index as u32, block.hash.clone()));
-                        }
-                    }
-                }
+    /// Iterates over the ballots that currently count, with the block holding each. Non-ballot
+    /// records and ballots superseded by a later recast are skipped; the chain keeps them all.
+    pub fn ballots(&self) -> impl Iterator<Item = (&Block, &Value)> {
+        let superseded: HashSet<u64> = self.chain.iter()
+            .flat_map(|block| block.transactions.iter())
+            .filter_map(superseded_block)
+            .collect();
+        self.chain.iter()
+            .skip(1)
+            .filter(move |block| !superseded.contains(&(block.index as u64)))
+            .flat_map(|block| block.transactions.iter().map(move |transaction| (block, transaction)))
+            .filter(|(_, transaction)| transaction.is_object() && transaction.get(RECORD_TYPE_FIELD).is_none())
+    }
+
+    /// Searches for the current ballot carrying a ballot tag, returning its block index and hash.
+    pub fn find_tagged_ballot(&self, ballot_tag: &str) -> Option<(u32, String)> {
+        self.ballots()
+            .find(|(_, transaction)| transaction.get(BALLOT_TAG_FIELD).and_then(|t| t.as_str()) == Some(ballot_tag))
+            .map(|(block, _)| (block.index as u32, block.hash.clone()))
+    }
+
+    /// Extracts the chosen option from a vote transaction, if it names one. Abstentions, blank
+    /// and spoiled ballots never name an option.
+    fn vote_choice(transaction: &Value) -> Option<&str> {
+        if BallotStatus::of_record(transaction) != BallotStatus::Valid {
+            return None;
+        }
+        if let Some(obj) = transaction.as_object() {
+            // Try to get the vote from the "choice" field first (new format),
+            // then the "candidate" field (old format)
+            if let Some(choice_val) = obj.get("choice") {
+                return choice_val.as_str();
             }
+            if let Some(candidate_val) = obj.get("candidate") {
+                return candidate_val.as_str();
+            }
+            // Plain string votes are recorded as "default_choice"
+            obj.get("default_choice").and_then(|v| v.as_str())
+        } else {
+            transaction.as_str()
         }
-        None
     }
 
-    /// Aggregates vote counts by iterating over transactions in each block.
+    /// Cumulative vote counts per option at the end of each time bucket, replayed from block
+    /// timestamps. A recast ballot moves the voter's vote at the time it was cast.
+    pub fn timeline(&self, options: &[String], bucket: Bucket) -> Vec<TimelinePoint<BTreeMap<String, u64>>> {
+        let choice_at = |index: u64| {
+            self.chain.get(index as usize)
+                .and_then(|block| block.transactions.iter().find_map(Self::vote_choice))
+                .map(str::to_string)
+        };
+        let events = self.chain.iter()
+            .skip(1)
+            .flat_map(|block| block.transactions.iter().map(move |transaction| (block, transaction)))
+            .filter(|(_, transaction)| transaction.is_object() && transaction.get(RECORD_TYPE_FIELD).is_none())
+            .map(|(block, transaction)| {
+                let removed = superseded_block(transaction).and_then(choice_at);
+                (block.timestamp, (removed, Self::vote_choice(transaction).map(str::to_string)))
+            })
+            .collect();
+        let initial = options.iter().map(|option| (option.clone(), 0)).collect();
+        timeline::cumulative(bucket, initial, events, |counts: &mut BTreeMap<String, u64>, (removed, added)| {
+            if let Some(count) = removed.and_then(|option| counts.get_mut(&option)) {
+                *count = count.saturating_sub(1);
+            }
+            if let Some(option) = added {
+                *counts.entry(option).or_insert(0) += 1;
+            }
+        })
+    }
+
+    /// Aggregates vote counts by iterating over transactions in each block. Each option maps to
+    /// its headcount, unless ballots carry the `weight` in effect when they were cast (weighted
+    /// polls): then each option maps to the sum of its ballots' weights alongside the raw
+    /// headcount, with ballots lacking a weight counting as 1.
     pub fn get_vote_counts(&self) -> serde_json::Map<String, serde_json::Value> {
-        let mut vote_counts = serde_json::Map::new();
-        for block in self.chain.iter().skip(1) {
-            for transaction in &block.transactions {
-                if let Some(obj) = transaction.as_object() {
-                    // Try to get the vote from the "choice" field first (new format)
-                    if let Some(choice_val) = obj.get("choice") {
-                        if let Some(choice) = choice_val.as_str() {
-                            let count = vote_counts.entry(choice.to_string())
-                                .or_insert(serde_json::json!(0));
-                            if let Some(n) = count.as_u64() {
-                                *count = serde_json::json!(n + 1);
-                            }
-                        }
-                        continue; // Skip to next transaction after processing choice
-                    }
-                    
-                    // Then try the "candidate" field (old format)
-                    if let Some(candidate_val) = obj.get("candidate") {
-                        if let Some(candidate) = candidate_val.as_str() {
-                            let count = vote_counts.entry(candidate.to_string())
-                                .or_insert(serde_json::json!(0));
-                            if let Some(n) = count.as_u64() {
-                                *count = serde_json::json!(n + 1);
-                            }
-                        }
-                        continue; // Skip to next transaction after processing candidate
-                    }
-                    
-                    // If neither field exists, try to use the transaction itself as a string
-                    if let Some(vote_str) = transaction.as_str() {
-                        let count = vote_counts.entry(vote_str.to_string())
-                            .or_insert(serde_json::json!(0));
-                        if let Some(n) = count.as_u64() {
-                            *count = serde_json::json!(n + 1);
-                        }
-                    }
-                }
+        let mut tallies: BTreeMap<String, (f64, u64)> = BTreeMap::new();
+        let mut weighted = false;
+        for (_, transaction) in self.ballots() {
+            if let Some(choice) = Self::vote_choice(transaction) {
+                let weight = transaction.get("weight").and_then(|w| w.as_f64());
+                weighted |= weight.is_some();
+                let tally = tallies.entry(choice.to_string()).or_default();
+                tally.0 += weight.unwrap_or(1.0);
+                tally.1 += 1;
+            }
+        }
+
+        tallies.into_iter()
+            .map(|(option, (weight, headcount))| {
+                let count = if weighted {
+                    serde_json::json!({ "weight": weight, "headcount": headcount })
+                } else {
+                    serde_json::json!(headcount)
+                };
+                (option, count)
+            })
+            .collect()
+    }
+
+    /// The headcount of an option in the result of [`Blockchain::get_vote_counts`], whether or
+    /// not the poll is weighted.
+    pub fn headcount(count: &Value) -> u64 {
+        count.get("headcount").unwrap_or(count).as_u64().unwrap_or(0)
+    }
+
+    /// Counts every ballot on the chain by status, so turnout can be reported against valid votes.
+    pub fn get_turnout(&self) -> Turnout {
+        let mut turnout = Turnout::default();
+        for (_, transaction) in self.ballots() {
+            turnout.record(BallotStatus::of_record(transaction));
+        }
+        turnout
+    }
+
+    /// Iterates over the non-ballot records of a given type, in chain order.
+    pub fn records<'a>(&'a self, record_type: &'a str) -> impl Iterator<Item = &'a Value> + 'a {
+        self.chain.iter()
+            .skip(1)
+            .flat_map(|block| block.transactions.iter())
+            .filter(move |tx| tx.get(RECORD_TYPE_FIELD).and_then(|t| t.as_str()) == Some(record_type))
+    }
+
+    /// Searches for the latest record of a given type carrying a ballot tag, returning its block
+    /// index and hash with the record.
+    pub fn find_tagged_record(&self, record_type: &str, ballot_tag: &str) -> Option<(u32, String, &Value)> {
+        self.chain.iter()
+            .skip(1)
+            .rev()
+            .find_map(|block| {
+                block.transactions.iter()
+                    .find(|tx| {
+                        tx.get(RECORD_TYPE_FIELD).and_then(|t| t.as_str()) == Some(record_type)
+                            && tx.get(BALLOT_TAG_FIELD).and_then(|v| v.as_str()) == Some(ballot_tag)
+                    })
+                    .map(|tx| (block.index as u32, block.hash.clone(), tx))
+            })
+    }
+
+    /// Returns the tie-break decisions recorded on the chain, in chain order.
+    pub fn get_tie_break_records(&self) -> Vec<TieBreakRecord> {
+        self.records(TIE_BREAK_RECORD)
+            .filter_map(|record| serde_json::from_value(record.clone()).ok())
+            .collect()
+    }
+
+    /// Running tallies of `options` after each ballot in chain order, oldest first, used to
+    /// compare tied options at earlier points in the count.
+    pub fn running_tallies(&self, options: &[String]) -> Vec<BTreeMap<String, f64>> {
+        let mut running: BTreeMap<String, f64> = options.iter().map(|o| (o.clone(), 0.0)).collect();
+        let mut history = Vec::new();
+        for (_, transaction) in self.ballots() {
+            if let Some(tally) = Self::vote_choice(transaction).and_then(|choice| running.get_mut(choice)) {
+                *tally += 1.0;
+                history.push(running.clone());
             }
         }
-        vote_counts
+        history
     }
 }
@@ -0,0 +1,185 @@
+use std::collections::BTreeMap;
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+use crate::blockchain::Blockchain;
+
+/// Projects funded by one allocation rule.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BudgetAllocation {
+    pub funded: Vec<String>,
+    pub total_cost: u64,
+    pub unspent: u64,
+}
+
+/// Results of a participatory budgeting poll under both allocation rules.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetResults {
+    pub budget: u64,
+    pub ballots: u32,
+    pub approvals: BTreeMap<String, u32>,
+    pub greedy: BudgetAllocation,
+    pub equal_shares: BudgetAllocation,
+}
+
+/// A project on the ballot with its cost.
+#[derive(Debug, Clone)]
+pub struct Project {
+    pub name: String,
+    pub cost: u64,
+}
+
+/// Pairs poll options with their costs, failing if the lists do not line up.
+pub fn projects_from_options(options: &[String], costs: &[u64]) -> Result<Vec<Project>, String> {
+    if options.len() != costs.len() {
+        return Err(format!("{} options were given but {} costs", options.len(), costs.len()));
+    }
+    Ok(options.iter()
+        .zip(costs)
+        .map(|(name, &cost)| Project { name: name.clone(), cost })
+        .collect())
+}
+
+/// Validates a ballot of the form `{"projects": ["Park", "Library"]}`, returning the selected
+/// projects if they are distinct, on the ballot, and fit within `budget` together.
+pub fn validate_ballot(projects: &[Project], budget: u64, vote: &Value) -> Result<Vec<String>, String> {
+    let selected = vote.get("projects")
+        .and_then(|p| p.as_array())
+        .ok_or_else(|| "Budgeting ballots must contain a \"projects\" list".to_string())?;
+
+    let mut chosen: Vec<String> = Vec::new();
+    let mut total: u64 = 0;
+    for entry in selected {
+        let name = entry.as_str()
+            .ok_or_else(|| "Selected projects must be project names".to_string())?;
+        let project = projects.iter().find(|p| p.name == name)
+            .ok_or_else(|| format!("'{}' is not a project in this poll", name))?;
+        if chosen.iter().any(|c| c == name) {
+            return Err(format!("'{}' is selected more than once", name));
+        }
+        total += project.cost;
+        chosen.push(name.to_string());
+    }
+
+    if total > budget {
+        return Err(format!("Selected projects cost {} but the budget is {}", total, budget));
+    }
+    Ok(chosen)
+}
+
+/// Reads every project selection recorded on the chain.
+fn ballots_from_chain(blockchain: &Blockchain, projects: &[Project]) -> Vec<Vec<usize>> {
+    let mut ballots = Vec::new();
+    for (_, transaction) in blockchain.ballots() {
+        if let Some(selected) = transaction.get("projects").and_then(|p| p.as_array()) {
+            let ballot: Vec<usize> = selected.iter()
+                .filter_map(|v| v.as_str())
+                .filter_map(|name| projects.iter().position(|p| p.name == name))
+                .collect();
+            ballots.push(ballot);
+        }
+    }
+    ballots
+}
+
+/// Funds projects in order of approvals, skipping any that no longer fit in the budget.
+fn greedy(projects: &[Project], approvals: &[u32], budget: u64) -> BudgetAllocation {
+    let mut order: Vec<usize> = (0..projects.len()).collect();
+    order.sort_by(|&a, &b| approvals[b].cmp(&approvals[a]).then(projects[a].cost.cmp(&projects[b].cost)).then(a.cmp(&b)));
+
+    let mut allocation = BudgetAllocation::default();
+    for p in order {
+        if approvals[p] > 0 && allocation.total_cost + projects[p].cost <= budget {
+            allocation.total_cost += projects[p].cost;
+            allocation.funded.push(projects[p].name.clone());
+        }
+    }
+    allocation.unspent = budget - allocation.total_cost;
+    allocation
+}
+
+/// Method of Equal Shares with approval utilities.
+///
+/// Each voter receives an equal share of the budget. Projects are funded one at a time by their
+/// supporters, choosing the project whose cost can be split with the smallest maximum payment
+/// per supporter; supporters who cannot cover that payment contribute everything they have left.
+fn equal_shares(projects: &[Project], ballots: &[Vec<usize>], budget: u64) -> BudgetAllocation {
+    let mut allocation = BudgetAllocation::default();
+    if ballots.is_empty() {
+        allocation.unspent = budget;
+        return allocation;
+    }
+
+    let mut balances = vec![budget as f64 / ballots.len() as f64; ballots.len()];
+    let mut funded = vec![false; projects.len()];
+
+    loop {
+        let mut best: Option<(usize, f64)> = None;
+        for (p, project) in projects.iter().enumerate() {
+            if funded[p] {
+                continue;
+            }
+            let supporters: Vec<usize> = (0..ballots.len()).filter(|&v| ballots[v].contains(&p)).collect();
+            let Some(rho) = payment_cap(&supporters, &balances, project.cost as f64) else {
+                continue;
+            };
+            if best.is_none_or(|(_, best_rho)| rho < best_rho - 1e-9) {
+                best = Some((p, rho));
+            }
+        }
+
+        let Some((p, rho)) = best else { break };
+        for (v, ballot) in ballots.iter().enumerate() {
+            if ballot.contains(&p) {
+                balances[v] -= balances[v].min(rho);
+            }
+        }
+        funded[p] = true;
+        allocation.total_cost += projects[p].cost;
+        allocation.funded.push(projects[p].name.clone());
+    }
+
+    allocation.unspent = budget - allocation.total_cost;
+    allocation
+}
+
+/// The smallest per-supporter payment cap that covers `cost`, or `None` if the supporters
+/// cannot afford the project between them.
+fn payment_cap(supporters: &[usize], balances: &[f64], cost: f64) -> Option<f64> {
+    if supporters.is_empty() {
+        return None;
+    }
+    let mut available: Vec<f64> = supporters.iter().map(|&v| balances[v]).collect();
+    if available.iter().sum::<f64>() < cost - 1e-9 {
+        return None;
+    }
+    available.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut remaining = cost;
+    for (i, balance) in available.iter().enumerate() {
+        let payers = (available.len() - i) as f64;
+        if balance * payers >= remaining - 1e-9 {
+            return Some(remaining / payers);
+        }
+        remaining -= balance;
+    }
+    None
+}
+
+/// Tallies a participatory budgeting poll from the chain.
+pub fn tally(blockchain: &Blockchain, projects: &[Project], budget: u64) -> BudgetResults {
+    let ballots = ballots_from_chain(blockchain, projects);
+    let mut approvals = vec![0u32; projects.len()];
+    for ballot in &ballots {
+        for &p in ballot {
+            approvals[p] += 1;
+        }
+    }
+
+    BudgetResults {
+        budget,
+        ballots: ballots.len() as u32,
+        approvals: projects.iter().map(|p| p.name.clone()).zip(approvals.iter().copied()).collect(),
+        greedy: greedy(projects, &approvals, budget),
+        equal_shares: equal_shares(projects, &ballots, budget),
+    }
+}
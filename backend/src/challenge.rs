@@ -0,0 +1,102 @@
+use serde::{Serialize, Deserialize};
+use serde_json::{json, Value};
+use crate::elgamal::PublicKey;
+use crate::encrypted_tally::{BallotOpenings, EncryptedLayout, ENCRYPTED_FIELD};
+use crate::mixnet::{self, SealOpening};
+use crate::poll_manager::{Poll, PollInput};
+use crate::vote_service::ballot_receipt;
+
+/// Record type for a prepared ballot the voter challenged instead of casting, published with
+/// its opening.
+pub const CHALLENGED_BALLOT_RECORD: &str = "challenged_ballot";
+
+/// The secrets behind a prepared ballot's ciphertexts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BallotOpening {
+    /// A ballot sealed whole for a mixed poll.
+    Sealed(SealOpening),
+    /// A ballot with a ciphertext per option.
+    Selections(BallotOpenings),
+}
+
+/// A ballot the server encrypted for a voter, who either casts it or challenges it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PreparedBallot {
+    pub tracking_code: String,
+    pub ballot: Value,
+}
+
+/// A challenged ballot as published on the chain: no longer castable, and openable by anyone.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChallengedBallot {
+    pub tracking_code: String,
+    pub ballot: Value,
+    pub opening: BallotOpening,
+}
+
+/// The tracking code of a prepared ballot: a hash of its ciphertexts and proofs, which the voter
+/// notes before deciding whether to cast or challenge it.
+pub fn tracking_code(ballot: &Value) -> String {
+    ballot_receipt(ballot)
+}
+
+/// Encrypts a plaintext vote the way an encrypted poll's ballots are cast: sealed whole for
+/// mixed polls, otherwise a ciphertext with proofs per option.
+pub fn prepare(metadata: &PollInput, key: &PublicKey, vote: &Value) -> Result<(PreparedBallot, BallotOpening), String> {
+    let (ballot, opening) = if metadata.settings.mixnet {
+        let (ballot, opening) = mixnet::seal_opened(key, vote)?;
+        (ballot, BallotOpening::Sealed(opening))
+    } else {
+        let layout = EncryptedLayout::for_poll(metadata)?;
+        let (ballot, openings) = layout.encrypt_ballot_opened(key, &layout.choices(vote)?)?;
+        (ballot, BallotOpening::Selections(openings))
+    };
+    Ok((PreparedBallot { tracking_code: tracking_code(&ballot), ballot }, opening))
+}
+
+/// Checks that a challenged ballot's opening re-encrypts to its ciphertexts and that its
+/// tracking code matches, returning the selections the ballot encoded for the voter to compare
+/// with what they chose.
+pub fn verify(metadata: &PollInput, key: &PublicKey, challenged: &ChallengedBallot) -> Result<Value, String> {
+    if tracking_code(&challenged.ballot) != challenged.tracking_code {
+        return Err("The tracking code does not match the challenged ballot".to_string());
+    }
+    match &challenged.opening {
+        BallotOpening::Sealed(opening) if metadata.settings.mixnet => {
+            mixnet::open_ballot(key, &challenged.ballot, opening)?;
+            Ok(opening.selections.clone())
+        },
+        BallotOpening::Selections(openings) if !metadata.settings.mixnet => {
+            let layout = EncryptedLayout::for_poll(metadata)?;
+            Ok(json!(layout.open_ballot(&challenged.ballot, key, openings)?))
+        },
+        _ => Err("The opening does not fit the poll's ballots".to_string()),
+    }
+}
+
+/// Checks every challenged ballot on an encrypted poll's chain offline: that each opens to the
+/// selections it was published with, and that none of them was also cast. Returns how many
+/// challenged ballots there are.
+pub fn verify_challenges(poll: &Poll, key: &PublicKey) -> Result<usize, String> {
+    let (records, cast): (Vec<&Value>, Vec<&Value>) = match poll {
+        Poll::Election { blockchain, .. } => (
+            blockchain.records(CHALLENGED_BALLOT_RECORD).collect(),
+            blockchain.ballots().filter_map(|block| block.transactions.get(ENCRYPTED_FIELD)).collect(),
+        ),
+        Poll::Normal { blockchain, .. } => (
+            blockchain.records(CHALLENGED_BALLOT_RECORD).collect(),
+            blockchain.ballots().filter_map(|(_, transaction)| transaction.get(ENCRYPTED_FIELD)).collect(),
+        ),
+    };
+    for record in &records {
+        let challenged: ChallengedBallot = serde_json::from_value((*record).clone())
+            .map_err(|e| format!("Malformed challenged ballot: {}", e))?;
+        verify(poll.metadata(), key, &challenged)
+            .map_err(|e| format!("Challenged ballot {}: {}", challenged.tracking_code, e))?;
+        if challenged.ballot.get(ENCRYPTED_FIELD).is_some_and(|encrypted| cast.contains(&encrypted)) {
+            return Err(format!("Challenged ballot {} was also cast", challenged.tracking_code));
+        }
+    }
+    Ok(records.len())
+}
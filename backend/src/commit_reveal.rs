@@ -0,0 +1,58 @@
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+/// Record type for a ballot committed to during a commit-reveal poll's voting window.
+pub const COMMITMENT_RECORD: &str = "commitment";
+
+/// Field holding a ballot's commitment: a hex SHA-256 of the poll, the ballot and a salt.
+pub const COMMITMENT_FIELD: &str = "commitment";
+
+/// Field on a revealed ballot referencing the block of the commitment it opens.
+pub const REVEALS_FIELD: &str = "reveals";
+
+/// Field on a revealed ballot holding the salt its commitment was made with.
+pub const SALT_FIELD: &str = "salt";
+
+/// Shortest salt accepted, so a commitment cannot be opened by guessing the salt.
+pub const MIN_SALT_LENGTH: usize = 16;
+
+/// The commitment to `vote` in a poll: the hex SHA-256 of the poll id, the ballot as compact
+/// JSON with sorted keys, and the salt. Computed by the voter's client when casting and by the
+/// server when the ballot is revealed.
+pub fn commit(poll_id: &str, vote: &Value, salt: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(b"ballot-commitment");
+    hasher.update(poll_id.as_bytes());
+    hasher.update([0]);
+    hasher.update(serde_json::to_string(vote).unwrap_or_default().as_bytes());
+    hasher.update([0]);
+    hasher.update(salt.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Reads the commitment cast in a commit-reveal poll, which must carry nothing else.
+pub fn parse_commitment(vote: &Value) -> Result<String, String> {
+    let obj = vote.as_object().ok_or("Ballots in a commit-reveal poll are commitments")?;
+    if let Some(field) = obj.keys().find(|field| field.as_str() != COMMITMENT_FIELD) {
+        return Err(format!("Committed ballots cannot carry the field \"{}\" until they are revealed", field));
+    }
+    let commitment = obj.get(COMMITMENT_FIELD)
+        .and_then(Value::as_str)
+        .ok_or("Committed ballots carry a \"commitment\" string")?;
+    if commitment.len() != 64 || !commitment.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err("A commitment is a hex SHA-256 digest".to_string());
+    }
+    Ok(commitment.to_ascii_lowercase())
+}
+
+/// Returns the block index of the commitment a revealed ballot opens, if it is one.
+pub fn revealed_block(vote: &Value) -> Option<u64> {
+    vote.get(REVEALS_FIELD)
+        .and_then(|reveals| reveals.get("block_index"))
+        .and_then(Value::as_u64)
+}
+
+/// Whether `vote` and `salt` open `commitment`.
+pub fn opens(poll_id: &str, commitment: &str, vote: &Value, salt: &str) -> bool {
+    salt.len() >= MIN_SALT_LENGTH && commit(poll_id, vote, salt) == commitment
+}
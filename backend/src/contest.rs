@@ -0,0 +1,198 @@
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+use crate::apportionment::ElectorConfig;
+use crate::jurisdiction::Jurisdiction;
+use crate::stv::SurplusTransfer;
+
+/// How a contest on an election ballot is counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContestMethod {
+    /// One choice per ballot, most votes wins (the original election format).
+    #[default]
+    Plurality,
+    /// Ranked ballots counted by single transferable vote over `seats` seats.
+    Stv,
+}
+
+fn default_seats() -> u32 {
+    1
+}
+
+/// A single contest (office or proposition) declared in an election poll's options.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Contest {
+    #[serde(default)]
+    pub name: String,
+    pub candidates: Vec<String>,
+    #[serde(default)]
+    pub method: ContestMethod,
+    #[serde(default = "default_seats")]
+    pub seats: u32,
+    #[serde(default)]
+    pub surplus_transfer: SurplusTransfer,
+    /// How many write-in entries a ballot may carry in this contest (0 disables write-ins).
+    #[serde(default)]
+    pub write_in_slots: u32,
+    /// Declared write-in candidates that adjudicators may map write-ins to.
+    #[serde(default)]
+    pub qualified_write_ins: Vec<String>,
+    /// States or districts (e.g. `"CA"`, `"NY-12"`) whose voters see this contest. Empty means
+    /// every voter in the election.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub jurisdictions: Vec<String>,
+    /// Electors the contest is apportioned over by state and district, for presidency-style
+    /// contests decided by an electoral college.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub electors: Option<ElectorConfig>,
+}
+
+/// Longest write-in accepted on a ballot, in characters.
+pub const MAX_WRITE_IN_LENGTH: usize = 100;
+
+/// Resolution recorded by adjudicators for write-ins that do not name a qualified candidate.
+pub const INVALID_WRITE_IN: &str = "invalid";
+
+/// Normalizes a write-in so equivalent spellings are stored and adjudicated once: surrounding
+/// whitespace is trimmed, inner whitespace collapsed and letters lowercased.
+pub fn normalize_write_in(raw: &str) -> Result<String, String> {
+    let normalized = raw.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+    if normalized.is_empty() {
+        return Err("Write-in cannot be empty".to_string());
+    }
+    if normalized.chars().count() > MAX_WRITE_IN_LENGTH {
+        return Err(format!("Write-in cannot be longer than {} characters", MAX_WRITE_IN_LENGTH));
+    }
+    Ok(normalized)
+}
+
+/// Returns the raw write-in text if a ballot entry is a write-in (`{"write_in": "..."}`).
+pub fn write_in_text(entry: &Value) -> Option<&str> {
+    entry.get("write_in").and_then(|w| w.as_str())
+}
+
+impl Contest {
+    /// Creates a single-seat plurality contest, as described by a plain list of candidates.
+    pub fn plurality(name: &str, candidates: Vec<String>) -> Self {
+        Contest {
+            name: name.to_string(),
+            candidates,
+            method: ContestMethod::Plurality,
+            seats: 1,
+            surplus_transfer: SurplusTransfer::default(),
+            write_in_slots: 0,
+            qualified_write_ins: Vec::new(),
+            jurisdictions: Vec::new(),
+            electors: None,
+        }
+    }
+
+    /// Whether voters in `jurisdiction` are eligible to vote in this contest.
+    pub fn is_eligible(&self, jurisdiction: &Jurisdiction) -> bool {
+        self.jurisdictions.is_empty() || self.jurisdictions.iter().any(|code| jurisdiction.covers(code))
+    }
+
+    /// Candidates that can win this contest: those on the ballot and any qualified write-ins.
+    pub fn eligible_candidates(&self) -> Vec<String> {
+        let mut eligible = self.candidates.clone();
+        for name in &self.qualified_write_ins {
+            if !eligible.contains(name) {
+                eligible.push(name.clone());
+            }
+        }
+        eligible
+    }
+
+    /// Normalizes a single ballot entry: a listed candidate or, where slots allow, a write-in.
+    fn normalize_entry(&self, entry: &Value) -> Result<Value, String> {
+        if let Some(candidate) = entry.as_str() {
+            if self.candidates.iter().any(|c| c == candidate) {
+                return Ok(entry.clone());
+            }
+            return Err(format!("'{}' is not a candidate in contest '{}'", candidate, self.name));
+        }
+        if let Some(raw) = write_in_text(entry) {
+            if self.write_in_slots == 0 {
+                return Err(format!("Contest '{}' does not accept write-ins", self.name));
+            }
+            return Ok(serde_json::json!({ "write_in": normalize_write_in(raw)? }));
+        }
+        Err(format!("Contest '{}' entries must be a candidate name or a write-in", self.name))
+    }
+
+    /// Validates a ballot entry for this contest and returns it in the form stored on-chain,
+    /// with any write-ins normalized.
+    pub fn validate_selection(&self, selection: &Value) -> Result<Value, String> {
+        match self.method {
+            ContestMethod::Plurality => {
+                if selection.is_array() {
+                    return Err(format!("Contest '{}' expects a single choice", self.name));
+                }
+                self.normalize_entry(selection)
+            }
+            ContestMethod::Stv => {
+                let ranking = selection.as_array().ok_or_else(|| {
+                    format!("Contest '{}' expects a ranked list of candidates", self.name)
+                })?;
+                let mut normalized: Vec<Value> = Vec::new();
+                let mut write_ins = 0;
+                for entry in ranking {
+                    let entry = self.normalize_entry(entry)?;
+                    if entry.is_object() {
+                        write_ins += 1;
+                    }
+                    if normalized.contains(&entry) {
+                        return Err(format!("{} is ranked more than once in contest '{}'", entry, self.name));
+                    }
+                    normalized.push(entry);
+                }
+                if write_ins > self.write_in_slots {
+                    return Err(format!("Contest '{}' allows at most {} write-ins", self.name, self.write_in_slots));
+                }
+                Ok(Value::Array(normalized))
+            }
+        }
+    }
+}
+
+/// Parses the contests declared in an election poll's options.
+///
+/// Election polls store their contests as a JSON object in the first option string. Each entry is
+/// either a plain list of candidates (a plurality contest) or an object such as
+/// `{"candidates": [...], "method": "stv", "seats": 3, "surplus_transfer": "meek"}`. Detailed
+/// contests may also set `write_in_slots`, list `qualified_write_ins`, restrict the contest to
+/// voters in certain `jurisdictions`, and declare the `electors` it is apportioned over.
+pub fn parse_contests(options: &[String]) -> Result<Vec<Contest>, String> {
+    let mut contests = Vec::new();
+    for option in options {
+        let parsed: Value = serde_json::from_str(option)
+            .map_err(|e| format!("Election options must be a JSON object of contests: {}", e))?;
+        let map = parsed.as_object()
+            .ok_or_else(|| "Election options must be a JSON object of contests".to_string())?;
+
+        for (name, spec) in map {
+            let contest = if spec.is_array() {
+                let candidates: Vec<String> = serde_json::from_value(spec.clone())
+                    .map_err(|_| format!("Contest '{}' must list candidate names", name))?;
+                Contest::plurality(name, candidates)
+            } else {
+                let mut contest: Contest = serde_json::from_value(spec.clone())
+                    .map_err(|e| format!("Invalid definition for contest '{}': {}", name, e))?;
+                contest.name = name.clone();
+                contest
+            };
+
+            if contest.seats == 0 {
+                return Err(format!("Contest '{}' must fill at least one seat", name));
+            }
+            if contest.method == ContestMethod::Stv && contest.candidates.len() < contest.seats as usize {
+                return Err(format!("Contest '{}' has fewer candidates than seats", name));
+            }
+            if contest.electors.is_some() && contest.method != ContestMethod::Plurality {
+                return Err(format!("Contest '{}' apportions electors and must be a plurality contest", name));
+            }
+            contests.push(contest);
+        }
+    }
+    Ok(contests)
+}
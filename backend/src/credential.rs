@@ -0,0 +1,117 @@
+use num_bigint_dig::{BigUint, RandBigInt, RandPrime};
+use num_integer::Integer;
+use num_traits::One;
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use crate::crypto::{self, hex_biguint};
+
+/// Bits in the RSA modulus of a poll's credential signing key.
+pub const CREDENTIAL_KEY_BITS: usize = 2048;
+
+/// Public exponent of credential signing keys.
+const PUBLIC_EXPONENT: u32 = 65537;
+
+/// Field that identifies an anonymous ballot on the chain in place of a voter id.
+pub const NULLIFIER_FIELD: &str = "nullifier";
+
+/// The public half of a poll's credential key, which voters blind their tokens with and anyone
+/// can verify credentials against.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CredentialPublicKey {
+    #[serde(with = "hex_biguint")]
+    pub n: BigUint,
+    #[serde(with = "hex_biguint")]
+    pub e: BigUint,
+}
+
+/// An RSA key the server signs blinded ballot credentials for one poll with.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CredentialKey {
+    #[serde(flatten)]
+    pub public: CredentialPublicKey,
+    #[serde(with = "hex_biguint")]
+    pub d: BigUint,
+}
+
+/// A credential presented with an anonymous ballot: a token the voter chose and the server's
+/// unblinded signature on it, both hexadecimal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Credential {
+    pub token: String,
+    pub signature: String,
+}
+
+impl CredentialKey {
+    /// Generates a key with a modulus of `bits` bits.
+    pub fn generate(bits: usize) -> Self {
+        let mut rng = rand::thread_rng();
+        let e = BigUint::from(PUBLIC_EXPONENT);
+        loop {
+            let p = rng.gen_prime(bits / 2);
+            let q = rng.gen_prime(bits - bits / 2);
+            if p == q {
+                continue;
+            }
+            let lambda = (&p - 1u32).lcm(&(&q - 1u32));
+            let Some(d) = crypto::mod_inverse(&e, &lambda) else { continue };
+            return CredentialKey { public: CredentialPublicKey { n: p * q, e: e.clone() }, d };
+        }
+    }
+
+    /// Signs a blinded token. The server learns nothing about the token it signs.
+    pub fn sign_blinded(&self, blinded: &BigUint) -> Result<BigUint, String> {
+        if *blinded <= BigUint::one() || *blinded >= self.public.n {
+            return Err("Blinded token must be between 2 and the key modulus".to_string());
+        }
+        Ok(blinded.modpow(&self.d, &self.public.n))
+    }
+}
+
+impl CredentialPublicKey {
+    /// Full-domain hash of a token, bound to the poll and this key.
+    pub fn token_digest(&self, poll_id: &str, token: &str) -> BigUint {
+        crypto::hash_to_below(&self.n, &[b"ballot-credential", poll_id.as_bytes(), &self.n.to_bytes_be(), token.as_bytes()])
+    }
+
+    /// Whether `signature` is this key's signature on `token` for the poll.
+    pub fn verify(&self, poll_id: &str, credential: &Credential) -> bool {
+        let Some(signature) = crypto::parse_hex(&credential.signature) else { return false };
+        if credential.token.is_empty() || signature >= self.n {
+            return false;
+        }
+        signature.modpow(&self.e, &self.n) == self.token_digest(poll_id, &credential.token)
+    }
+
+    /// Blinds a token for signing, returning the blinded token and the blinding factor the
+    /// voter keeps to unblind the signature. Run by the voter's client.
+    pub fn blind(&self, poll_id: &str, token: &str) -> (BigUint, BigUint) {
+        let mut rng = rand::thread_rng();
+        let digest = self.token_digest(poll_id, token);
+        loop {
+            let factor = rng.gen_biguint_below(&self.n);
+            if factor <= BigUint::one() || crypto::mod_inverse(&factor, &self.n).is_none() {
+                continue;
+            }
+            let blinded = digest * factor.modpow(&self.e, &self.n) % &self.n;
+            return (blinded, factor);
+        }
+    }
+
+    /// Removes the blinding factor from a blind signature, leaving the signature on the token.
+    /// Run by the voter's client.
+    pub fn unblind(&self, blind_signature: &BigUint, factor: &BigUint) -> Option<BigUint> {
+        let inverse = crypto::mod_inverse(factor, &self.n)?;
+        Some(blind_signature * inverse % &self.n)
+    }
+}
+
+/// The nullifier recorded when a credential is spent: a hash of the token, so the token can be
+/// spent only once without the ballot revealing who was issued it.
+pub fn nullifier(poll_id: &str, token: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(b"ballot-credential-nullifier");
+    hasher.update(poll_id.as_bytes());
+    hasher.update([0]);
+    hasher.update(token.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
@@ -0,0 +1,98 @@
+use num_bigint_dig::{BigUint, ModInverse, ToBigInt};
+use num_traits::Zero;
+use sha2::{Digest, Sha256};
+
+/// The inverse of `a` modulo `m`, if `a` and `m` are coprime.
+pub fn mod_inverse(a: &BigUint, m: &BigUint) -> Option<BigUint> {
+    let a = a.to_bigint()?;
+    let inverse = (&a).mod_inverse(m)?;
+    inverse.to_biguint()
+}
+
+/// Hashes the parts of a message to an integer below `modulus`, expanding SHA-256 with a
+/// counter until the digest is longer than the modulus so the result is close to uniform.
+pub fn hash_to_below(modulus: &BigUint, parts: &[&[u8]]) -> BigUint {
+    let wanted = modulus.bits() / 8 + 16;
+    let mut bytes = Vec::with_capacity(wanted + 32);
+    let mut counter: u32 = 0;
+    while bytes.len() < wanted {
+        let mut hasher = Sha256::new();
+        hasher.update(counter.to_be_bytes());
+        for part in parts {
+            hasher.update((part.len() as u64).to_be_bytes());
+            hasher.update(part);
+        }
+        bytes.extend_from_slice(&hasher.finalize());
+        counter += 1;
+    }
+    BigUint::from_bytes_be(&bytes) % modulus
+}
+
+/// Parses a big integer written in hexadecimal.
+pub fn parse_hex(hex: &str) -> Option<BigUint> {
+    let hex = hex.trim();
+    let hex = hex.strip_prefix("0x").unwrap_or(hex);
+    if hex.is_empty() {
+        return None;
+    }
+    BigUint::parse_bytes(hex.as_bytes(), 16)
+}
+
+/// Writes a big integer in lowercase hexadecimal.
+pub fn to_hex(value: &BigUint) -> String {
+    if value.is_zero() {
+        return "0".to_string();
+    }
+    value.to_str_radix(16)
+}
+
+/// Serializes big integers as hexadecimal strings, for use with `#[serde(with = "...")]`.
+pub mod hex_biguint {
+    use num_bigint_dig::BigUint;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &BigUint, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&super::to_hex(value))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BigUint, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        super::parse_hex(&hex).ok_or_else(|| D::Error::custom(format!("'{}' is not a hexadecimal integer", hex)))
+    }
+}
+
+/// Serializes lists of big integers as lists of hexadecimal strings.
+pub mod hex_biguint_vec {
+    use num_bigint_dig::BigUint;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(values: &[BigUint], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(values.iter().map(super::to_hex))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<BigUint>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|hex| super::parse_hex(hex).ok_or_else(|| D::Error::custom(format!("'{}' is not a hexadecimal integer", hex))))
+            .collect()
+    }
+}
+
+/// Serializes optional big integers as hexadecimal strings or null.
+pub mod hex_biguint_option {
+    use num_bigint_dig::BigUint;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &Option<BigUint>, serializer: S) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => serializer.serialize_some(&super::to_hex(value)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<BigUint>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|hex| super::parse_hex(&hex).ok_or_else(|| D::Error::custom(format!("'{}' is not a hexadecimal integer", hex))))
+            .transpose()
+    }
+}
@@ -15,7 +15,11 @@ pub struct ElectionBlock {
 
 impl ElectionBlock {
     pub fn new(index: u32, transactions: Value, previous_hash: String) -> Self {
-        let timestamp = Utc::now().timestamp_millis();
+        Self::new_at(index, transactions, previous_hash, Utc::now().timestamp_millis())
+    }
+
+    /// Creates a block stamped with the given time in milliseconds (used when replaying stored votes).
+    pub fn new_at(index: u32, transactions: Value, previous_hash: String, timestamp: i64) -> Self {
         let hash = Self::calculate_hash(index, timestamp, &transactions, &previous_hash);
 
         ElectionBlock {
@@ -1,14 +1,50 @@
 use serde::{Serialize, Deserialize};
 use serde_json::Value;
-use std::collections::HashMap;
+use chrono::Utc;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use crate::apportionment::{apportion, ElectoralResult};
+use crate::ballot::{is_abstention, is_blank_selection, superseded_block, BallotStatus, Turnout, BALLOT_STATUS_FIELD, BALLOT_TAG_FIELD, SUPERSEDES_FIELD};
+use crate::contest::{write_in_text, Contest, ContestMethod, INVALID_WRITE_IN};
 use crate::election_block::ElectionBlock;
+use crate::stv::{count_stv_with_tie_break, StvResult};
+use crate::statistics::NO_SELECTION;
+use crate::timeline::{self, Bucket, TimelinePoint};
+use crate::tie_break::{TieBreakPolicy, TieBreakRecord, TieBreaker, TIE_BREAK_RECORD};
+use crate::credential::NULLIFIER_FIELD;
+use crate::encrypted_tally::{ENCRYPTED_FIELD, PROOFS_FIELD};
+use crate::commit_reveal::{REVEALS_FIELD, SALT_FIELD};
 use sqlx::postgres::PgRow;
 
+/// Field that marks a block as a non-ballot record rather than a vote.
+pub const RECORD_TYPE_FIELD: &str = "record_type";
+
+/// Fields of an election ballot that describe the ballot rather than a contest selection.
+pub const BALLOT_METADATA_FIELDS: [&str; 14] = [
+    "voter_id", NULLIFIER_FIELD, ENCRYPTED_FIELD, PROOFS_FIELD, "state", "district", "poll_type", "candidate", "contest", BALLOT_STATUS_FIELD, SUPERSEDES_FIELD,
+    REVEALS_FIELD, SALT_FIELD, BALLOT_TAG_FIELD,
+];
+
+/// Record type for an official's resolution of a write-in.
+pub const ADJUDICATION_RECORD: &str = "adjudication";
+
+/// How often a write-in appeared in a contest and how it has been adjudicated so far.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WriteInTally {
+    pub ballots: u32,
+    pub resolution: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ElectionBlockchain {
     pub chain: Vec<ElectionBlock>,
 }
 
+impl Default for ElectionBlockchain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ElectionBlockchain {
     /// Creates a new election blockchain with a genesis block.
     pub fn new() -> Self {
@@ -30,8 +66,13 @@ impl ElectionBlockchain {
 
     /// Adds a vote by creating a new block for each vote.
     pub fn add_vote(&mut self, vote: Value) -> Result<(), String> {
+        self.add_vote_at(vote, Utc::now().timestamp_millis())
+    }
+
+    /// Adds a vote in a block stamped with the given time in milliseconds.
+    pub fn add_vote_at(&mut self, vote: Value, timestamp: i64) -> Result<(), String> {
         if let Some(last_block) = self.chain.last() {
-            let new_block = ElectionBlock::new(last_block.index + 1, vote, last_block.hash.clone());
+            let new_block = ElectionBlock::new_at(last_block.index + 1, vote, last_block.hash.clone(), timestamp);
             self.chain.push(new_block);
             Ok(())
         } else {
@@ -51,42 +92,280 @@ impl ElectionBlockchain {
         true
     }
 
-    /// Returns a HashMap of vote counts for each contest and candidate.
-    pub fn get_vote_counts(&self) -> HashMap<String, HashMap<String, u32>> {
-        let mut counts: HashMap<String, HashMap<String, u32>> = HashMap::new();
-        
-        // Fields to ignore in vote processing
-        let excluded_fields = vec!["voter_id", "state", "poll_type", "candidate", "contest"];
-        
-        for block in self.chain.iter().skip(1) {  // Skip genesis block
-            if let Some(vote_obj) = block.transactions.as_object() {
-                // Process each field in the vote as a potential contest
-                for (key, value) in vote_obj {
-                    // Skip excluded fields
-                    if excluded_fields.contains(&key.as_str()) {
-                        continue;
-                    }
-                    
-                    // Process this field as a contest if it has a string value
-                    if let Some(candidate) = value.as_str() {
-                        counts.entry(key.to_string())
-                            .or_insert_with(HashMap::new)
-                            .entry(candidate.to_string())
-                            .and_modify(|c| *c += 1)
-                            .or_insert(1);
-                    }
-                }
+    /// Iterates over the blocks holding ballots that currently count, skipping the genesis block,
+    /// non-ballot records (such as write-in adjudications) and ballots superseded by a recast.
+    pub fn ballots(&self) -> impl Iterator<Item = &ElectionBlock> {
+        let superseded: HashSet<u64> = self.chain.iter()
+            .filter_map(|block| superseded_block(&block.transactions))
+            .collect();
+        self.chain.iter()
+            .skip(1)
+            .filter(|block| block.transactions.get(RECORD_TYPE_FIELD).is_none())
+            .filter(move |block| !superseded.contains(&(block.index as u64)))
+    }
+
+    /// Number of ballots on the chain.
+    pub fn ballot_count(&self) -> usize {
+        self.ballots().count()
+    }
+
+    /// Iterates over the non-ballot records of a given type, in chain order.
+    pub fn records<'a>(&'a self, record_type: &'a str) -> impl Iterator<Item = &'a Value> + 'a {
+        self.chain.iter()
+            .skip(1)
+            .map(|block| &block.transactions)
+            .filter(move |tx| tx.get(RECORD_TYPE_FIELD).and_then(|t| t.as_str()) == Some(record_type))
+    }
+
+    /// Searches for the latest record of a given type carrying a ballot tag, returning its block
+    /// index and hash with the record.
+    pub fn find_tagged_record(&self, record_type: &str, ballot_tag: &str) -> Option<(u32, String, &Value)> {
+        self.chain.iter()
+            .skip(1)
+            .rev()
+            .find(|block| {
+                block.transactions.get(RECORD_TYPE_FIELD).and_then(|t| t.as_str()) == Some(record_type)
+                    && block.transactions.get(BALLOT_TAG_FIELD).and_then(|v| v.as_str()) == Some(ballot_tag)
+            })
+            .map(|block| (block.index, block.hash.clone(), &block.transactions))
+    }
+
+    /// Returns the current resolution of every adjudicated write-in, keyed by
+    /// (contest, normalized write-in). Later adjudications replace earlier ones.
+    pub fn get_write_in_adjudications(&self) -> HashMap<(String, String), String> {
+        let mut adjudications = HashMap::new();
+        for record in self.records(ADJUDICATION_RECORD) {
+            let contest = record.get("contest").and_then(|v| v.as_str());
+            let write_in = record.get("write_in").and_then(|v| v.as_str());
+            let resolution = record.get("resolution").and_then(|v| v.as_str());
+            if let (Some(contest), Some(write_in), Some(resolution)) = (contest, write_in, resolution) {
+                adjudications.insert((contest.to_string(), write_in.to_string()), resolution.to_string());
+            }
+        }
+        adjudications
+    }
+
+    /// Resolves a ballot entry to the candidate it counts for: a listed candidate as-is, or a
+    /// write-in once adjudicated to a qualified candidate.
+    fn resolve_entry(contest: &str, entry: &Value, adjudications: &HashMap<(String, String), String>) -> Option<String> {
+        if let Some(candidate) = entry.as_str() {
+            return Some(candidate.to_string());
+        }
+        let write_in = write_in_text(entry)?;
+        adjudications.get(&(contest.to_string(), write_in.to_string()))
+            .filter(|resolution| resolution.as_str() != INVALID_WRITE_IN)
+            .cloned()
+    }
+
+    /// The (contest, candidate) selections a ballot counts for in plurality tallies. Ranked
+    /// contests are counted separately, and legacy single-contest votes with a bare `candidate`
+    /// field count under `"default"`.
+    fn selections(transactions: &Value, adjudications: &HashMap<(String, String), String>) -> Vec<(String, String)> {
+        let Some(vote_obj) = transactions.as_object() else { return Vec::new() };
+        let mut selections: Vec<(String, String)> = vote_obj.iter()
+            .filter(|(key, value)| !BALLOT_METADATA_FIELDS.contains(&key.as_str()) && !value.is_array())
+            .filter_map(|(key, value)| Some((key.clone(), Self::resolve_entry(key, value, adjudications)?)))
+            .collect();
+        if let Some(candidate) = vote_obj.get("candidate").and_then(|v| v.as_str()) {
+            selections.push(("default".to_string(), candidate.to_string()));
+        }
+        selections
+    }
+
+    /// Returns vote counts for each contest and candidate, both sorted by name so iteration (and
+    /// any tie among leaders) is deterministic.
+    pub fn get_vote_counts(&self) -> BTreeMap<String, BTreeMap<String, u32>> {
+        let mut counts: BTreeMap<String, BTreeMap<String, u32>> = BTreeMap::new();
+        let adjudications = self.get_write_in_adjudications();
+        for block in self.ballots() {
+            for (contest, candidate) in Self::selections(&block.transactions, &adjudications) {
+                *counts.entry(contest).or_default().entry(candidate).or_insert(0) += 1;
             }
         }
-        
         counts
     }
 
-    pub fn get_vote_counts_by_state(&self) -> HashMap<String, HashMap<String, u32>> {
-        let mut result: HashMap<String, HashMap<String, u32>> = HashMap::new();
-        let excluded_fields = vec!["voter_id", "state", "poll_type", "candidate", "contest"];
+    /// Pairs the choices each current ballot made in two contests, using `NO_SELECTION` where a
+    /// ballot made no countable choice in one of them.
+    pub fn get_selection_pairs(&self, first: &str, second: &str) -> Vec<(String, String)> {
+        let adjudications = self.get_write_in_adjudications();
+        self.ballots()
+            .map(|block| {
+                let selections = Self::selections(&block.transactions, &adjudications);
+                let choice = |contest: &str| selections.iter()
+                    .find(|(c, _)| c == contest)
+                    .map_or_else(|| NO_SELECTION.to_string(), |(_, candidate)| candidate.clone());
+                (choice(first), choice(second))
+            })
+            .collect()
+    }
+
+    /// Cumulative vote counts per contest at the end of each time bucket, replayed from block
+    /// timestamps. A recast ballot moves the voter's selections at the time it was cast.
+    pub fn timeline(&self, bucket: Bucket) -> Vec<TimelinePoint<BTreeMap<String, BTreeMap<String, u64>>>> {
+        let adjudications = self.get_write_in_adjudications();
+        let selections_at = |index: u64| {
+            self.chain.get(index as usize)
+                .map(|block| Self::selections(&block.transactions, &adjudications))
+                .unwrap_or_default()
+        };
+        let events = self.chain.iter()
+            .skip(1)
+            .filter(|block| block.transactions.get(RECORD_TYPE_FIELD).is_none())
+            .map(|block| {
+                let removed = superseded_block(&block.transactions).map(&selections_at).unwrap_or_default();
+                (block.timestamp, (removed, Self::selections(&block.transactions, &adjudications)))
+            })
+            .collect();
+        timeline::cumulative(bucket, BTreeMap::new(), events, |counts, (removed, added)| {
+            for (contest, candidate) in removed {
+                if let Some(count) = counts.get_mut(&contest).and_then(|c: &mut BTreeMap<String, u64>| c.get_mut(&candidate)) {
+                    *count = count.saturating_sub(1);
+                }
+            }
+            for (contest, candidate) in added {
+                *counts.entry(contest).or_default().entry(candidate).or_insert(0) += 1;
+            }
+        })
+    }
+
+    /// Collects the ranked ballots cast in a contest, in chain order, with adjudicated
+    /// write-ins replaced by their candidate and unresolved or invalid write-ins dropped.
+    pub fn get_ranked_ballots(&self, contest: &str) -> Vec<Vec<String>> {
+        let adjudications = self.get_write_in_adjudications();
+        let mut ballots = Vec::new();
+        for block in self.ballots() {
+            if let Some(ranking) = block.transactions.get(contest).and_then(|v| v.as_array()) {
+                let ballot: Vec<String> = ranking.iter()
+                    .filter_map(|entry| Self::resolve_entry(contest, entry, &adjudications))
+                    .collect();
+                ballots.push(ballot);
+            }
+        }
+        ballots
+    }
+
+    /// Runs an STV count for every STV contest in `contests`, keyed by contest name.
+    pub fn get_stv_results(&self, contests: &[Contest]) -> BTreeMap<String, StvResult> {
+        self.get_stv_results_with_tie_break(contests, TieBreakPolicy::default())
+    }
+
+    /// Runs an STV count for every STV contest, settling ties with `policy` and the tie-break
+    /// decisions recorded on the chain.
+    pub fn get_stv_results_with_tie_break(&self, contests: &[Contest], policy: TieBreakPolicy) -> BTreeMap<String, StvResult> {
+        let records = self.get_tie_break_records();
+        contests.iter()
+            .filter(|contest| contest.method == ContestMethod::Stv)
+            .map(|contest| {
+                let ballots = self.get_ranked_ballots(&contest.name);
+                let tie_breaker = TieBreaker::new(policy, Some(&contest.name), &records);
+                let result = count_stv_with_tie_break(
+                    &contest.eligible_candidates(), &ballots, contest.seats, contest.surplus_transfer, &tie_breaker,
+                );
+                (contest.name.clone(), result)
+            })
+            .collect()
+    }
+
+    /// Returns the tie-break decisions recorded on the chain, in chain order.
+    pub fn get_tie_break_records(&self) -> Vec<TieBreakRecord> {
+        self.records(TIE_BREAK_RECORD)
+            .filter_map(|record| serde_json::from_value(record.clone()).ok())
+            .collect()
+    }
+
+    /// Running tallies of `candidates` in a plurality contest after each ballot in chain order,
+    /// oldest first, used to compare tied candidates at earlier points in the count.
+    pub fn running_tallies(&self, contest: &str, candidates: &[String]) -> Vec<BTreeMap<String, f64>> {
+        let adjudications = self.get_write_in_adjudications();
+        let mut running: BTreeMap<String, f64> = candidates.iter().map(|c| (c.clone(), 0.0)).collect();
+        let mut history = Vec::new();
+        for block in self.ballots() {
+            let Some(selection) = block.transactions.get(contest) else { continue };
+            if let Some(tally) = Self::resolve_entry(contest, selection, &adjudications).and_then(|c| running.get_mut(&c)) {
+                *tally += 1.0;
+                history.push(running.clone());
+            }
+        }
+        history
+    }
+
+    /// Summarizes every write-in cast, per contest, with its ballot count and current resolution.
+    pub fn get_write_in_report(&self) -> BTreeMap<String, BTreeMap<String, WriteInTally>> {
+        let adjudications = self.get_write_in_adjudications();
+        let mut report: BTreeMap<String, BTreeMap<String, WriteInTally>> = BTreeMap::new();
+        for block in self.ballots() {
+            let Some(vote_obj) = block.transactions.as_object() else { continue };
+            for (contest, value) in vote_obj {
+                let entries: Vec<&Value> = match value.as_array() {
+                    Some(ranking) => ranking.iter().collect(),
+                    None => vec![value],
+                };
+                for write_in in entries.into_iter().filter_map(write_in_text) {
+                    let tally = report.entry(contest.clone())
+                        .or_default()
+                        .entry(write_in.to_string())
+                        .or_insert_with(|| WriteInTally {
+                            ballots: 0,
+                            resolution: adjudications.get(&(contest.clone(), write_in.to_string())).cloned(),
+                        });
+                    tally.ballots += 1;
+                }
+            }
+        }
+        report
+    }
+
+    /// Counts every ballot on the chain by status.
+    pub fn get_turnout(&self) -> Turnout {
+        let mut turnout = Turnout::default();
+        for block in self.ballots() {
+            turnout.record(BallotStatus::of_record(&block.transactions));
+        }
+        turnout
+    }
+
+    /// Reports, for each contest, how many ballots were cast against how many carried a valid
+    /// vote in it. On a valid ballot, an omitted or empty contest counts as blank, an
+    /// `{"abstain": true}` selection as an abstention, and a write-in adjudicated invalid as spoiled.
+    pub fn get_contest_turnout(&self, contests: &[Contest]) -> BTreeMap<String, Turnout> {
+        let adjudications = self.get_write_in_adjudications();
+        let mut report: BTreeMap<String, Turnout> = contests.iter()
+            .map(|contest| (contest.name.clone(), Turnout::default()))
+            .collect();
+        for block in self.ballots() {
+            let ballot_status = BallotStatus::of_record(&block.transactions);
+            for contest in contests {
+                let status = match block.transactions.get(&contest.name) {
+                    _ if ballot_status != BallotStatus::Valid => ballot_status,
+                    None => BallotStatus::Blank,
+                    Some(selection) if is_blank_selection(selection) => BallotStatus::Blank,
+                    Some(selection) if is_abstention(selection) => BallotStatus::Abstain,
+                    Some(selection) if write_in_text(selection).is_some() => {
+                        let resolution = write_in_text(selection)
+                            .and_then(|w| adjudications.get(&(contest.name.clone(), w.to_string())));
+                        if resolution.map(String::as_str) == Some(INVALID_WRITE_IN) {
+                            BallotStatus::Spoiled
+                        } else {
+                            BallotStatus::Valid
+                        }
+                    },
+                    Some(_) => BallotStatus::Valid,
+                };
+                if let Some(turnout) = report.get_mut(&contest.name) {
+                    turnout.record(status);
+                }
+            }
+        }
+        report
+    }
+
+    pub fn get_vote_counts_by_state(&self) -> BTreeMap<String, BTreeMap<String, u32>> {
+        let mut result: BTreeMap<String, BTreeMap<String, u32>> = BTreeMap::new();
+        let adjudications = self.get_write_in_adjudications();
+        let excluded_fields = BALLOT_METADATA_FIELDS;
         
-        for block in self.chain.iter().skip(1) {
+        for block in self.ballots() {
             if let Some(obj) = block.transactions.as_object() {
                 // Get the state from the vote
                 let state = obj.get("state").and_then(|v| v.as_str()).unwrap_or("Unknown");
@@ -94,15 +373,15 @@ impl ElectionBlockchain {
                 // Process each contest in the vote
                 for (key, value) in obj {
                     // Skip excluded fields
-                    if excluded_fields.contains(&key.as_str()) {
+                    if excluded_fields.contains(&key.as_str()) || value.is_array() {
                         continue;
                     }
                     
-                    if let Some(candidate) = value.as_str() {
+                    if let Some(candidate) = Self::resolve_entry(key, value, &adjudications) {
                         // Store votes by state and key (contest name)
                         result
                             .entry(state.to_string())
-                            .or_insert_with(|| HashMap::new())
+                            .or_default()
                             .entry(format!("{}: {}", key, candidate))
                             .and_modify(|count| *count += 1)
                             .or_insert(1);
@@ -114,17 +393,40 @@ impl ElectionBlockchain {
         result
     }
 
-    /// Searches the blockchain for a vote by a given voter ID.
-    pub fn find_vote(&self, voter_id: &str) -> Option<(u32, String)> {
-        for block in self.chain.iter().skip(1) {
-            if let Some(vote_obj) = block.transactions.as_object() {
-                if let Some(voter_val) = vote_obj.get("voter_id") {
-                    if voter_val.as_str() == Some(voter_id) {
-                        return Some((block.index, block.hash.clone()));
-                    }
-                }
+    /// Counts a contest's votes grouped by a jurisdiction field stamped on each ballot
+    /// (`"state"` or `"district"`). Ballots without the field are left out.
+    pub fn get_contest_counts_by(&self, contest: &str, field: &str) -> BTreeMap<String, BTreeMap<String, u64>> {
+        let mut result: BTreeMap<String, BTreeMap<String, u64>> = BTreeMap::new();
+        let adjudications = self.get_write_in_adjudications();
+        for block in self.ballots() {
+            let Some(unit) = block.transactions.get(field).and_then(|v| v.as_str()) else { continue };
+            let Some(entry) = block.transactions.get(contest) else { continue };
+            if let Some(candidate) = Self::resolve_entry(contest, entry, &adjudications) {
+                *result.entry(unit.to_string()).or_default().entry(candidate).or_insert(0) += 1;
             }
         }
-        None
+        result
+    }
+
+    /// Apportions the electors of every contest that declares them.
+    pub fn get_electoral_results(&self, contests: &[Contest]) -> BTreeMap<String, ElectoralResult> {
+        contests.iter()
+            .filter_map(|contest| {
+                let electors = contest.electors.as_ref()?;
+                let result = apportion(
+                    electors.table(),
+                    &self.get_contest_counts_by(&contest.name, "state"),
+                    &self.get_contest_counts_by(&contest.name, "district"),
+                );
+                Some((contest.name.clone(), result))
+            })
+            .collect()
+    }
+
+    /// Searches for the current ballot carrying a ballot tag, returning its block index and hash.
+    pub fn find_tagged_ballot(&self, ballot_tag: &str) -> Option<(u32, String)> {
+        self.ballots()
+            .find(|block| block.transactions.get(BALLOT_TAG_FIELD).and_then(|t| t.as_str()) == Some(ballot_tag))
+            .map(|block| (block.index, block.hash.clone()))
     }
 }
\ No newline at end of file
@@ -26,6 +26,7 @@ pub async fn init_election_poll(pm: &mut PollManager) {
             options: vec![election_options],
             is_public: true,
             poll_type: Some("election".to_string()),
+            ..Default::default()
         };
 
         // Attempt to create a new poll; a poll_id (UUID) will be auto-generated.
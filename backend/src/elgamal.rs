@@ -0,0 +1,167 @@
+use std::sync::OnceLock;
+use num_bigint_dig::{BigUint, RandBigInt};
+use num_traits::{One, Zero};
+use serde::{Serialize, Deserialize};
+use crate::crypto::{self, hex_biguint};
+
+/// The 2048-bit MODP group of RFC 3526 (group 14). `p` is a safe prime and `g = 2` generates
+/// the subgroup of prime order `q = (p - 1) / 2`, in which ciphertexts live.
+const MODP_2048_PRIME: &str = concat!(
+    "FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74020BBEA63B139B22514A08798E3404DD",
+    "EF9519B3CD3A431B302B0A6DF25F14374FE1356D6D51C245E485B576625E7EC6F44C42E9A637ED6B0BFF5CB6F406B7ED",
+    "EE386BFB5A899FA5AE9F24117C4B1FE649286651ECE45B3DC2007CB8A163BF0598DA48361C55D39A69163FA8FD24CF5F",
+    "83655D23DCA3AD961C62F356208552BB9ED529077096966D670C354E4ABC9804F1746C08CA18217C32905E462E36CE3B",
+    "E39E772C180E86039B2783A2EC07A28FB5C55DF06F4C52C9DE2BCBF6955817183995497CEA956AE515D2261898FA0510",
+    "15728E5A8AACAA68FFFFFFFFFFFFFFFF",
+);
+
+/// Parameters of the group that election keys and ciphertexts belong to.
+#[derive(Debug, Clone)]
+pub struct Group {
+    pub p: BigUint,
+    pub q: BigUint,
+    pub g: BigUint,
+}
+
+/// The group every encrypted poll uses.
+pub fn group() -> &'static Group {
+    static GROUP: OnceLock<Group> = OnceLock::new();
+    GROUP.get_or_init(|| {
+        let p = crypto::parse_hex(MODP_2048_PRIME).expect("RFC 3526 prime is valid hexadecimal");
+        let q = (&p - 1u32) >> 1;
+        Group { p, q, g: BigUint::from(2u32) }
+    })
+}
+
+impl Group {
+    /// Whether `value` is an element of the order-`q` subgroup.
+    pub fn is_element(&self, value: &BigUint) -> bool {
+        !value.is_zero() && *value < self.p && value.modpow(&self.q, &self.p).is_one()
+    }
+
+    /// A uniformly random exponent in `[1, q)`.
+    pub fn random_exponent(&self) -> BigUint {
+        rand::thread_rng().gen_biguint_range(&BigUint::one(), &self.q)
+    }
+
+    /// `g` raised to `exponent`.
+    pub fn pow_g(&self, exponent: &BigUint) -> BigUint {
+        self.g.modpow(exponent, &self.p)
+    }
+}
+
+/// An exponential-ElGamal public key `y = g^x`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicKey {
+    #[serde(with = "hex_biguint")]
+    pub y: BigUint,
+}
+
+/// An exponential-ElGamal key pair. The secret exponent `x` decrypts tallies.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyPair {
+    #[serde(flatten)]
+    pub public: PublicKey,
+    #[serde(with = "hex_biguint")]
+    pub x: BigUint,
+}
+
+/// An exponential-ElGamal ciphertext `(a, b) = (g^r, g^m * y^r)` of a small integer `m`.
+/// Ciphertexts multiply component-wise to a ciphertext of the sum of their plaintexts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ciphertext {
+    #[serde(with = "hex_biguint")]
+    pub a: BigUint,
+    #[serde(with = "hex_biguint")]
+    pub b: BigUint,
+}
+
+impl KeyPair {
+    /// Generates a key pair with a random secret exponent.
+    pub fn generate() -> Self {
+        let group = group();
+        let x = group.random_exponent();
+        KeyPair { public: PublicKey { y: group.pow_g(&x) }, x }
+    }
+
+    /// The factor `a^x` that strips the key's mask from a ciphertext.
+    pub fn decryption_factor(&self, ciphertext: &Ciphertext) -> BigUint {
+        ciphertext.a.modpow(&self.x, &group().p)
+    }
+
+    /// Decrypts a ciphertext whose plaintext is at most `max`.
+    pub fn decrypt(&self, ciphertext: &Ciphertext, max: u64) -> Option<u64> {
+        ciphertext.plaintext_with(&self.decryption_factor(ciphertext), max)
+    }
+}
+
+impl PublicKey {
+    /// Encrypts `m` with fresh randomness. Run by the voter's client.
+    pub fn encrypt(&self, m: u64) -> Ciphertext {
+        self.encrypt_with(m, &group().random_exponent()).0
+    }
+
+    /// Encrypts `m` with the randomness `r`, returning the ciphertext and `r`.
+    pub fn encrypt_with(&self, m: u64, r: &BigUint) -> (Ciphertext, BigUint) {
+        let group = group();
+        let a = group.pow_g(r);
+        let b = group.pow_g(&BigUint::from(m)) * self.y.modpow(r, &group.p) % &group.p;
+        (Ciphertext { a, b }, r.clone())
+    }
+
+    /// Encrypts the group element `message` itself rather than `g^m`, with the randomness `r`.
+    /// Such ciphertexts cannot be summed, but decrypt back to any encoded message.
+    pub fn encrypt_element(&self, message: &BigUint, r: &BigUint) -> Ciphertext {
+        let group = group();
+        Ciphertext { a: group.pow_g(r), b: message * self.y.modpow(r, &group.p) % &group.p }
+    }
+}
+
+impl Ciphertext {
+    /// The encryption of zero with no randomness, the starting point of a homomorphic sum.
+    pub fn identity() -> Self {
+        Ciphertext { a: BigUint::one(), b: BigUint::one() }
+    }
+
+    /// The ciphertext of the sum of both plaintexts.
+    pub fn combine(&self, other: &Ciphertext) -> Self {
+        let p = &group().p;
+        Ciphertext { a: &self.a * &other.a % p, b: &self.b * &other.b % p }
+    }
+
+    /// Whether both components are group elements.
+    pub fn is_valid(&self) -> bool {
+        let group = group();
+        group.is_element(&self.a) && group.is_element(&self.b)
+    }
+
+    /// The same plaintext under fresh randomness `s`: `(a * g^s, b * y^s)`.
+    pub fn reencrypt(&self, key: &PublicKey, s: &BigUint) -> Self {
+        self.combine(&key.encrypt_element(&BigUint::one(), s))
+    }
+
+    /// Recovers an encrypted group element given the decryption factor `a^x`.
+    pub fn message_with(&self, factor: &BigUint) -> Option<BigUint> {
+        let p = &group().p;
+        Some(&self.b * crypto::mod_inverse(factor, p)? % p)
+    }
+
+    /// Recovers the plaintext, at most `max`, given the decryption factor `a^x`.
+    pub fn plaintext_with(&self, factor: &BigUint, max: u64) -> Option<u64> {
+        discrete_log(&self.message_with(factor)?, max)
+    }
+}
+
+/// Finds `m <= max` with `g^m = target` by stepping through powers of `g`. Tallies are bounded
+/// by the number of ballots, so the search is short.
+pub fn discrete_log(target: &BigUint, max: u64) -> Option<u64> {
+    let group = group();
+    let mut power = BigUint::one();
+    for m in 0..=max {
+        if power == *target {
+            return Some(m);
+        }
+        power = power * &group.g % &group.p;
+    }
+    None
+}
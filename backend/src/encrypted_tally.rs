@@ -0,0 +1,358 @@
+use std::collections::BTreeMap;
+use num_bigint_dig::BigUint;
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+use crate::contest::{parse_contests, ContestMethod};
+use crate::crypto::hex_biguint;
+use crate::elgamal::{group, Ciphertext, PublicKey};
+use crate::poll_manager::{Poll, PollInput};
+use crate::proofs::RangeProof;
+use crate::trustee::PartialTally;
+
+/// Field of an encrypted ballot holding its ciphertexts.
+pub const ENCRYPTED_FIELD: &str = "encrypted";
+
+/// Field of an encrypted ballot holding the proofs that its ciphertexts are well-formed.
+pub const PROOFS_FIELD: &str = "proofs";
+
+/// Record type for the decrypted tally of an encrypted poll.
+pub const DECRYPTED_TALLY_RECORD: &str = "decrypted_tally";
+
+/// Contest name the options of a poll without contests are tallied under.
+pub const DEFAULT_CONTEST: &str = "default";
+
+/// One ciphertext per option, keyed by contest and then option.
+pub type EncryptedSelections = BTreeMap<String, BTreeMap<String, Ciphertext>>;
+
+/// The value and randomness behind one of a ballot's ciphertexts, published when a prepared
+/// ballot is challenged so that anyone can re-encrypt it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SelectionOpening {
+    pub m: u64,
+    #[serde(with = "hex_biguint")]
+    pub r: BigUint,
+}
+
+/// Openings of every ciphertext of a ballot, keyed by contest and then option.
+pub type BallotOpenings = BTreeMap<String, BTreeMap<String, SelectionOpening>>;
+
+/// Proofs that a ballot's ciphertexts in one contest are well-formed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContestProofs {
+    /// Per option, that its ciphertext encrypts 0 or 1.
+    pub selections: BTreeMap<String, RangeProof>,
+    /// That the product of the contest's ciphertexts encrypts no more than the number of
+    /// selections the contest allows.
+    pub total: RangeProof,
+}
+
+/// The contests and options an encrypted poll's ballots carry a ciphertext for, and how many
+/// options each contest allows a ballot to select. Ballots of a poll without contests map
+/// options to ciphertexts and proofs directly; election ballots nest them under each contest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncryptedLayout {
+    pub contests: BTreeMap<String, Vec<String>>,
+    pub selection_limits: BTreeMap<String, u64>,
+    pub nested: bool,
+}
+
+/// The homomorphic product of every current ballot's ciphertexts, per contest and option.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EncryptedTally {
+    pub ballots: u64,
+    pub aggregates: EncryptedSelections,
+}
+
+/// A tally decrypted after the poll closed, recorded on the chain with the aggregates it was
+/// decrypted from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecryptedTally {
+    pub ballots: u64,
+    pub aggregates: EncryptedSelections,
+    pub counts: BTreeMap<String, BTreeMap<String, u64>>,
+    /// For polls held by trustees, the proven partial decryptions the counts were combined
+    /// from, keyed by trustee.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub partial_decryptions: BTreeMap<String, PartialTally>,
+}
+
+impl EncryptedLayout {
+    /// The layout of a poll's encrypted ballots. Only normal polls and elections of
+    /// plurality contests without write-ins or jurisdictions can be encrypted. A normal poll's
+    /// ballot selects one option; an election contest's ballot selects up to one per seat.
+    pub fn for_poll(metadata: &PollInput) -> Result<Self, String> {
+        match metadata.poll_type.as_deref().unwrap_or("normal") {
+            "normal" => Ok(EncryptedLayout {
+                contests: BTreeMap::from([(DEFAULT_CONTEST.to_string(), metadata.options.clone())]),
+                selection_limits: BTreeMap::from([(DEFAULT_CONTEST.to_string(), 1)]),
+                nested: false,
+            }),
+            "election" => {
+                let mut contests = BTreeMap::new();
+                let mut selection_limits = BTreeMap::new();
+                for contest in parse_contests(&metadata.options)? {
+                    if contest.method != ContestMethod::Plurality || contest.write_in_slots > 0 || !contest.jurisdictions.is_empty() {
+                        return Err(format!(
+                            "Contest '{}' cannot be encrypted: only plurality contests without write-ins or jurisdictions can",
+                            contest.name
+                        ));
+                    }
+                    contests.insert(contest.name.clone(), contest.candidates.clone());
+                    selection_limits.insert(contest.name.clone(), u64::from(contest.seats));
+                }
+                Ok(EncryptedLayout { contests, selection_limits, nested: true })
+            },
+            other => Err(format!("{} polls cannot be encrypted", other)),
+        }
+    }
+
+    /// Reads the ciphertexts of an encrypted ballot, which must carry exactly one valid
+    /// ciphertext for every option, proofs under `key` that each encrypts 0 or 1 and that each
+    /// contest's total is within its selection limit, and nothing in plaintext.
+    pub fn parse_ballot(&self, ballot: &Value, key: &PublicKey) -> Result<EncryptedSelections, String> {
+        let obj = ballot.as_object().ok_or("Encrypted ballots must be objects")?;
+        if let Some(field) = obj.keys().find(|field| ![ENCRYPTED_FIELD, PROOFS_FIELD].contains(&field.as_str())) {
+            return Err(format!("Encrypted ballots cannot carry the plaintext field \"{}\"", field));
+        }
+        let by_contest = self.read_ciphertexts(ballot)?;
+        self.verify_proofs(ballot, &by_contest, key)?;
+        Ok(by_contest)
+    }
+
+    /// Checks the ciphertexts and proofs of a ballot read from the chain, where the ballot also
+    /// carries the fields stamped on it at cast time. Ballots without ciphertexts, such as
+    /// abstentions, carry nothing to check.
+    pub fn verify_ballot(&self, ballot: &Value, key: &PublicKey) -> Result<(), String> {
+        if ballot.get(ENCRYPTED_FIELD).is_none() {
+            return Ok(());
+        }
+        let by_contest = self.read_ciphertexts(ballot)?;
+        self.verify_proofs(ballot, &by_contest, key)
+    }
+
+    /// Reads a ballot's ciphertexts, checking there is exactly one valid ciphertext per option.
+    fn read_ciphertexts(&self, ballot: &Value) -> Result<EncryptedSelections, String> {
+        let encrypted = ballot.get(ENCRYPTED_FIELD).ok_or("Ballot carries no ciphertexts")?;
+        let by_contest = self.read_selections(encrypted)?;
+        if by_contest.len() != self.contests.len() {
+            return Err("Ballot must carry ciphertexts for every contest".to_string());
+        }
+        for (contest, options) in &self.contests {
+            let ciphertexts = by_contest.get(contest)
+                .ok_or_else(|| format!("Ballot carries no ciphertexts for contest '{}'", contest))?;
+            if ciphertexts.len() != options.len() || options.iter().any(|option| !ciphertexts.contains_key(option)) {
+                return Err(format!("Ballot must carry one ciphertext per option in contest '{}'", contest));
+            }
+            if ciphertexts.values().any(|ciphertext| !ciphertext.is_valid()) {
+                return Err(format!("Ballot carries a ciphertext outside the group in contest '{}'", contest));
+            }
+        }
+        Ok(by_contest)
+    }
+
+    /// Checks a ballot's proofs against its ciphertexts.
+    fn verify_proofs(&self, ballot: &Value, by_contest: &EncryptedSelections, key: &PublicKey) -> Result<(), String> {
+        let proofs = ballot.get(PROOFS_FIELD).ok_or("Ballot carries no proofs that its ciphertexts are well-formed")?;
+        let proofs = if self.nested {
+            serde_json::from_value::<BTreeMap<String, ContestProofs>>(proofs.clone())
+        } else {
+            serde_json::from_value::<ContestProofs>(proofs.clone())
+                .map(|proofs| BTreeMap::from([(DEFAULT_CONTEST.to_string(), proofs)]))
+        }
+        .map_err(|e| format!("Malformed proofs: {}", e))?;
+
+        for (contest, ciphertexts) in by_contest {
+            let proofs = proofs.get(contest)
+                .ok_or_else(|| format!("Ballot carries no proofs for contest '{}'", contest))?;
+            for (option, ciphertext) in ciphertexts {
+                let valid = proofs.selections.get(option).is_some_and(|proof| proof.verify(key, ciphertext, 1));
+                if !valid {
+                    return Err(format!("Proof that '{}' in contest '{}' encrypts 0 or 1 is missing or invalid", option, contest));
+                }
+            }
+            let limit = self.selection_limits.get(contest).copied().unwrap_or(1);
+            let total = ciphertexts.values().fold(Ciphertext::identity(), |total, ciphertext| total.combine(ciphertext));
+            if !proofs.total.verify(key, &total, limit) {
+                return Err(format!("Proof that contest '{}' has at most {} selections is invalid", contest, limit));
+            }
+        }
+        Ok(())
+    }
+
+    /// Encrypts a ballot selecting `choices` in each contest (the default contest for polls
+    /// without contests) to `key`, with the proofs the poll requires. Run by the voter's client.
+    pub fn encrypt_ballot(&self, key: &PublicKey, choices: &BTreeMap<String, Vec<String>>) -> Result<Value, String> {
+        self.encrypt_ballot_opened(key, choices).map(|(ballot, _)| ballot)
+    }
+
+    /// Encrypts a ballot as [`encrypt_ballot`](Self::encrypt_ballot) does, also returning the
+    /// openings of its ciphertexts.
+    pub fn encrypt_ballot_opened(&self, key: &PublicKey, choices: &BTreeMap<String, Vec<String>>) -> Result<(Value, BallotOpenings), String> {
+        let q = &group().q;
+        let mut encrypted: EncryptedSelections = BTreeMap::new();
+        let mut openings: BallotOpenings = BTreeMap::new();
+        let mut proofs: BTreeMap<String, ContestProofs> = BTreeMap::new();
+        for (contest, options) in &self.contests {
+            let chosen = choices.get(contest).map(Vec::as_slice).unwrap_or_default();
+            if let Some(unknown) = chosen.iter().find(|choice| !options.contains(choice)) {
+                return Err(format!("'{}' is not an option in contest '{}'", unknown, contest));
+            }
+            let limit = self.selection_limits.get(contest).copied().unwrap_or(1);
+            let selected = options.iter().filter(|option| chosen.contains(option)).count() as u64;
+            if selected > limit {
+                return Err(format!("Contest '{}' allows at most {} selections", contest, limit));
+            }
+
+            let mut total = Ciphertext::identity();
+            let mut total_randomness = BigUint::default();
+            let mut selections = BTreeMap::new();
+            for option in options {
+                let m = u64::from(chosen.contains(option));
+                let (ciphertext, r) = key.encrypt_with(m, &group().random_exponent());
+                selections.insert(option.clone(), RangeProof::prove(key, &ciphertext, m, &r, 1));
+                total = total.combine(&ciphertext);
+                total_randomness = (&total_randomness + &r) % q;
+                encrypted.entry(contest.clone()).or_default().insert(option.clone(), ciphertext);
+                openings.entry(contest.clone()).or_default().insert(option.clone(), SelectionOpening { m, r });
+            }
+            let total = RangeProof::prove(key, &total, selected, &total_randomness, limit);
+            proofs.insert(contest.clone(), ContestProofs { selections, total });
+        }
+
+        let ballot = if self.nested {
+            serde_json::json!({ ENCRYPTED_FIELD: encrypted, PROOFS_FIELD: proofs })
+        } else {
+            serde_json::json!({
+                ENCRYPTED_FIELD: encrypted.get(DEFAULT_CONTEST),
+                PROOFS_FIELD: proofs.get(DEFAULT_CONTEST)
+            })
+        };
+        Ok((ballot, openings))
+    }
+
+    /// Reads the options a plaintext vote selects per contest: an option name for a poll
+    /// without contests, or per contest an option name or list of them.
+    pub fn choices(&self, vote: &Value) -> Result<BTreeMap<String, Vec<String>>, String> {
+        let names = |selection: &Value| -> Option<Vec<String>> {
+            match selection {
+                Value::String(option) => Some(vec![option.clone()]),
+                Value::Array(options) => options.iter().map(|option| option.as_str().map(str::to_string)).collect(),
+                _ => None,
+            }
+        };
+        let choices = if self.nested {
+            vote.as_object()
+                .and_then(|contests| contests.iter().map(|(contest, selection)| Some((contest.clone(), names(selection)?))).collect())
+        } else {
+            names(vote).map(|options| BTreeMap::from([(DEFAULT_CONTEST.to_string(), options)]))
+        };
+        let choices: BTreeMap<String, Vec<String>> = choices.ok_or("Only ballots that select options can be encrypted")?;
+        if let Some(unknown) = choices.keys().find(|contest| !self.contests.contains_key(*contest)) {
+            return Err(format!("Contest '{}' does not exist", unknown));
+        }
+        Ok(choices)
+    }
+
+    /// Checks that `openings` re-encrypt to exactly the ciphertexts of `ballot`, returning the
+    /// options the ballot selects per contest.
+    pub fn open_ballot(&self, ballot: &Value, key: &PublicKey, openings: &BallotOpenings) -> Result<BTreeMap<String, Vec<String>>, String> {
+        let mut choices: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (contest, ciphertexts) in self.read_ciphertexts(ballot)? {
+            for (option, ciphertext) in ciphertexts {
+                let opening = openings.get(&contest).and_then(|options| options.get(&option))
+                    .ok_or_else(|| format!("No opening for '{}' in contest '{}'", option, contest))?;
+                if opening.m > 1 || key.encrypt_with(opening.m, &opening.r).0 != ciphertext {
+                    return Err(format!("The opening of '{}' in contest '{}' does not match its ciphertext", option, contest));
+                }
+                if opening.m == 1 {
+                    choices.entry(contest.clone()).or_default().push(option);
+                }
+            }
+        }
+        Ok(choices)
+    }
+
+    /// Reads the ciphertexts of a ballot's encrypted field, nesting them under the default
+    /// contest for polls without contests.
+    fn read_selections(&self, encrypted: &Value) -> Result<EncryptedSelections, String> {
+        if self.nested {
+            serde_json::from_value::<EncryptedSelections>(encrypted.clone())
+        } else {
+            serde_json::from_value::<BTreeMap<String, Ciphertext>>(encrypted.clone())
+                .map(|options| BTreeMap::from([(DEFAULT_CONTEST.to_string(), options)]))
+        }
+        .map_err(|e| format!("Malformed ciphertexts: {}", e))
+    }
+
+    /// Multiplies together the ciphertexts of the given ballots. Ballots that carry no
+    /// ciphertexts, such as abstentions, only count towards the ballot total. Ciphertexts were
+    /// checked when their ballot was cast.
+    pub fn aggregate<'a>(&self, ballots: impl Iterator<Item = &'a Value>) -> EncryptedTally {
+        let mut aggregates: EncryptedSelections = self.contests.iter()
+            .map(|(contest, options)| {
+                let identity = options.iter().map(|option| (option.clone(), Ciphertext::identity())).collect();
+                (contest.clone(), identity)
+            })
+            .collect();
+        let mut count = 0;
+        for ballot in ballots {
+            count += 1;
+            let Some(encrypted) = ballot.get(ENCRYPTED_FIELD) else { continue };
+            let Ok(selections) = self.read_selections(encrypted) else { continue };
+            for (contest, ciphertexts) in selections {
+                let Some(totals) = aggregates.get_mut(&contest) else { continue };
+                for (option, ciphertext) in ciphertexts {
+                    if let Some(total) = totals.get_mut(&option) {
+                        *total = total.combine(&ciphertext);
+                    }
+                }
+            }
+        }
+        EncryptedTally { ballots: count, aggregates }
+    }
+
+    /// The decrypted counts in the shape results are reported in: per option for a poll without
+    /// contests, per contest and candidate for elections.
+    pub fn results(&self, counts: &BTreeMap<String, BTreeMap<String, u64>>) -> Value {
+        if self.nested {
+            serde_json::json!(counts)
+        } else {
+            serde_json::json!(counts.get(DEFAULT_CONTEST).cloned().unwrap_or_default())
+        }
+    }
+}
+
+/// Verifies an encrypted poll's chain without the server: that its blocks are intact and
+/// linked, that every current ballot's ciphertexts carry valid proofs under `key`, and that a
+/// recorded decrypted tally was decrypted from the aggregate of exactly those ballots. Returns
+/// the recomputed aggregate.
+pub fn verify_chain(poll: &Poll, key: &PublicKey) -> Result<EncryptedTally, String> {
+    let layout = EncryptedLayout::for_poll(poll.metadata())?;
+    let (valid, ballots, decrypted): (bool, Vec<(u64, &Value)>, Option<&Value>) = match poll {
+        Poll::Election { blockchain, .. } => (
+            blockchain.is_valid(),
+            blockchain.ballots().map(|block| (u64::from(block.index), &block.transactions)).collect(),
+            blockchain.records(DECRYPTED_TALLY_RECORD).last(),
+        ),
+        Poll::Normal { blockchain, .. } => (
+            blockchain.is_valid(),
+            blockchain.ballots().map(|(block, transaction)| (block.index as u64, transaction)).collect(),
+            blockchain.records(DECRYPTED_TALLY_RECORD).last(),
+        ),
+    };
+    if !valid {
+        return Err("Chain integrity check failed: a block's hash or link does not match".to_string());
+    }
+    for (index, ballot) in &ballots {
+        layout.verify_ballot(ballot, key).map_err(|e| format!("Ballot in block {}: {}", index, e))?;
+    }
+    let tally = layout.aggregate(ballots.iter().map(|(_, ballot)| *ballot));
+    if let Some(record) = decrypted {
+        let decrypted: DecryptedTally = serde_json::from_value(record.clone())
+            .map_err(|e| format!("Malformed decrypted tally: {}", e))?;
+        if decrypted.ballots != tally.ballots || decrypted.aggregates != tally.aggregates {
+            return Err("The decrypted tally was not decrypted from the aggregate of the chain's ballots".to_string());
+        }
+    }
+    Ok(tally)
+}
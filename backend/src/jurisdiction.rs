@@ -0,0 +1,83 @@
+use std::sync::OnceLock;
+use serde::{Serialize, Deserialize};
+use crate::contest::Contest;
+
+/// Zip code ranges and the state (and, where known, congressional district) they belong to.
+/// Ranges cover whole three-digit zip prefixes. Districts are listed for at-large states and,
+/// approximated by prefix, for Maine and Nebraska, which split their electors by district.
+const ZIP_JURISDICTIONS: &str = include_str!("../data/zip_jurisdictions.csv");
+
+/// Where a registered voter votes, derived by the server from their zip code.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Jurisdiction {
+    /// Two-letter state code, e.g. `"NY"`.
+    pub state: String,
+    /// Congressional district, e.g. `"AK-AL"`, where the bundled dataset resolves one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub district: Option<String>,
+}
+
+impl Jurisdiction {
+    /// Whether a contest restricted to `code` (a state or a district) appears on this ballot.
+    pub fn covers(&self, code: &str) -> bool {
+        self.state == code || self.district.as_deref() == Some(code)
+    }
+}
+
+struct ZipRange {
+    start: u32,
+    end: u32,
+    jurisdiction: Jurisdiction,
+}
+
+fn zip_ranges() -> &'static [ZipRange] {
+    static RANGES: OnceLock<Vec<ZipRange>> = OnceLock::new();
+    RANGES.get_or_init(|| {
+        ZIP_JURISDICTIONS.lines()
+            .skip(1)
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+                let [start, end, state, district] = fields[..] else { return None };
+                Some(ZipRange {
+                    start: start.parse().ok()?,
+                    end: end.parse().ok()?,
+                    jurisdiction: Jurisdiction {
+                        state: state.to_string(),
+                        district: (!district.is_empty()).then(|| district.to_string()),
+                    },
+                })
+            })
+            .collect()
+    })
+}
+
+/// Looks up the jurisdiction of a five-digit zip code (ZIP+4 suffixes are ignored).
+pub fn lookup_zip(zip_code: &str) -> Option<Jurisdiction> {
+    let zip = zip_code.trim();
+    let zip = zip.split_once('-').map_or(zip, |(five, _)| five);
+    if zip.len() != 5 || !zip.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let zip: u32 = zip.parse().ok()?;
+    zip_ranges().iter()
+        .find(|range| range.start <= zip && zip <= range.end)
+        .map(|range| range.jurisdiction.clone())
+}
+
+/// The contests of an election a voter is eligible to vote in.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BallotStyle {
+    pub jurisdiction: Jurisdiction,
+    pub contests: Vec<String>,
+}
+
+/// Builds the ballot style for a voter in `jurisdiction`, listing contests in declared order.
+pub fn ballot_style(contests: &[Contest], jurisdiction: &Jurisdiction) -> BallotStyle {
+    BallotStyle {
+        jurisdiction: jurisdiction.clone(),
+        contests: contests.iter()
+            .filter(|contest| contest.is_eligible(jurisdiction))
+            .map(|contest| contest.name.clone())
+            .collect(),
+    }
+}
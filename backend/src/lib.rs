@@ -1,17 +1,46 @@
+pub mod apportionment;
+pub mod ballot;
 pub mod block;
 pub mod blockchain;
+pub mod budgeting;
+pub mod challenge;
+pub mod commit_reveal;
+pub mod contest;
+pub mod credential;
+pub mod crypto;
+pub mod elgamal;
+pub mod encrypted_tally;
+pub mod mixnet;
+pub mod outcome;
 pub mod poll_manager;
+pub mod proofs;
+pub mod pseudonym;
+pub mod quadratic;
 pub mod election_block;
 pub mod election_blockchain;
+pub mod jurisdiction;
+pub mod statistics;
+pub mod stv;
+pub mod tie_break;
+pub mod timeline;
+pub mod tracker;
+pub mod trustee;
 pub mod vote_service;
 pub mod user;
 pub mod voting_integration;
 
+pub use apportionment::{ElectorConfig, ElectoralResult};
+pub use ballot::{BallotStatus, Turnout};
 pub use block::Block;
 pub use blockchain::Blockchain;
 pub use poll_manager::PollManager;
 pub use election_block::ElectionBlock;
 pub use election_blockchain::ElectionBlockchain;
+pub use contest::{Contest, ContestMethod};
+pub use jurisdiction::{BallotStyle, Jurisdiction};
+pub use outcome::{DecisionRules, Outcome, OutcomeStatus};
+pub use stv::{SurplusTransfer, StvResult};
+pub use tie_break::{TieBreakPolicy, TieBreaker};
 pub use vote_service::VoteService;
 pub use user::{User, UserManager, UserRegistration, UserLogin, UserError};
 pub use voting_integration::{VotingIntegration, VotingError};
\ No newline at end of file
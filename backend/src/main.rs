@@ -2,12 +2,16 @@ use warp::{Filter, cors, reject, Rejection, Reply};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use std::convert::Infallible;
-use serde::{Serialize, Deserialize};
 use serde_json::json;
 use backend::poll_manager::{PollManager, PollInput, Poll};
 use backend::user::{UserManager, UserRegistration, UserLogin, UserError, migrate_password_column};
-use backend::vote_service::VoteService;
-use backend::voting_integration::{VotingIntegration, VotingError};
+use backend::timeline::Bucket;
+use backend::vote_service::{VoteService, VoterWeight};
+use backend::pseudonym;
+use backend::credential::Credential;
+use backend::trustee::{EncryptedShare, PartialTally, TrusteeCommitments};
+use backend::mixnet::PartialMix;
+use backend::voting_integration::{BallotReveal, VotingIntegration, VotingError, TieBreakRequest, WriteInAdjudication};
 mod election_initializer;
 use election_initializer::init_election_poll;
 use sqlx::migrate::Migrator;
@@ -15,13 +19,7 @@ use dotenv::dotenv;
 mod db;
 use sqlx::Row;
 use std::fs;
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct VoteInput {
-    pub poll_id: String,
-    pub voter_id: String,
-    pub candidate: String,
-}
+use std::collections::{BTreeMap, HashMap};
 
 // Custom rejection for user errors
 #[derive(Debug)]
@@ -91,6 +89,13 @@ async fn serve_states_map() -> Result<impl warp::Reply, Infallible> {
 #[tokio::main]
 async fn main() {
     dotenv().ok();
+    let pseudonym_secret = match pseudonym::server_secret() {
+        Ok(secret) => secret,
+        Err(e) => {
+            eprintln!("Refusing to start: {}", e);
+            std::process::exit(1);
+        }
+    };
     let pool = db::create_db_pool().await;
     
     // Run migrations
@@ -124,6 +129,7 @@ async fn main() {
     }
 
     // Now load the votes for each poll
+    let vote_service = Arc::new(VoteService::with_pseudonym_secret(pool.clone(), pseudonym_secret));
     {
         let poll_ids: Vec<String> = {
             let pm = poll_manager.lock().await;
@@ -132,38 +138,22 @@ async fn main() {
     
         for poll_id in poll_ids {
             println!("Loading votes for poll: {}", poll_id);
-            
-            // Fetch votes for this poll
-            let vote_rows = match sqlx::query("SELECT vote FROM votes WHERE poll_id = $1")
-                .bind(&poll_id)
-                .fetch_all(&pool)
-                .await 
-            {
-                Ok(rows) => rows,
+
+            // Fetch votes and other chain records for this poll, in the order they were added
+            let entries = match vote_service.get_chain_entries(&poll_id).await {
+                Ok(entries) => entries,
                 Err(e) => {
                     eprintln!("Error fetching votes for poll {}: {}", poll_id, e);
                     continue;
                 }
             };
             
-            println!("Found {} votes for poll {}", vote_rows.len(), poll_id);
+            println!("Found {} votes for poll {}", entries.len(), poll_id);
             
-            // Add votes to the blockchain
+            // Add votes to the blockchain, keeping the time each was cast
             let mut pm = poll_manager.lock().await;
-            for vote_row in vote_rows {
-                let vote_data: serde_json::Value = match vote_row.try_get("vote") {
-                    Ok(data) => data,
-                    Err(e) => {
-                        eprintln!("Failed to extract vote data: {}", e);
-                        continue;
-                    }
-                };
-                
-                // Add the vote to the poll
-                if let Err(e) = pm.add_vote(&poll_id, vote_data.clone()) {
-                    eprintln!("Failed to add vote to poll {}: {}", poll_id, e);
-                    eprintln!("Vote data: {}", serde_json::to_string_pretty(&vote_data).unwrap_or_default());
-                }
+            if let Err(e) = pm.rebuild_chain(&poll_id, entries) {
+                eprintln!("Failed to rebuild chain for poll {}: {}", poll_id, e);
             }
         }
     }
@@ -176,7 +166,6 @@ async fn main() {
 
     // Create other managers
     let user_manager = Arc::new(UserManager::new(pool.clone()));
-    let vote_service = Arc::new(VoteService::new(pool.clone()));
     let voting_integration = Arc::new(VotingIntegration::new(poll_manager.clone(), vote_service.clone()));
 
     // Warp + CORS
@@ -351,29 +340,56 @@ async fn main() {
                     message: "Missing vote data".to_string(),
                 }))?;
             
+            // A ballot handle from an earlier receipt recasts that ballot
+            let cast = match body.get("ballot_handle").and_then(|v| v.as_str()) {
+                Some(ballot_handle) => voting_integration.recast_vote(poll_id, voter_id, ballot_handle, vote_data).await,
+                None => voting_integration.cast_vote(poll_id, voter_id, vote_data).await,
+            };
+            cast
+                .map(|cast| warp::reply::json(&json!({
+                    "status": "Vote cast successfully",
+                    "poll_id": poll_id,
+                    "voter_id": voter_id,
+                    "receipt": cast.receipt,
+                    "tracker_code": cast.tracker_code,
+                    "ballot_handle": cast.ballot_handle
+                })))
+                .map_err(voting_error_to_rejection)
+        })
+        .with(cors.clone());
+
+    let reveal_vote = warp::post()
+        .and(warp::path("poll"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("reveal"))
+        .and(warp::body::json())
+        .and(vi_filter.clone())
+        .and_then(|poll_id: String, reveal: BallotReveal, voting_integration: Arc<VotingIntegration>| async move {
             voting_integration
-                .cast_vote(poll_id, voter_id, vote_data)
+                .reveal_vote(&poll_id, &reveal)
                 .await
-                .map(|_| warp::reply::json(&json!({
-                    "status": "Vote cast successfully",
+                .map(|cast| warp::reply::json(&json!({
+                    "status": "Vote revealed successfully",
                     "poll_id": poll_id,
-                    "voter_id": voter_id
+                    "voter_id": reveal.voter_id,
+                    "receipt": cast.receipt,
+                    "tracker_code": cast.tracker_code
                 })))
                 .map_err(voting_error_to_rejection)
         })
         .with(cors.clone());
 
-    let verify_vote_integrated = warp::get()
-        .and(warp::path("vote"))
+    let track_ballot = warp::get()
+        .and(warp::path("poll"))
         .and(warp::path::param::<String>())
+        .and(warp::path("tracker"))
         .and(warp::path::param::<String>())
-        .and(warp::path("verify"))
         .and(vi_filter.clone())
-        .and_then(|poll_id: String, voter_id: String, voting_integration: Arc<VotingIntegration>| async move {
+        .and_then(|poll_id: String, code: String, voting_integration: Arc<VotingIntegration>| async move {
             voting_integration
-                .verify_vote(&poll_id, &voter_id)
+                .track_ballot(&poll_id, &code)
                 .await
-                .map(|result| warp::reply::json(&result))
+                .map(|tracker| warp::reply::json(&tracker))
                 .map_err(voting_error_to_rejection)
         })
         .with(cors.clone());
@@ -392,6 +408,503 @@ async fn main() {
         })
         .with(cors.clone());
 
+    let ballot_style = warp::get()
+        .and(warp::path("poll"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("ballot_style"))
+        .and(warp::path::param::<String>())
+        .and(vi_filter.clone())
+        .and_then(|poll_id: String, voter_id: String, voting_integration: Arc<VotingIntegration>| async move {
+            voting_integration
+                .get_ballot_style(&poll_id, &voter_id)
+                .await
+                .map(|style| warp::reply::json(&style))
+                .map_err(voting_error_to_rejection)
+        })
+        .with(cors.clone());
+
+    let results_timeline = warp::get()
+        .and(warp::path("poll"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("results"))
+        .and(warp::path("timeline"))
+        .and(warp::query::<HashMap<String, String>>())
+        .and(vi_filter.clone())
+        .and_then(|poll_id: String, query: HashMap<String, String>, voting_integration: Arc<VotingIntegration>| async move {
+            let bucket = match query.get("bucket") {
+                Some(bucket) => Bucket::parse(bucket)
+                    .map_err(|e| voting_error_to_rejection(VotingError::ValidationError(e)))?,
+                None => Bucket::default(),
+            };
+            voting_integration
+                .get_results_timeline(&poll_id, bucket)
+                .await
+                .map(|timeline| warp::reply::json(&json!({
+                    "poll_id": poll_id,
+                    "bucket": bucket,
+                    "timeline": timeline
+                })))
+                .map_err(voting_error_to_rejection)
+        })
+        .with(cors.clone());
+
+    let cross_tab = warp::get()
+        .and(warp::path("poll"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("cross_tab"))
+        .and(warp::query::<HashMap<String, String>>())
+        .and(vi_filter.clone())
+        .and_then(|poll_id: String, query: HashMap<String, String>, voting_integration: Arc<VotingIntegration>| async move {
+            let (Some(first), Some(second)) = (query.get("first"), query.get("second")) else {
+                return Err(voting_error_to_rejection(VotingError::ValidationError(
+                    "Name two contests with ?first=...&second=...".to_string()
+                )));
+            };
+            voting_integration
+                .get_cross_tab(&poll_id, first, second)
+                .await
+                .map(|cross_tab| warp::reply::json(&cross_tab))
+                .map_err(voting_error_to_rejection)
+        })
+        .with(cors.clone());
+
+    let turnout_report = warp::get()
+        .and(warp::path("poll"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("turnout"))
+        .and(vi_filter.clone())
+        .and_then(|poll_id: String, voting_integration: Arc<VotingIntegration>| async move {
+            voting_integration
+                .get_turnout_report(&poll_id)
+                .await
+                .map(|report| warp::reply::json(&report))
+                .map_err(voting_error_to_rejection)
+        })
+        .with(cors.clone());
+
+    let import_weights = warp::post()
+        .and(warp::path("poll"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("weights"))
+        .and(warp::body::json())
+        .and(vi_filter.clone())
+        .and_then(|poll_id: String, weights: Vec<VoterWeight>, voting_integration: Arc<VotingIntegration>| async move {
+            voting_integration
+                .import_voter_weights(&poll_id, &weights)
+                .await
+                .map(|imported| warp::reply::json(&json!({
+                    "status": "Weights imported successfully",
+                    "poll_id": poll_id,
+                    "imported": imported
+                })))
+                .map_err(voting_error_to_rejection)
+        })
+        .with(cors.clone());
+
+    let adjudicate_write_in = warp::post()
+        .and(warp::path("poll"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("write_ins"))
+        .and(warp::path("adjudicate"))
+        .and(warp::body::json())
+        .and(vi_filter.clone())
+        .and_then(|poll_id: String, adjudication: WriteInAdjudication, voting_integration: Arc<VotingIntegration>| async move {
+            voting_integration
+                .adjudicate_write_in(&poll_id, &adjudication)
+                .await
+                .map(|record| warp::reply::json(&json!({
+                    "status": "Write-in adjudicated successfully",
+                    "poll_id": poll_id,
+                    "adjudication": record
+                })))
+                .map_err(voting_error_to_rejection)
+        })
+        .with(cors.clone());
+
+    let record_tie_break = warp::post()
+        .and(warp::path("poll"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("tie_breaks"))
+        .and(warp::body::json())
+        .and(vi_filter.clone())
+        .and_then(|poll_id: String, request: TieBreakRequest, voting_integration: Arc<VotingIntegration>| async move {
+            voting_integration
+                .record_tie_break(&poll_id, &request)
+                .await
+                .map(|record| warp::reply::json(&json!({
+                    "status": "Tie-break recorded successfully",
+                    "poll_id": poll_id,
+                    "tie_break": record
+                })))
+                .map_err(voting_error_to_rejection)
+        })
+        .with(cors.clone());
+
+    let rotate_pseudonyms = warp::post()
+        .and(warp::path("poll"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("pseudonyms"))
+        .and(warp::path("rotate"))
+        .and(vi_filter.clone())
+        .and_then(|poll_id: String, voting_integration: Arc<VotingIntegration>| async move {
+            voting_integration
+                .rotate_pseudonyms(&poll_id)
+                .await
+                .map(|rotation| warp::reply::json(&rotation))
+                .map_err(voting_error_to_rejection)
+        })
+        .with(cors.clone());
+
+    let get_credential_key = warp::get()
+        .and(warp::path("poll"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("credentials"))
+        .and(warp::path("key"))
+        .and(vi_filter.clone())
+        .and_then(|poll_id: String, voting_integration: Arc<VotingIntegration>| async move {
+            voting_integration
+                .get_credential_key(&poll_id)
+                .await
+                .map(|key| warp::reply::json(&key))
+                .map_err(voting_error_to_rejection)
+        })
+        .with(cors.clone());
+
+    let issue_credential = warp::post()
+        .and(warp::path("poll"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("credentials"))
+        .and(warp::path("issue"))
+        .and(warp::body::json())
+        .and(um_filter.clone())
+        .and(vi_filter.clone())
+        .and_then(|poll_id: String, body: serde_json::Value, user_manager: Arc<UserManager>, voting_integration: Arc<VotingIntegration>| async move {
+            let login = serde_json::from_value::<UserLogin>(body.clone())
+                .map_err(|_| reject::custom(CustomRejection {
+                    message: "Missing email or password".to_string(),
+                }))?;
+
+            let blinded = body.get("blinded").and_then(|v| v.as_str())
+                .ok_or_else(|| reject::custom(CustomRejection {
+                    message: "Missing blinded token".to_string(),
+                }))?;
+
+            // Credentials are issued only to the voter who logs in, never to a voter id named by the caller
+            let user = user_manager
+                .login_user(login)
+                .await
+                .map_err(user_error_to_rejection)?;
+
+            voting_integration
+                .issue_credential(&poll_id, &user.voter_id, blinded)
+                .await
+                .map(|issued| warp::reply::json(&issued))
+                .map_err(voting_error_to_rejection)
+        })
+        .with(cors.clone());
+
+    let cast_anonymous_vote = warp::post()
+        .and(warp::path("poll"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("anonymous_vote"))
+        .and(warp::body::json())
+        .and(vi_filter.clone())
+        .and_then(|poll_id: String, body: serde_json::Value, voting_integration: Arc<VotingIntegration>| async move {
+            let credential = serde_json::from_value::<Credential>(body.clone())
+                .map_err(|_| reject::custom(CustomRejection {
+                    message: "Missing credential token or signature".to_string(),
+                }))?;
+
+            let vote_data = body.get("vote").cloned()
+                .ok_or_else(|| reject::custom(CustomRejection {
+                    message: "Missing vote data".to_string(),
+                }))?;
+
+            voting_integration
+                .cast_anonymous_vote(&poll_id, &credential, vote_data)
+                .await
+                .map(|cast| warp::reply::json(&json!({
+                    "status": "Vote cast successfully",
+                    "poll_id": poll_id,
+                    "receipt": cast.receipt,
+                    "tracker_code": cast.tracker_code
+                })))
+                .map_err(voting_error_to_rejection)
+        })
+        .with(cors.clone());
+
+    let get_encryption_key = warp::get()
+        .and(warp::path("poll"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("encryption_key"))
+        .and(vi_filter.clone())
+        .and_then(|poll_id: String, voting_integration: Arc<VotingIntegration>| async move {
+            voting_integration
+                .get_encryption_key(&poll_id)
+                .await
+                .map(|key| warp::reply::json(&key))
+                .map_err(voting_error_to_rejection)
+        })
+        .with(cors.clone());
+
+    let get_encrypted_tally = warp::get()
+        .and(warp::path("poll"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("encrypted_tally"))
+        .and(vi_filter.clone())
+        .and_then(|poll_id: String, voting_integration: Arc<VotingIntegration>| async move {
+            voting_integration
+                .get_encrypted_tally(&poll_id)
+                .await
+                .map(|tally| warp::reply::json(&tally))
+                .map_err(voting_error_to_rejection)
+        })
+        .with(cors.clone());
+
+    let decrypt_tally = warp::post()
+        .and(warp::path("poll"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("tally"))
+        .and(warp::path("decrypt"))
+        .and(vi_filter.clone())
+        .and_then(|poll_id: String, voting_integration: Arc<VotingIntegration>| async move {
+            voting_integration
+                .decrypt_tally(&poll_id)
+                .await
+                .map(|tally| warp::reply::json(&json!({
+                    "status": "Tally decrypted successfully",
+                    "poll_id": poll_id,
+                    "tally": tally
+                })))
+                .map_err(voting_error_to_rejection)
+        })
+        .with(cors.clone());
+
+    let get_ceremony = warp::get()
+        .and(warp::path("poll"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("trustees"))
+        .and(warp::path::end())
+        .and(vi_filter.clone())
+        .and_then(|poll_id: String, voting_integration: Arc<VotingIntegration>| async move {
+            voting_integration
+                .get_ceremony(&poll_id)
+                .await
+                .map(|ceremony| warp::reply::json(&ceremony))
+                .map_err(voting_error_to_rejection)
+        })
+        .with(cors.clone());
+
+    let publish_trustee_commitments = warp::post()
+        .and(warp::path("poll"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("trustees"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("commitments"))
+        .and(warp::body::json())
+        .and(vi_filter.clone())
+        .and_then(|poll_id: String, trustee: String, commitments: TrusteeCommitments, voting_integration: Arc<VotingIntegration>| async move {
+            voting_integration
+                .publish_trustee_commitments(&poll_id, &trustee, &commitments)
+                .await
+                .map(|ceremony| warp::reply::json(&ceremony))
+                .map_err(voting_error_to_rejection)
+        })
+        .with(cors.clone());
+
+    let distribute_trustee_shares = warp::post()
+        .and(warp::path("poll"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("trustees"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("shares"))
+        .and(warp::body::json())
+        .and(vi_filter.clone())
+        .and_then(|poll_id: String, trustee: String, shares: BTreeMap<String, EncryptedShare>, voting_integration: Arc<VotingIntegration>| async move {
+            voting_integration
+                .distribute_trustee_shares(&poll_id, &trustee, &shares)
+                .await
+                .map(|ceremony| warp::reply::json(&ceremony))
+                .map_err(voting_error_to_rejection)
+        })
+        .with(cors.clone());
+
+    let get_trustee_shares = warp::get()
+        .and(warp::path("poll"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("trustees"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("shares"))
+        .and(vi_filter.clone())
+        .and_then(|poll_id: String, trustee: String, voting_integration: Arc<VotingIntegration>| async move {
+            voting_integration
+                .get_trustee_shares(&poll_id, &trustee)
+                .await
+                .map(|shares| warp::reply::json(&shares))
+                .map_err(voting_error_to_rejection)
+        })
+        .with(cors.clone());
+
+    let submit_partial_decryption = warp::post()
+        .and(warp::path("poll"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("trustees"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("partial_decryption"))
+        .and(warp::body::json())
+        .and(vi_filter.clone())
+        .and_then(|poll_id: String, trustee: String, partial: PartialTally, voting_integration: Arc<VotingIntegration>| async move {
+            voting_integration
+                .submit_partial_decryption(&poll_id, &trustee, &partial)
+                .await
+                .map(|ceremony| warp::reply::json(&ceremony))
+                .map_err(voting_error_to_rejection)
+        })
+        .with(cors.clone());
+
+    let prepare_ballot = warp::post()
+        .and(warp::path("poll"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("ballots"))
+        .and(warp::path("prepare"))
+        .and(warp::body::json())
+        .and(vi_filter.clone())
+        .and_then(|poll_id: String, body: serde_json::Value, voting_integration: Arc<VotingIntegration>| async move {
+            let voter_id = body.get("voter_id").and_then(|v| v.as_str())
+                .ok_or_else(|| reject::custom(CustomRejection {
+                    message: "Missing voter_id".to_string(),
+                }))?;
+
+            let vote_data = body.get("vote")
+                .ok_or_else(|| reject::custom(CustomRejection {
+                    message: "Missing vote data".to_string(),
+                }))?;
+
+            voting_integration
+                .prepare_ballot(&poll_id, voter_id, vote_data)
+                .await
+                .map(|prepared| warp::reply::json(&prepared))
+                .map_err(voting_error_to_rejection)
+        })
+        .with(cors.clone());
+
+    let cast_prepared_ballot = warp::post()
+        .and(warp::path("poll"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("ballots"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("cast"))
+        .and(warp::body::json())
+        .and(vi_filter.clone())
+        .and_then(|poll_id: String, tracking_code: String, body: serde_json::Value, voting_integration: Arc<VotingIntegration>| async move {
+            let voter_id = body.get("voter_id").and_then(|v| v.as_str())
+                .ok_or_else(|| reject::custom(CustomRejection {
+                    message: "Missing voter_id".to_string(),
+                }))?;
+
+            voting_integration
+                .cast_prepared_ballot(&poll_id, voter_id, &tracking_code)
+                .await
+                .map(|cast| warp::reply::json(&json!({
+                    "status": "Vote cast successfully",
+                    "poll_id": poll_id,
+                    "tracking_code": tracking_code,
+                    "receipt": cast.receipt,
+                    "tracker_code": cast.tracker_code,
+                    "ballot_handle": cast.ballot_handle
+                })))
+                .map_err(voting_error_to_rejection)
+        })
+        .with(cors.clone());
+
+    let challenge_ballot = warp::post()
+        .and(warp::path("poll"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("ballots"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("challenge"))
+        .and(warp::body::json())
+        .and(vi_filter.clone())
+        .and_then(|poll_id: String, tracking_code: String, body: serde_json::Value, voting_integration: Arc<VotingIntegration>| async move {
+            let voter_id = body.get("voter_id").and_then(|v| v.as_str())
+                .ok_or_else(|| reject::custom(CustomRejection {
+                    message: "Missing voter_id".to_string(),
+                }))?;
+
+            voting_integration
+                .challenge_ballot(&poll_id, voter_id, &tracking_code)
+                .await
+                .map(|challenged| warp::reply::json(&challenged))
+                .map_err(voting_error_to_rejection)
+        })
+        .with(cors.clone());
+
+    let mix_ballots = warp::post()
+        .and(warp::path("poll"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("mix"))
+        .and(warp::path::end())
+        .and(vi_filter.clone())
+        .and_then(|poll_id: String, voting_integration: Arc<VotingIntegration>| async move {
+            voting_integration
+                .mix_ballots(&poll_id)
+                .await
+                .map(|shuffle| warp::reply::json(&shuffle))
+                .map_err(voting_error_to_rejection)
+        })
+        .with(cors.clone());
+
+    let get_mix = warp::get()
+        .and(warp::path("poll"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("mix"))
+        .and(warp::path::end())
+        .and(vi_filter.clone())
+        .and_then(|poll_id: String, voting_integration: Arc<VotingIntegration>| async move {
+            voting_integration
+                .get_mix(&poll_id)
+                .await
+                .map(|mix| warp::reply::json(&mix))
+                .map_err(voting_error_to_rejection)
+        })
+        .with(cors.clone());
+
+    let submit_mix_decryption = warp::post()
+        .and(warp::path("poll"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("trustees"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("mix_decryption"))
+        .and(warp::body::json())
+        .and(vi_filter.clone())
+        .and_then(|poll_id: String, trustee: String, partial: PartialMix, voting_integration: Arc<VotingIntegration>| async move {
+            voting_integration
+                .submit_mix_decryption(&poll_id, &trustee, &partial)
+                .await
+                .map(|ceremony| warp::reply::json(&ceremony))
+                .map_err(voting_error_to_rejection)
+        })
+        .with(cors.clone());
+
+    let decrypt_mix = warp::post()
+        .and(warp::path("poll"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("mix"))
+        .and(warp::path("decrypt"))
+        .and(vi_filter.clone())
+        .and_then(|poll_id: String, voting_integration: Arc<VotingIntegration>| async move {
+            voting_integration
+                .decrypt_mix(&poll_id)
+                .await
+                .map(|decrypted| warp::reply::json(&json!({
+                    "status": "Mixed ballots decrypted successfully",
+                    "poll_id": poll_id,
+                    "decrypted": decrypted
+                })))
+                .map_err(voting_error_to_rejection)
+        })
+        .with(cors.clone());
+
     // --------------------------
     // EXISTING POLL ROUTES
     // --------------------------
@@ -461,7 +974,7 @@ async fn main() {
                     warp::reply::json(&json!({
                         "poll_id": poll_id,
                         "vote_counts": counts,
-                        "total_votes": blockchain.chain.len() - 1, // Subtract genesis block
+                        "total_votes": blockchain.ballot_count(),
                         "timestamp": chrono::Utc::now().timestamp()
                     }))
                 },
@@ -470,7 +983,7 @@ async fn main() {
                     warp::reply::json(&json!({
                         "poll_id": poll_id,
                         "vote_counts": counts,
-                        "total_votes": blockchain.chain.len() - 1,
+                        "total_votes": blockchain.ballots().count(),
                         "timestamp": chrono::Utc::now().timestamp()
                     }))
                 },
@@ -531,7 +1044,7 @@ async fn main() {
             match pm.get_poll(&poll_id) {
                 Some(Poll::Election { blockchain, .. }) => {
                     let results = blockchain.get_vote_counts_by_state();
-                    let total_votes = blockchain.chain.len() - 1; // Subtract genesis block
+                    let total_votes = blockchain.ballot_count();
                     
                     // Create a more detailed response
                     let json_resp = warp::reply::json(&json!({
@@ -553,45 +1066,6 @@ async fn main() {
         })
         .with(cors.clone());
 
-    let verify_vote = warp::get()
-        .and(warp::path("poll"))
-        .and(warp::path::param::<String>())
-        .and(warp::path("verify_vote"))
-        .and(warp::path::param::<String>())
-        .and(pm_filter.clone())
-        .and_then(|poll_id: String, voter_id: String, poll_manager: Arc<Mutex<PollManager>>| async move {
-            let pm = poll_manager.lock().await;
-            let response = match pm.get_poll(&poll_id) {
-                Some(Poll::Normal { blockchain, .. }) => {
-                    if let Some((block_index, vote_hash)) = blockchain.find_vote(&voter_id) {
-                        warp::reply::json(&json!({
-                            "verified": true,
-                            "block_index": block_index,
-                            "vote_hash": vote_hash,
-                            "timestamp": blockchain.chain[block_index as usize].timestamp
-                        }))
-                    } else {
-                        warp::reply::json(&json!({ "verified": false, "error": "Vote not found" }))
-                    }
-                },
-                Some(Poll::Election { blockchain, .. }) => {
-                    if let Some((block_index, vote_hash)) = blockchain.find_vote(&voter_id) {
-                        warp::reply::json(&json!({
-                            "verified": true,
-                            "block_index": block_index,
-                            "vote_hash": vote_hash,
-                            "timestamp": blockchain.chain[block_index as usize].timestamp
-                        }))
-                    } else {
-                        warp::reply::json(&json!({ "verified": false, "error": "Vote not found" }))
-                    }
-                },
-                None => warp::reply::json(&json!({ "error": "Poll not found" })),
-            };
-            Ok::<_, Infallible>(response)
-        })
-        .with(cors.clone());
-
     // Serve states-10m.json from data/
     let serve_us_map = warp::path!("map" / "us_states")
         .and(warp::get())
@@ -611,16 +1085,42 @@ async fn main() {
         .or(change_password);
 
     let integrated_voting_routes = cast_vote
-        .or(verify_vote_integrated)
-        .or(poll_results);
+        .or(reveal_vote)
+        .or(track_ballot)
+        .or(results_timeline)
+        .or(poll_results)
+        .or(ballot_style)
+        .or(turnout_report)
+        .or(cross_tab)
+        .or(import_weights)
+        .or(adjudicate_write_in)
+        .or(record_tie_break)
+        .or(rotate_pseudonyms)
+        .or(get_credential_key)
+        .or(issue_credential)
+        .or(cast_anonymous_vote)
+        .or(get_encryption_key)
+        .or(get_encrypted_tally)
+        .or(decrypt_tally)
+        .or(get_ceremony)
+        .or(publish_trustee_commitments)
+        .or(distribute_trustee_shares)
+        .or(get_trustee_shares)
+        .or(submit_partial_decryption)
+        .or(prepare_ballot)
+        .or(cast_prepared_ballot)
+        .or(challenge_ballot)
+        .or(mix_ballots)
+        .or(get_mix)
+        .or(submit_mix_decryption)
+        .or(decrypt_mix);
 
     let poll_routes = create_poll
         .or(list_polls)
         .or(get_blockchain)
         .or(get_vote_counts)
         .or(check_validity)
-        .or(get_poll_details)
-        .or(verify_vote);
+        .or(get_poll_details);
 
     let routes = user_routes
         .or(integrated_voting_routes)
@@ -0,0 +1,563 @@
+use std::collections::BTreeMap;
+use num_bigint_dig::BigUint;
+use num_traits::{One, Zero};
+use rand::seq::SliceRandom;
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+use crate::crypto::{self, hex_biguint, hex_biguint_vec};
+use crate::election_blockchain::{ElectionBlockchain, RECORD_TYPE_FIELD};
+use crate::elgamal::{group, Ciphertext, PublicKey};
+use crate::encrypted_tally::{ENCRYPTED_FIELD, PROOFS_FIELD};
+use crate::poll_manager::{Poll, PollInput};
+use crate::proofs::{self, SchnorrProof};
+use crate::trustee::{self, Ceremony, PartialDecryption, TrusteeConfig};
+use crate::voting_integration::VotingIntegration;
+
+/// Group elements a mixed ballot is encrypted as. Every ballot has the same width so mixed
+/// ballots cannot be told apart by size.
+pub const BALLOT_CHUNKS: usize = 4;
+
+/// Plaintext bytes encoded in each group element.
+pub const CHUNK_BYTES: usize = 240;
+
+/// Record type for one verifiable shuffle of a poll's encrypted ballots.
+pub const SHUFFLE_RECORD: &str = "shuffle";
+
+/// Record type for the individually decrypted ballots of a mixed poll.
+pub const MIX_DECRYPTION_RECORD: &str = "mix_decryption";
+
+/// One encrypted ballot: a ciphertext per chunk of its encoded selections.
+pub type MixedBallot = Vec<Ciphertext>;
+
+/// A trustee's partial decryption of every chunk of every mixed ballot, in mixed order.
+pub type PartialMix = Vec<Vec<PartialDecryption>>;
+
+/// Terelius-Wikström proof that a list of ballots is a re-encryption of another under some
+/// secret permutation. The mixer commits to the permutation matrix and proves, for challenges
+/// `u_j` derived from both lists, that the committed permutation maps `u` to `u'` and that the
+/// outputs raised to `u'` re-encrypt the inputs raised to `u`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShuffleProof {
+    /// `c_j = g^r_j * h_i` for the output position `i` input `j` was moved to.
+    #[serde(with = "hex_biguint_vec")]
+    pub permutation_commitments: Vec<BigUint>,
+    /// Chained commitments to the permuted challenges `u'_i`.
+    #[serde(with = "hex_biguint_vec")]
+    pub chain_commitments: Vec<BigUint>,
+    #[serde(with = "hex_biguint")]
+    pub t1: BigUint,
+    #[serde(with = "hex_biguint")]
+    pub t2: BigUint,
+    #[serde(with = "hex_biguint")]
+    pub t3: BigUint,
+    /// Per chunk, commitments for the re-encryption of the `a` and `b` components.
+    #[serde(with = "hex_biguint_vec")]
+    pub t4_a: Vec<BigUint>,
+    #[serde(with = "hex_biguint_vec")]
+    pub t4_b: Vec<BigUint>,
+    #[serde(with = "hex_biguint_vec")]
+    pub t_hat: Vec<BigUint>,
+    #[serde(with = "hex_biguint")]
+    pub s1: BigUint,
+    #[serde(with = "hex_biguint")]
+    pub s2: BigUint,
+    #[serde(with = "hex_biguint")]
+    pub s3: BigUint,
+    #[serde(with = "hex_biguint_vec")]
+    pub s4: Vec<BigUint>,
+    #[serde(with = "hex_biguint_vec")]
+    pub s_hat: Vec<BigUint>,
+    #[serde(with = "hex_biguint_vec")]
+    pub s_prime: Vec<BigUint>,
+}
+
+/// One pass of the mix as recorded on the chain: the shuffled ballots and the proof that they
+/// re-encrypt the previous pass's output, or the cast ballots for the first pass.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShuffleRecord {
+    pub pass: u32,
+    pub ballots: Vec<MixedBallot>,
+    pub proof: ShuffleProof,
+}
+
+/// The individually decrypted ballots of a mixed poll, in mixed order, recorded on the chain
+/// with the partial decryptions they were combined from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MixDecryption {
+    pub ballots: Vec<Value>,
+    pub partial_decryptions: BTreeMap<String, PartialMix>,
+}
+
+/// Encodes up to `BALLOT_CHUNKS * CHUNK_BYTES - 2` bytes as group elements. The length is
+/// prefixed, each chunk is read as an integer `v` and `v + 1` or `p - (v + 1)`, whichever is in
+/// the order-`q` subgroup, is used.
+pub fn encode(plaintext: &[u8]) -> Result<Vec<BigUint>, String> {
+    let capacity = BALLOT_CHUNKS * CHUNK_BYTES - 2;
+    if plaintext.len() > capacity {
+        return Err(format!("Ballot is {} bytes, more than the {} a mixed ballot holds", plaintext.len(), capacity));
+    }
+    let group = group();
+    let mut bytes = (plaintext.len() as u16).to_be_bytes().to_vec();
+    bytes.extend_from_slice(plaintext);
+    bytes.resize(BALLOT_CHUNKS * CHUNK_BYTES, 0);
+    Ok(bytes.chunks(CHUNK_BYTES)
+        .map(|chunk| {
+            let x = BigUint::from_bytes_be(chunk) + 1u32;
+            if group.is_element(&x) { x } else { &group.p - x }
+        })
+        .collect())
+}
+
+/// Recovers the bytes encoded by `encode`.
+pub fn decode(elements: &[BigUint]) -> Result<Vec<u8>, String> {
+    let group = group();
+    let mut bytes = Vec::with_capacity(elements.len() * CHUNK_BYTES);
+    for element in elements {
+        if element.is_zero() || *element >= group.p {
+            return Err("Decrypted chunk is not an encoded ballot".to_string());
+        }
+        let x = if *element <= group.q { element.clone() } else { &group.p - element };
+        let value = (x - 1u32).to_bytes_be();
+        if value.len() > CHUNK_BYTES {
+            return Err("Decrypted chunk is not an encoded ballot".to_string());
+        }
+        bytes.resize(bytes.len() + CHUNK_BYTES - value.len(), 0);
+        bytes.extend_from_slice(&value);
+    }
+    let length = match bytes.as_slice() {
+        [high, low, ..] => usize::from(u16::from_be_bytes([*high, *low])),
+        _ => return Err("Decrypted ballot is empty".to_string()),
+    };
+    bytes.get(2..2 + length).map(<[u8]>::to_vec).ok_or_else(|| "Decrypted ballot length is out of range".to_string())
+}
+
+/// The selections sealed in a mixed ballot and the randomness of each chunk, published when a
+/// prepared ballot is challenged so that anyone can re-encrypt it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SealOpening {
+    pub selections: Value,
+    #[serde(with = "hex_biguint_vec")]
+    pub randomness: Vec<BigUint>,
+}
+
+/// Encrypts a ballot's selections whole to `key`, with a proof of knowledge of each chunk's
+/// randomness so cast ciphertexts cannot be derived from someone else's. Run by the voter's
+/// client.
+pub fn seal(key: &PublicKey, selections: &Value) -> Result<Value, String> {
+    seal_opened(key, selections).map(|(ballot, _)| ballot)
+}
+
+/// Seals a ballot as [`seal`] does, also returning its opening.
+pub fn seal_opened(key: &PublicKey, selections: &Value) -> Result<(Value, SealOpening), String> {
+    let group = group();
+    let plaintext = serde_json::to_vec(selections).map_err(|e| e.to_string())?;
+    let mut randomness = Vec::with_capacity(BALLOT_CHUNKS);
+    let (ciphertexts, proofs): (Vec<Ciphertext>, Vec<SchnorrProof>) = encode(&plaintext)?.iter()
+        .map(|message| {
+            let r = group.random_exponent();
+            let ciphertext = key.encrypt_element(message, &r);
+            let proof = SchnorrProof::prove(&r, &ciphertext.a);
+            randomness.push(r);
+            (ciphertext, proof)
+        })
+        .unzip();
+    let ballot = serde_json::json!({ ENCRYPTED_FIELD: ciphertexts, PROOFS_FIELD: proofs });
+    Ok((ballot, SealOpening { selections: selections.clone(), randomness }))
+}
+
+/// Checks that `opening` re-encrypts to exactly the ciphertexts of a sealed ballot.
+pub fn open_ballot(key: &PublicKey, ballot: &Value, opening: &SealOpening) -> Result<(), String> {
+    let ciphertexts = parse_ballot(ballot)?;
+    let plaintext = serde_json::to_vec(&opening.selections).map_err(|e| e.to_string())?;
+    let messages = encode(&plaintext)?;
+    let matches = opening.randomness.len() == ciphertexts.len()
+        && messages.iter().zip(&opening.randomness).zip(&ciphertexts)
+            .all(|((message, r), ciphertext)| key.encrypt_element(message, r) == *ciphertext);
+    if !matches {
+        return Err("The opening does not match the sealed ballot's ciphertexts".to_string());
+    }
+    Ok(())
+}
+
+/// Reads the ciphertexts of a ballot cast in a mixed poll, which must carry nothing else.
+pub fn parse_ballot(ballot: &Value) -> Result<MixedBallot, String> {
+    let obj = ballot.as_object().ok_or("Encrypted ballots must be objects")?;
+    if let Some(field) = obj.keys().find(|field| ![ENCRYPTED_FIELD, PROOFS_FIELD].contains(&field.as_str())) {
+        return Err(format!("Encrypted ballots cannot carry the plaintext field \"{}\"", field));
+    }
+    ballot_ciphertexts(ballot)?.ok_or_else(|| "Ballot carries no ciphertexts".to_string())
+}
+
+/// Reads and checks the ciphertexts of a ballot on the chain. Ballots without ciphertexts,
+/// such as abstentions, are not mixed.
+pub fn ballot_ciphertexts(ballot: &Value) -> Result<Option<MixedBallot>, String> {
+    let Some(encrypted) = ballot.get(ENCRYPTED_FIELD) else { return Ok(None) };
+    let ciphertexts: MixedBallot = serde_json::from_value(encrypted.clone())
+        .map_err(|e| format!("Malformed ciphertexts: {}", e))?;
+    let proofs: Vec<SchnorrProof> = ballot.get(PROOFS_FIELD)
+        .map(|proofs| serde_json::from_value(proofs.clone()))
+        .transpose()
+        .map_err(|e| format!("Malformed proofs: {}", e))?
+        .ok_or("Ballot carries no proofs of its ciphertexts' randomness")?;
+    if ciphertexts.len() != BALLOT_CHUNKS || proofs.len() != BALLOT_CHUNKS {
+        return Err(format!("Mixed ballots carry exactly {} ciphertexts, each with a proof", BALLOT_CHUNKS));
+    }
+    for (ciphertext, proof) in ciphertexts.iter().zip(&proofs) {
+        if !ciphertext.is_valid() || !proof.verify(&ciphertext.a) {
+            return Err("Ballot carries a ciphertext outside the group or without a valid proof".to_string());
+        }
+    }
+    Ok(Some(ciphertexts))
+}
+
+/// Generators `h, h_1, ..., h_n` whose discrete logs nobody knows, hashed into the group.
+fn generators(n: usize) -> Vec<BigUint> {
+    let group = group();
+    (0..=n as u64)
+        .map(|i| {
+            (0u32..)
+                .map(|attempt| {
+                    let x = crypto::hash_to_below(&group.p, &[b"mixnet-generator", &i.to_be_bytes(), &attempt.to_be_bytes()]);
+                    x.modpow(&BigUint::from(2u32), &group.p)
+                })
+                .find(|h| !h.is_zero() && !h.is_one())
+                .expect("hashing finds a generator")
+        })
+        .collect()
+}
+
+/// `x^-e` for a group element `x`.
+fn pow_neg(x: &BigUint, e: &BigUint) -> BigUint {
+    let group = group();
+    x.modpow(&((&group.q - e % &group.q) % &group.q), &group.p)
+}
+
+/// The statement a shuffle proof is about, hashed, and the challenges `u_j` derived from it.
+fn statement(key: &PublicKey, input: &[MixedBallot], output: &[MixedBallot], commitments: &[BigUint]) -> (BigUint, Vec<BigUint>) {
+    let q = &group().q;
+    let mut elements = vec![&key.y];
+    for ballot in input.iter().chain(output) {
+        elements.extend(ballot.iter().flat_map(|c| [&c.a, &c.b]));
+    }
+    elements.extend(commitments);
+    let seed = proofs::challenge("shuffle-statement", &elements);
+    let seed_bytes = seed.to_bytes_be();
+    let u = (0..input.len() as u64)
+        .map(|j| crypto::hash_to_below(q, &[b"shuffle-u", &seed_bytes, &j.to_be_bytes()]))
+        .collect();
+    (seed, u)
+}
+
+/// Re-encrypts and secretly permutes `input` under `key`, returning the mixed ballots and a
+/// proof that they hold the same plaintexts.
+pub fn shuffle(key: &PublicKey, input: &[MixedBallot]) -> Result<(Vec<MixedBallot>, ShuffleProof), String> {
+    let group = group();
+    let (p, q) = (&group.p, &group.q);
+    let n = input.len();
+    if n == 0 {
+        return Err("There are no encrypted ballots to mix".to_string());
+    }
+    let width = input[0].len();
+    if input.iter().any(|ballot| ballot.len() != width) {
+        return Err("Every ballot in a mix must have the same width".to_string());
+    }
+    let h = generators(n);
+
+    // Output i re-encrypts input permutation[i] with randomness rho[i]
+    let mut permutation: Vec<usize> = (0..n).collect();
+    permutation.shuffle(&mut rand::thread_rng());
+    let mut position = vec![0; n];
+    for (i, j) in permutation.iter().enumerate() {
+        position[*j] = i;
+    }
+    let rho: Vec<Vec<BigUint>> = (0..n).map(|_| (0..width).map(|_| group.random_exponent()).collect()).collect();
+    let output: Vec<MixedBallot> = permutation.iter().zip(&rho)
+        .map(|(j, rho)| input[*j].iter().zip(rho).map(|(c, s)| c.reencrypt(key, s)).collect())
+        .collect();
+
+    let r: Vec<BigUint> = (0..n).map(|_| group.random_exponent()).collect();
+    let permutation_commitments: Vec<BigUint> = (0..n)
+        .map(|j| group.pow_g(&r[j]) * &h[1 + position[j]] % p)
+        .collect();
+    let (seed, u) = statement(key, input, &output, &permutation_commitments);
+    let u_prime: Vec<&BigUint> = permutation.iter().map(|j| &u[*j]).collect();
+
+    let r_hat: Vec<BigUint> = (0..n).map(|_| group.random_exponent()).collect();
+    let mut chain_commitments = Vec::with_capacity(n);
+    let mut previous = h[0].clone();
+    for i in 0..n {
+        previous = group.pow_g(&r_hat[i]) * previous.modpow(u_prime[i], p) % p;
+        chain_commitments.push(previous.clone());
+    }
+
+    let (w1, w2, w3) = (group.random_exponent(), group.random_exponent(), group.random_exponent());
+    let w4: Vec<BigUint> = (0..width).map(|_| group.random_exponent()).collect();
+    let w_hat: Vec<BigUint> = (0..n).map(|_| group.random_exponent()).collect();
+    let w_prime: Vec<BigUint> = (0..n).map(|_| group.random_exponent()).collect();
+
+    let t1 = group.pow_g(&w1);
+    let t2 = group.pow_g(&w2);
+    let t3 = (0..n).fold(group.pow_g(&w3), |t, i| t * h[1 + i].modpow(&w_prime[i], p) % p);
+    let t4_a: Vec<BigUint> = (0..width)
+        .map(|k| (0..n).fold(pow_neg(&group.g, &w4[k]), |t, i| t * output[i][k].a.modpow(&w_prime[i], p) % p))
+        .collect();
+    let t4_b: Vec<BigUint> = (0..width)
+        .map(|k| (0..n).fold(pow_neg(&key.y, &w4[k]), |t, i| t * output[i][k].b.modpow(&w_prime[i], p) % p))
+        .collect();
+    let t_hat: Vec<BigUint> = (0..n)
+        .map(|i| {
+            let previous = if i == 0 { &h[0] } else { &chain_commitments[i - 1] };
+            group.pow_g(&w_hat[i]) * previous.modpow(&w_prime[i], p) % p
+        })
+        .collect();
+    let c = proof_challenge(&seed, &chain_commitments, [&t1, &t2, &t3], &t4_a, &t4_b, &t_hat);
+
+    // v_i is the product of the permuted challenges after position i
+    let mut v = vec![BigUint::one(); n];
+    for i in (0..n.saturating_sub(1)).rev() {
+        v[i] = u_prime[i + 1] * &v[i + 1] % q;
+    }
+    let r_bar = r.iter().fold(BigUint::zero(), |sum, r| (sum + r) % q);
+    let r_hat_sum = (0..n).fold(BigUint::zero(), |sum, i| (sum + &r_hat[i] * &v[i]) % q);
+    let r_tilde = (0..n).fold(BigUint::zero(), |sum, j| (sum + &r[j] * &u[j]) % q);
+    let rho_sum: Vec<BigUint> = (0..width)
+        .map(|k| (0..n).fold(BigUint::zero(), |sum, i| (sum + &rho[i][k] * u_prime[i]) % q))
+        .collect();
+
+    let respond = |w: &BigUint, secret: &BigUint| (w + &c * secret) % q;
+    let proof = ShuffleProof {
+        permutation_commitments,
+        chain_commitments,
+        t1,
+        t2,
+        t3,
+        t4_a,
+        t4_b,
+        t_hat,
+        s1: respond(&w1, &r_bar),
+        s2: respond(&w2, &r_hat_sum),
+        s3: respond(&w3, &r_tilde),
+        s4: (0..width).map(|k| respond(&w4[k], &rho_sum[k])).collect(),
+        s_hat: (0..n).map(|i| respond(&w_hat[i], &r_hat[i])).collect(),
+        s_prime: (0..n).map(|i| respond(&w_prime[i], u_prime[i])).collect(),
+    };
+    Ok((output, proof))
+}
+
+/// The Fiat-Shamir challenge of a shuffle proof.
+fn proof_challenge(
+    seed: &BigUint,
+    chain_commitments: &[BigUint],
+    t: [&BigUint; 3],
+    t4_a: &[BigUint],
+    t4_b: &[BigUint],
+    t_hat: &[BigUint],
+) -> BigUint {
+    let mut elements = vec![seed];
+    elements.extend(chain_commitments);
+    elements.extend(t);
+    elements.extend(t4_a.iter().chain(t4_b).chain(t_hat));
+    proofs::challenge("shuffle", &elements)
+}
+
+impl ShuffleProof {
+    /// Checks that `output` is a re-encryption of a permutation of `input` under `key`.
+    pub fn verify(&self, key: &PublicKey, input: &[MixedBallot], output: &[MixedBallot]) -> bool {
+        let group = group();
+        let (p, q) = (&group.p, &group.q);
+        let n = input.len();
+        let Some(width) = input.first().map(Vec::len) else { return false };
+        let shapes_match = output.len() == n
+            && input.iter().chain(output).all(|ballot| ballot.len() == width)
+            && self.permutation_commitments.len() == n
+            && self.chain_commitments.len() == n
+            && self.t_hat.len() == n
+            && self.s_hat.len() == n
+            && self.s_prime.len() == n
+            && self.t4_a.len() == width
+            && self.t4_b.len() == width
+            && self.s4.len() == width;
+        if !shapes_match
+            || !output.iter().flatten().all(Ciphertext::is_valid)
+            || !self.permutation_commitments.iter().chain(&self.chain_commitments).all(|x| group.is_element(x))
+            || ![&self.s1, &self.s2, &self.s3].into_iter().chain(&self.s4).chain(&self.s_hat).chain(&self.s_prime).all(|s| s < q)
+        {
+            return false;
+        }
+        let h = generators(n);
+        let (seed, u) = statement(key, input, output, &self.permutation_commitments);
+        let c = proof_challenge(&seed, &self.chain_commitments, [&self.t1, &self.t2, &self.t3], &self.t4_a, &self.t4_b, &self.t_hat);
+
+        let Some(h_product_inverse) = crypto::mod_inverse(&h[1..].iter().fold(BigUint::one(), |x, h| x * h % p), p) else { return false };
+        let c_bar = self.permutation_commitments.iter().fold(h_product_inverse, |x, c| x * c % p);
+        let u_product = u.iter().fold(BigUint::one(), |x, u| x * u % q);
+        let Some(h_u_inverse) = crypto::mod_inverse(&h[0].modpow(&u_product, p), p) else { return false };
+        let c_hat = &self.chain_commitments[n - 1] * h_u_inverse % p;
+        let c_tilde = (0..n).fold(BigUint::one(), |x, j| x * self.permutation_commitments[j].modpow(&u[j], p) % p);
+
+        if self.t1 != pow_neg(&c_bar, &c) * group.pow_g(&self.s1) % p
+            || self.t2 != pow_neg(&c_hat, &c) * group.pow_g(&self.s2) % p
+            || self.t3 != (0..n).fold(pow_neg(&c_tilde, &c) * group.pow_g(&self.s3) % p, |x, i| x * h[1 + i].modpow(&self.s_prime[i], p) % p)
+        {
+            return false;
+        }
+        for k in 0..width {
+            let a = (0..n).fold(BigUint::one(), |x, j| x * input[j][k].a.modpow(&u[j], p) % p);
+            let b = (0..n).fold(BigUint::one(), |x, j| x * input[j][k].b.modpow(&u[j], p) % p);
+            let t4_a = (0..n).fold(pow_neg(&a, &c) * pow_neg(&group.g, &self.s4[k]) % p, |x, i| x * output[i][k].a.modpow(&self.s_prime[i], p) % p);
+            let t4_b = (0..n).fold(pow_neg(&b, &c) * pow_neg(&key.y, &self.s4[k]) % p, |x, i| x * output[i][k].b.modpow(&self.s_prime[i], p) % p);
+            if self.t4_a[k] != t4_a || self.t4_b[k] != t4_b {
+                return false;
+            }
+        }
+        (0..n).all(|i| {
+            let previous = if i == 0 { &h[0] } else { &self.chain_commitments[i - 1] };
+            self.t_hat[i] == pow_neg(&self.chain_commitments[i], &c) * group.pow_g(&self.s_hat[i]) % p * previous.modpow(&self.s_prime[i], p) % p
+        })
+    }
+}
+
+/// The ciphertexts of a mixed poll's current ballots, in chain order: the input to the first
+/// shuffle.
+pub fn cast_ballots(blockchain: &ElectionBlockchain) -> Result<Vec<MixedBallot>, String> {
+    blockchain.ballots()
+        .filter_map(|block| ballot_ciphertexts(&block.transactions).map_err(|e| format!("Ballot in block {}: {}", block.index, e)).transpose())
+        .collect()
+}
+
+/// The shuffles recorded on a mixed poll's chain, in order.
+pub fn shuffles(blockchain: &ElectionBlockchain) -> Vec<ShuffleRecord> {
+    blockchain.records(SHUFFLE_RECORD)
+        .filter_map(|record| serde_json::from_value(record.clone()).ok())
+        .collect()
+}
+
+/// The ballots the trustees decrypt: the last shuffle's output, if the poll has been mixed.
+pub fn mixed_ballots(blockchain: &ElectionBlockchain) -> Option<Vec<MixedBallot>> {
+    shuffles(blockchain).pop().map(|shuffle| shuffle.ballots)
+}
+
+/// The decrypted ballots of a mixed poll, once recorded.
+pub fn decryption(blockchain: &ElectionBlockchain) -> Option<MixDecryption> {
+    blockchain.records(MIX_DECRYPTION_RECORD).last()
+        .and_then(|record| serde_json::from_value(record.clone()).ok())
+}
+
+/// The chain a mixed poll's results are counted from once its ballots are decrypted: the
+/// decrypted ballots in mixed order, followed by the chain's other records such as write-in
+/// adjudications and tie-breaks.
+pub fn counted_chain(blockchain: &ElectionBlockchain) -> Option<ElectionBlockchain> {
+    let decrypted = decryption(blockchain)?;
+    let mut counted = ElectionBlockchain::new();
+    for ballot in decrypted.ballots {
+        counted.add_vote_at(ballot, 0).ok()?;
+    }
+    let records = blockchain.chain.iter().skip(1)
+        .filter(|block| block.transactions.get(RECORD_TYPE_FIELD)
+            .and_then(Value::as_str)
+            .is_some_and(|record_type| ![SHUFFLE_RECORD, MIX_DECRYPTION_RECORD].contains(&record_type)));
+    for block in records {
+        counted.add_vote_at(block.transactions.clone(), block.timestamp).ok()?;
+    }
+    Some(counted)
+}
+
+/// The partial decryptions a mixed poll's ballots are decrypted from, with the trustees'
+/// indices: those of the first `threshold` trustees, by name, who submitted one.
+pub fn cooperating<'a>(config: &TrusteeConfig, partials: &'a BTreeMap<String, PartialMix>) -> Vec<(u64, &'a PartialMix)> {
+    partials.iter()
+        .filter_map(|(trustee, partial)| Some((config.index_of(trustee)?, partial)))
+        .take(config.threshold as usize)
+        .collect()
+}
+
+/// Decrypts the mixed ballot at `position` by combining the cooperating trustees' partial
+/// decryptions and reads its selections. Selections that do not decode are `null`, which
+/// counts as spoiled; `None` if a partial decryption does not cover the ballot.
+pub fn decrypt_ballot(ballot: &MixedBallot, position: usize, cooperating: &[(u64, &PartialMix)]) -> Option<Value> {
+    let elements: Option<Vec<BigUint>> = ballot.iter()
+        .enumerate()
+        .map(|(chunk, ciphertext)| {
+            let factors: Option<Vec<(u64, &BigUint)>> = cooperating.iter()
+                .map(|(index, partial)| Some((*index, &partial.get(position)?.get(chunk)?.factor)))
+                .collect();
+            ciphertext.message_with(&trustee::combine_factors(&factors?))
+        })
+        .collect();
+    Some(decode(&elements?).ok()
+        .and_then(|plaintext| serde_json::from_slice(&plaintext).ok())
+        .unwrap_or(Value::Null))
+}
+
+/// Checks a recorded decryption of the final mix: that every trustee's partial decryption
+/// covers every chunk of every ballot with a valid proof under the trustee's verification key
+/// from `ceremony`, and that combining them and counting the decoded selections under the
+/// poll's rules gives exactly the recorded ballots, in order.
+fn verify_decryption(metadata: &PollInput, ceremony: &Ceremony, ballots: &[MixedBallot], decrypted: &MixDecryption) -> Result<(), String> {
+    let config = metadata.settings.trustees.as_ref().ok_or("Mixed polls are decrypted by trustees")?;
+    for (trustee, partial) in &decrypted.partial_decryptions {
+        let verification_key = config.index_of(trustee)
+            .and_then(|index| ceremony.trustee(index))
+            .filter(|t| &t.trustee == trustee)
+            .and_then(|t| t.verification_key.as_ref())
+            .ok_or_else(|| format!("{} has no verification key in the poll's ceremony", trustee))?;
+        let valid = partial.len() == ballots.len()
+            && ballots.iter().zip(partial).all(|(ballot, factors)| {
+                factors.len() == ballot.len()
+                    && ballot.iter().zip(factors).all(|(ciphertext, factor)| trustee::verify_partial(verification_key, ciphertext, factor))
+            });
+        if !valid {
+            return Err(format!("The partial decryption of trustee {} is incomplete or its proofs are invalid", trustee));
+        }
+    }
+    let cooperating = cooperating(config, &decrypted.partial_decryptions);
+    if cooperating.len() < config.threshold as usize {
+        return Err(format!("The ballots were decrypted with fewer than the {} partial decryptions needed", config.threshold));
+    }
+    if decrypted.ballots.len() != ballots.len() {
+        return Err("The decrypted ballots do not match the final mix".to_string());
+    }
+    for (position, (ballot, recorded)) in ballots.iter().zip(&decrypted.ballots).enumerate() {
+        let selections = decrypt_ballot(ballot, position, &cooperating)
+            .ok_or_else(|| format!("Mixed ballot {} could not be decrypted from the partial decryptions", position))?;
+        if &VotingIntegration::counted_mixed_ballot(metadata, &selections) != recorded {
+            return Err(format!("Decrypted ballot {} is not the decryption of mixed ballot {}", position, position));
+        }
+    }
+    Ok(())
+}
+
+/// Verifies a mixed poll's chain without the server: that its blocks are intact and linked,
+/// that every cast ballot's ciphertexts are well-formed, and that each shuffle re-encrypts the
+/// previous pass's ballots. If the final mix has been decrypted, every trustee's partial
+/// decryption is checked against its verification key in `ceremony`, and the ballots are
+/// decrypted again and must match the recorded ones. Returns the final mixed ballots.
+pub fn verify_chain(poll: &Poll, key: &PublicKey, ceremony: &Ceremony) -> Result<Vec<MixedBallot>, String> {
+    let Poll::Election { metadata, blockchain } = poll else {
+        return Err("Only elections are mixed".to_string());
+    };
+    if !blockchain.is_valid() {
+        return Err("Chain integrity check failed: a block's hash or link does not match".to_string());
+    }
+    let decrypted: Option<MixDecryption> = blockchain.records(MIX_DECRYPTION_RECORD).last()
+        .map(|record| serde_json::from_value(record.clone()).map_err(|e| format!("Malformed mix decryption: {}", e)))
+        .transpose()?;
+    let mut ballots = cast_ballots(blockchain)?;
+    let shuffles = shuffles(blockchain);
+    if shuffles.is_empty() && decrypted.is_some() {
+        return Err("Ballots were decrypted without being mixed".to_string());
+    }
+    for (pass, shuffle) in shuffles.into_iter().enumerate() {
+        if shuffle.pass as usize != pass || !shuffle.proof.verify(key, &ballots, &shuffle.ballots) {
+            return Err(format!("Shuffle pass {} is not a verifiable re-encryption of the previous ballots", pass));
+        }
+        ballots = shuffle.ballots;
+    }
+    if let Some(decrypted) = decrypted {
+        if ceremony.election_key.as_ref() != Some(key) {
+            return Err("The ceremony's election key is not the poll's encryption key".to_string());
+        }
+        verify_decryption(metadata, ceremony, &ballots, &decrypted)?;
+    }
+    Ok(ballots)
+}
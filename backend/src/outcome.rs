@@ -0,0 +1,229 @@
+use std::collections::BTreeMap;
+use serde::{Serialize, Deserialize};
+use crate::ballot::Turnout;
+use crate::tie_break::TieResolution;
+
+/// Minimum participation for a result to stand.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Quorum {
+    /// At least this many voters must take part, e.g. `{"absolute": 50}`.
+    Absolute(u64),
+    /// At least this percentage of eligible voters must take part, e.g. `{"percent": 25}`.
+    Percent(f64),
+}
+
+/// Share of the base the leading option needs in order to pass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Fraction {
+    /// More than half.
+    #[default]
+    SimpleMajority,
+    /// At least two thirds.
+    TwoThirds,
+    /// At least three fifths.
+    ThreeFifths,
+}
+
+impl Fraction {
+    /// Smallest number of votes out of `base` that meets this fraction.
+    pub fn required_votes(&self, base: u64) -> u64 {
+        match self {
+            Fraction::SimpleMajority => base / 2 + 1,
+            Fraction::TwoThirds => (base * 2).div_ceil(3),
+            Fraction::ThreeFifths => (base * 3).div_ceil(5),
+        }
+    }
+
+    fn describe(&self) -> &'static str {
+        match self {
+            Fraction::SimpleMajority => "a simple majority (more than 1/2)",
+            Fraction::TwoThirds => "a two-thirds majority (at least 2/3)",
+            Fraction::ThreeFifths => "a three-fifths majority (at least 3/5)",
+        }
+    }
+}
+
+/// What the passing fraction is taken of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThresholdBase {
+    /// Votes cast for an option (plus abstentions, if they count).
+    #[default]
+    VotesCast,
+    /// Every eligible voter, whether or not they took part.
+    Members,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Threshold {
+    #[serde(default)]
+    pub fraction: Fraction,
+    #[serde(default)]
+    pub base: ThresholdBase,
+}
+
+/// Rules deciding whether a poll's leading option carries, declared as `rules` on a poll.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DecisionRules {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quorum: Option<Quorum>,
+    #[serde(default)]
+    pub threshold: Threshold,
+    /// Whether abstentions count towards the quorum and the votes-cast base.
+    #[serde(default)]
+    pub abstentions_count: bool,
+    /// Number of eligible voters. Defaults to the size of the poll's electorate when omitted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub members: Option<u64>,
+}
+
+impl DecisionRules {
+    /// Checks the rules are internally consistent.
+    pub fn validate(&self) -> Result<(), String> {
+        match self.quorum {
+            Some(Quorum::Absolute(0)) => Err("An absolute quorum must be at least 1".to_string()),
+            Some(Quorum::Percent(p)) if !(p > 0.0 && p <= 100.0) => {
+                Err("A percentage quorum must be greater than 0 and at most 100".to_string())
+            },
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutcomeStatus {
+    Passed,
+    Failed,
+    NoQuorum,
+    Tie,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuorumCheck {
+    pub present: u64,
+    pub required: u64,
+    pub met: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThresholdCheck {
+    pub rule: Threshold,
+    pub base: u64,
+    pub votes: u64,
+    pub required: u64,
+    pub met: bool,
+}
+
+/// The computed outcome of a poll or contest, with every figure used to reach it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Outcome {
+    pub outcome: OutcomeStatus,
+    pub leading_options: Vec<String>,
+    pub members: u64,
+    pub quorum: Option<QuorumCheck>,
+    pub threshold: ThresholdCheck,
+    /// The arithmetic behind the outcome, one step per line.
+    pub arithmetic: Vec<String>,
+    /// How a tie for the lead was settled, if there was one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tie_break: Option<TieResolution>,
+}
+
+impl Outcome {
+    /// Applies a tie-break to a tied outcome. A resolved tie leaves the most favoured option
+    /// leading, which passes or fails on the threshold it shares with the other tied options.
+    pub fn break_tie(&mut self, resolution: TieResolution) {
+        if self.outcome != OutcomeStatus::Tie {
+            return;
+        }
+        self.arithmetic.push(resolution.detail.clone());
+        if resolution.resolved {
+            self.leading_options = resolution.ranking.iter().take(1).cloned().collect();
+            self.outcome = if self.threshold.met { OutcomeStatus::Passed } else { OutcomeStatus::Failed };
+        }
+        self.tie_break = Some(resolution);
+    }
+}
+
+/// Decides whether the leading option in `counts` carries under `rules`.
+///
+/// Quorum is checked first, then a tie for the lead, then the passing threshold. `members` is
+/// the number of eligible voters used when `rules.members` is not set.
+pub fn decide(rules: &DecisionRules, counts: &BTreeMap<String, u64>, turnout: &Turnout, members: u64) -> Outcome {
+    let members = rules.members.unwrap_or(members);
+    let abstentions = if rules.abstentions_count { turnout.abstained as u64 } else { 0 };
+    let mut arithmetic = Vec::new();
+
+    let quorum = rules.quorum.map(|quorum| {
+        let present = turnout.ballots_cast as u64 - turnout.abstained as u64 + abstentions;
+        let required = match quorum {
+            Quorum::Absolute(n) => {
+                arithmetic.push(format!("Quorum: {} voters required", n));
+                n
+            },
+            Quorum::Percent(p) => {
+                let required = (members as f64 * p / 100.0).ceil() as u64;
+                arithmetic.push(format!("Quorum: {}% of {} members = {} voters required", p, members, required));
+                required
+            },
+        };
+        let met = present >= required;
+        arithmetic.push(format!(
+            "Present: {} ballots cast{} = {} ({})",
+            turnout.ballots_cast,
+            if rules.abstentions_count {
+                String::new()
+            } else {
+                format!(" - {} abstentions", turnout.abstained)
+            },
+            present,
+            if met { "quorum met" } else { "quorum not met" }
+        ));
+        QuorumCheck { present, required, met }
+    });
+
+    let top = counts.values().copied().max().unwrap_or(0);
+    let leading_options: Vec<String> = counts.iter()
+        .filter(|(_, &votes)| top > 0 && votes == top)
+        .map(|(option, _)| option.clone())
+        .collect();
+
+    let votes_cast: u64 = counts.values().sum();
+    let base = match rules.threshold.base {
+        ThresholdBase::VotesCast => {
+            arithmetic.push(format!(
+                "Base: {} votes for options + {} abstentions = {}",
+                votes_cast, abstentions, votes_cast + abstentions
+            ));
+            votes_cast + abstentions
+        },
+        ThresholdBase::Members => {
+            arithmetic.push(format!("Base: {} members", members));
+            members
+        },
+    };
+    let required = rules.threshold.fraction.required_votes(base);
+    let met = top > 0 && top >= required;
+    arithmetic.push(format!(
+        "Threshold: {} of {} requires {} votes; the leading option has {} ({})",
+        rules.threshold.fraction.describe(), base, required, top,
+        if met { "met" } else { "not met" }
+    ));
+    let threshold = ThresholdCheck { rule: rules.threshold, base, votes: top, required, met };
+
+    let outcome = if quorum.as_ref().is_some_and(|q| !q.met) {
+        OutcomeStatus::NoQuorum
+    } else if leading_options.len() > 1 {
+        arithmetic.push(format!("Tie for the lead between {}", leading_options.join(", ")));
+        OutcomeStatus::Tie
+    } else if met {
+        OutcomeStatus::Passed
+    } else {
+        OutcomeStatus::Failed
+    };
+
+    Outcome { outcome, leading_options, members, quorum, threshold, arithmetic, tie_break: None }
+}
@@ -3,15 +3,88 @@ use serde::{Serialize, Deserialize};
 use serde_json::Value;
 use crate::blockchain::Blockchain;
 use crate::election_blockchain::ElectionBlockchain;
+use crate::contest::parse_contests;
+use crate::budgeting::projects_from_options;
+use crate::encrypted_tally::EncryptedLayout;
+use crate::trustee::TrusteeConfig;
+use crate::outcome::DecisionRules;
+use crate::tie_break::TieBreakPolicy;
+use chrono::{DateTime, Utc};
 use sqlx::{Pool, Postgres, Row};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Where an entry was added to a poll's chain. Stored with the entry so the chain is rebuilt in
+/// the same order and with the same block timestamps, and so the same hashes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainPosition {
+    /// Index of the block holding the entry.
+    pub sequence: i64,
+    /// Timestamp of the block in milliseconds.
+    pub chained_at: i64,
+}
+
+/// Optional per-poll configuration used by the specialised poll types.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PollSettings {
+    /// Voice credits each voter may spend in a "quadratic" poll.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credit_budget: Option<u32>,
+    /// Cost of each option, in the same order as `options`, for a "budgeting" poll.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub costs: Option<Vec<u64>>,
+    /// Total funds available to a "budgeting" poll.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget: Option<u64>,
+    /// Quorum and passing threshold used to compute the poll's outcome.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rules: Option<DecisionRules>,
+    /// How ties in outcomes and STV counts are settled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tie_break: Option<TieBreakPolicy>,
+    /// Voters may recast their ballot until this time; each recast supersedes the previous one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revote_until: Option<DateTime<Utc>>,
+    /// Ballots are no longer accepted after this time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub closes_at: Option<DateTime<Utc>>,
+    /// Ballots are cast as salted hash commitments until `closes_at` and revealed until this
+    /// time; only revealed ballots that match their commitment are counted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reveal_until: Option<DateTime<Utc>>,
+    /// Smallest group reported in statistical breakdowns such as turnout by state; smaller
+    /// groups are suppressed. Defaults to `DEFAULT_MIN_CELL_SIZE`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_cell_size: Option<u32>,
+    /// Record each voter's age bracket and region at cast time and report results broken down
+    /// by them.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub demographics: bool,
+    /// Ballots are cast anonymously with blind-signed credentials instead of voter ids.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub anonymous_credentials: bool,
+    /// Ballots carry an exponential-ElGamal ciphertext per option instead of plaintext choices;
+    /// the tally is aggregated homomorphically and decrypted once the poll closes.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub encrypted: bool,
+    /// Trustees who jointly generate an encrypted poll's key, so that no single server can
+    /// decrypt its tally. Required for encrypted elections.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trustees: Option<TrusteeConfig>,
+    /// Encrypted election ballots are encrypted whole instead of per option, then shuffled by
+    /// a verifiable re-encryption mix after the poll closes and decrypted one by one, so ranked
+    /// and write-in contests can be encrypted too.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub mixnet: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PollInput {
     pub title: String,
     pub question: String,
     pub options: Vec<String>,
     pub is_public: bool,
-    pub poll_type: Option<String>, // "normal" or "election"
+    pub poll_type: Option<String>, // "normal", "election", "weighted", "quadratic" or "budgeting"
+    #[serde(flatten)]
+    pub settings: PollSettings,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,6 +104,16 @@ pub struct PollManager {
     pub pool: Pool<Postgres>,
 }
 
+impl Poll {
+    /// The poll's definition, regardless of which kind of chain backs it.
+    pub fn metadata(&self) -> &PollInput {
+        match self {
+            Poll::Normal { metadata, .. } => metadata,
+            Poll::Election { metadata, .. } => metadata,
+        }
+    }
+}
+
 impl PollManager {
     pub fn new(pool: Pool<Postgres>) -> Self {
         PollManager {
@@ -44,6 +127,81 @@ impl PollManager {
         use uuid::Uuid;
         // If poll_type is "election", force the poll_id to "election".
         let poll_type = poll.poll_type.clone().unwrap_or_else(|| "normal".to_string());
+        if poll_type == "election" {
+            let contests = parse_contests(&poll.options).map_err(sqlx::Error::Protocol)?;
+            if poll.settings.anonymous_credentials && contests.iter().any(|c| !c.jurisdictions.is_empty()) {
+                return Err(sqlx::Error::Protocol(
+                    "Anonymous ballots cannot be styled, so contests cannot be limited to jurisdictions".to_string()
+                ));
+            }
+        }
+        if poll.settings.anonymous_credentials {
+            // Weights, demographics and revoting all need to know who cast a ballot
+            if poll_type == "weighted" || poll.settings.demographics || poll.settings.revote_until.is_some() {
+                return Err(sqlx::Error::Protocol(
+                    "Anonymous credentials cannot be combined with weights, demographics or revoting".to_string()
+                ));
+            }
+        }
+        if poll.settings.mixnet {
+            if !poll.settings.encrypted || poll_type != "election" {
+                return Err(sqlx::Error::Protocol("Only encrypted elections can be mixed".to_string()));
+            }
+            // Eligibility for a contest cannot be checked on a ballot encrypted whole
+            let contests = parse_contests(&poll.options).map_err(sqlx::Error::Protocol)?;
+            if contests.iter().any(|c| !c.jurisdictions.is_empty()) {
+                return Err(sqlx::Error::Protocol("Mixed elections cannot limit contests to jurisdictions".to_string()));
+            }
+        } else if poll.settings.encrypted {
+            EncryptedLayout::for_poll(&poll).map_err(sqlx::Error::Protocol)?;
+        }
+        if poll.settings.encrypted {
+            if poll.settings.closes_at.is_none() {
+                return Err(sqlx::Error::Protocol("Encrypted polls require closes_at, after which the tally is decrypted".to_string()));
+            }
+            // Outcomes and demographic breakdowns are computed from plaintext ballots
+            if poll.settings.rules.is_some() || poll.settings.demographics {
+                return Err(sqlx::Error::Protocol("Encrypted polls cannot be combined with rules or demographics".to_string()));
+            }
+            if poll_type == "election" && poll.settings.trustees.is_none() {
+                return Err(sqlx::Error::Protocol("Encrypted elections require trustees to hold the election key".to_string()));
+            }
+        }
+        if let Some(trustees) = &poll.settings.trustees {
+            if !poll.settings.encrypted {
+                return Err(sqlx::Error::Protocol("Only encrypted polls have trustees".to_string()));
+            }
+            trustees.validate().map_err(sqlx::Error::Protocol)?;
+        }
+        if let Some(reveal_until) = poll.settings.reveal_until {
+            if poll.settings.closes_at.is_none_or(|closes_at| closes_at >= reveal_until) {
+                return Err(sqlx::Error::Protocol("Commit-reveal polls require a closes_at before reveal_until".to_string()));
+            }
+            // Reveals are matched to commitments by the voter's ballot handle, and each voter
+            // commits to one plain ballot
+            if poll.settings.encrypted || poll.settings.anonymous_credentials || poll.settings.revote_until.is_some()
+                || poll.settings.demographics || poll_type == "weighted" {
+                return Err(sqlx::Error::Protocol(
+                    "Commit-reveal polls cannot be combined with encryption, anonymous credentials, revoting, demographics or weights".to_string()
+                ));
+            }
+        }
+        if poll_type == "quadratic" && poll.settings.credit_budget.unwrap_or(0) == 0 {
+            return Err(sqlx::Error::Protocol("Quadratic polls require a positive credit_budget".to_string()));
+        }
+        if poll_type == "budgeting" {
+            if poll.settings.budget.unwrap_or(0) == 0 {
+                return Err(sqlx::Error::Protocol("Budgeting polls require a positive budget".to_string()));
+            }
+            let costs = poll.settings.costs.clone().unwrap_or_default();
+            projects_from_options(&poll.options, &costs).map_err(sqlx::Error::Protocol)?;
+        }
+        if let Some(rules) = &poll.settings.rules {
+            rules.validate().map_err(sqlx::Error::Protocol)?;
+        }
+        if poll.settings.min_cell_size == Some(0) {
+            return Err(sqlx::Error::Protocol("min_cell_size must be at least 1".to_string()));
+        }
         let poll_id = if poll_type == "election" {
             "election".to_string()
         } else {
@@ -58,7 +216,7 @@ impl PollManager {
     pub async fn create_named_poll(&mut self, poll_id: &str, poll: PollInput) -> Result<(), sqlx::Error> {
         if self.polls.contains_key(poll_id) {
             return Err(sqlx::Error::Protocol(
-                format!("Poll ID '{}' already exists in memory", poll_id).into(),
+                format!("Poll ID '{}' already exists in memory", poll_id),
             ));
         }
 
@@ -77,8 +235,8 @@ impl PollManager {
     ) -> Result<(), sqlx::Error> {
         sqlx::query(
             r#"
-            INSERT INTO polls (poll_id, title, question, options, is_public, poll_type)
-            VALUES ($1, $2, $3, $4, $5, $6)
+            INSERT INTO polls (poll_id, title, question, options, is_public, poll_type, settings)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
             "#
         )
         .bind(poll_id)
@@ -87,6 +245,7 @@ impl PollManager {
         .bind(serde_json::to_value(&poll.options).unwrap())
         .bind(poll.is_public)
         .bind(poll_type)
+        .bind(serde_json::to_value(&poll.settings).unwrap())
         .execute(&self.pool)
         .await?;
         Ok(())
@@ -135,7 +294,7 @@ impl PollManager {
 
             let poll_instance = Poll::Election {
                 metadata: poll,
-                blockchain: blockchain,
+                blockchain,
             };
             self.polls.insert(poll_id.to_string(), poll_instance);
 
@@ -175,7 +334,7 @@ impl PollManager {
 
             let poll_instance = Poll::Normal {
                 metadata: poll,
-                blockchain: blockchain,
+                blockchain,
             };
             self.polls.insert(poll_id.to_string(), poll_instance);
         }
@@ -187,7 +346,7 @@ impl PollManager {
     pub async fn load_poll(&mut self, poll_id: &str) -> Result<(), sqlx::Error> {
         let row = sqlx::query(
             r#"
-            SELECT poll_id, title, question, options, is_public, poll_type
+            SELECT poll_id, title, question, options, is_public, poll_type, settings
             FROM polls
             WHERE poll_id = $1
             "#
@@ -202,6 +361,8 @@ impl PollManager {
         let options: Vec<String> = serde_json::from_value(options_value).unwrap_or_default();
         let is_public: bool = row.try_get("is_public")?;
         let poll_type: String = row.try_get("poll_type")?;
+        let settings_value: Value = row.try_get("settings")?;
+        let settings: PollSettings = serde_json::from_value(settings_value).unwrap_or_default();
 
         let poll_input = PollInput {
             title,
@@ -209,6 +370,7 @@ impl PollManager {
             options,
             is_public,
             poll_type: Some(poll_type.clone()),
+            settings,
         };
 
         // Use build_in_memory_poll logic to unify approach
@@ -217,10 +379,20 @@ impl PollManager {
     }
 
     /// Adds a vote to the specified poll in memory. This does NOT persist the block to DB.
-    pub fn add_vote(&mut self, poll_id: &str, vote_data: Value) -> Result<(), String> {
+    /// Returns where the vote was chained.
+    pub fn add_vote(&mut self, poll_id: &str, vote_data: Value) -> Result<ChainPosition, String> {
+        self.add_vote_at(poll_id, vote_data, Utc::now().timestamp_millis())
+    }
+
+    /// Adds a vote to a poll's chain in a block stamped with the given time in milliseconds, so
+    /// votes replayed at startup keep the time they were cast. Returns where the vote was chained.
+    pub fn add_vote_at(&mut self, poll_id: &str, vote_data: Value, timestamp: i64) -> Result<ChainPosition, String> {
         if let Some(poll) = self.polls.get_mut(poll_id) {
-            match poll {
-                Poll::Election { blockchain, .. } => blockchain.add_vote(vote_data),
+            let sequence = match poll {
+                Poll::Election { blockchain, .. } => {
+                    blockchain.add_vote_at(vote_data, timestamp)?;
+                    blockchain.chain.len() - 1
+                },
                 Poll::Normal { blockchain, .. } => {
                     let vote_obj = if vote_data.is_object() {
                         vote_data
@@ -229,15 +401,35 @@ impl PollManager {
                     } else {
                         return Err("Normal poll expects a plain string or JSON object vote".to_string());
                     };
-                    blockchain.add_block(vote_obj);
-                    Ok(())
+                    blockchain.add_block_at(vote_obj, timestamp);
+                    blockchain.chain.len() - 1
                 }
-            }
+            };
+            Ok(ChainPosition { sequence: sequence as i64, chained_at: timestamp })
         } else {
             Err(format!("Poll '{}' does not exist", poll_id))
         }
     }
 
+    /// Replaces a poll's chain with a fresh one built from its stored votes and records, given
+    /// in chain order with their block timestamps in milliseconds. Returns how many entries
+    /// could not be added.
+    pub fn rebuild_chain(&mut self, poll_id: &str, entries: Vec<(Value, i64)>) -> Result<usize, String> {
+        match self.polls.get_mut(poll_id) {
+            Some(Poll::Election { blockchain, .. }) => *blockchain = ElectionBlockchain::new(),
+            Some(Poll::Normal { blockchain, .. }) => *blockchain = Blockchain::new(),
+            None => return Err(format!("Poll '{}' does not exist", poll_id)),
+        }
+        let mut failed = 0;
+        for (vote_data, created_at) in entries {
+            if let Err(e) = self.add_vote_at(poll_id, vote_data, created_at) {
+                eprintln!("Failed to add vote to poll {}: {}", poll_id, e);
+                failed += 1;
+            }
+        }
+        Ok(failed)
+    }
+
     /// Persists a newly added block to the database (once you have it).
     pub async fn persist_block(&self, poll_id: &str, block: &crate::block::Block) -> Result<(), sqlx::Error> {
         let transactions_json = serde_json::to_string(&block.transactions).unwrap_or_default();
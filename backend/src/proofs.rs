@@ -0,0 +1,162 @@
+use num_bigint_dig::BigUint;
+use serde::{Serialize, Deserialize};
+use crate::crypto::{self, hex_biguint};
+use crate::elgamal::{group, Ciphertext, PublicKey};
+
+/// Non-interactive proof of knowledge of the discrete log of a public value, made
+/// non-interactive with the Fiat-Shamir heuristic.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchnorrProof {
+    #[serde(with = "hex_biguint")]
+    pub commitment: BigUint,
+    #[serde(with = "hex_biguint")]
+    pub response: BigUint,
+}
+
+/// Non-interactive Chaum-Pedersen proof that two values share a discrete log: that
+/// `public = g^x` and `result = base^x` for the same secret `x`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChaumPedersenProof {
+    #[serde(with = "hex_biguint")]
+    pub pad: BigUint,
+    #[serde(with = "hex_biguint")]
+    pub data: BigUint,
+    #[serde(with = "hex_biguint")]
+    pub challenge: BigUint,
+    #[serde(with = "hex_biguint")]
+    pub response: BigUint,
+}
+
+/// Disjunctive Chaum-Pedersen proof that a ciphertext encrypts an integer in `0..=max` without
+/// revealing which. There is one branch per candidate plaintext `j`, proving that `(a, b / g^j)`
+/// is an encryption of zero; every branch but the true one is simulated, and the branch
+/// challenges must sum to the Fiat-Shamir challenge so at most one can be.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RangeProof {
+    pub branches: Vec<ChaumPedersenProof>,
+}
+
+/// The Fiat-Shamir challenge for a proof, hashed from its label and every group element
+/// involved.
+pub fn challenge(label: &str, elements: &[&BigUint]) -> BigUint {
+    let bytes: Vec<Vec<u8>> = elements.iter().map(|element| element.to_bytes_be()).collect();
+    let mut parts: Vec<&[u8]> = vec![label.as_bytes()];
+    parts.extend(bytes.iter().map(|b| b.as_slice()));
+    crypto::hash_to_below(&group().q, &parts)
+}
+
+impl SchnorrProof {
+    /// Proves knowledge of `secret`, where `public = g^secret`.
+    pub fn prove(secret: &BigUint, public: &BigUint) -> Self {
+        let group = group();
+        let nonce = group.random_exponent();
+        let commitment = group.pow_g(&nonce);
+        let c = challenge("schnorr", &[public, &commitment]);
+        let response = (nonce + c * secret) % &group.q;
+        SchnorrProof { commitment, response }
+    }
+
+    /// Checks the proof against `public`.
+    pub fn verify(&self, public: &BigUint) -> bool {
+        let group = group();
+        if !group.is_element(public) || !group.is_element(&self.commitment) || self.response >= group.q {
+            return false;
+        }
+        let c = challenge("schnorr", &[public, &self.commitment]);
+        group.pow_g(&self.response) == &self.commitment * public.modpow(&c, &group.p) % &group.p
+    }
+}
+
+impl ChaumPedersenProof {
+    /// Proves that `public = g^secret` and `result = base^secret`.
+    pub fn prove(secret: &BigUint, public: &BigUint, base: &BigUint, result: &BigUint) -> Self {
+        let group = group();
+        let nonce = group.random_exponent();
+        let pad = group.pow_g(&nonce);
+        let data = base.modpow(&nonce, &group.p);
+        let c = challenge("chaum-pedersen", &[public, base, result, &pad, &data]);
+        let response = (nonce + &c * secret) % &group.q;
+        ChaumPedersenProof { pad, data, challenge: c, response }
+    }
+
+    /// Checks the proof that `public` and `result` share a discrete log to the bases `g` and
+    /// `base`.
+    pub fn verify(&self, public: &BigUint, base: &BigUint, result: &BigUint) -> bool {
+        let group = group();
+        if self.response >= group.q || !group.is_element(&self.pad) || !group.is_element(&self.data) {
+            return false;
+        }
+        let c = challenge("chaum-pedersen", &[public, base, result, &self.pad, &self.data]);
+        c == self.challenge && self.satisfies(public, base, result)
+    }
+
+    /// Whether the proof's response answers its challenge for the given values, without checking
+    /// where the challenge came from.
+    fn satisfies(&self, public: &BigUint, base: &BigUint, result: &BigUint) -> bool {
+        let group = group();
+        self.response < group.q
+            && self.challenge < group.q
+            && group.pow_g(&self.response) == &self.pad * public.modpow(&self.challenge, &group.p) % &group.p
+            && base.modpow(&self.response, &group.p) == &self.data * result.modpow(&self.challenge, &group.p) % &group.p
+    }
+}
+
+impl RangeProof {
+    /// Proves that `ciphertext`, encrypted to `key` with randomness `r`, encrypts `m <= max`.
+    /// Run by the voter's client, which alone knows `m` and `r`.
+    pub fn prove(key: &PublicKey, ciphertext: &Ciphertext, m: u64, r: &BigUint, max: u64) -> Self {
+        let group = group();
+        let nonce = group.random_exponent();
+        let mut branches: Vec<ChaumPedersenProof> = (0..=max)
+            .map(|j| {
+                if j == m {
+                    let pad = group.pow_g(&nonce);
+                    let data = key.y.modpow(&nonce, &group.p);
+                    return ChaumPedersenProof { pad, data, challenge: BigUint::default(), response: BigUint::default() };
+                }
+                // A simulated branch picks its challenge and response and solves for the
+                // commitments; inverses are powers to q - c as every value is in the subgroup
+                let challenge = group.random_exponent();
+                let response = group.random_exponent();
+                let inverse = &group.q - &challenge;
+                let pad = group.pow_g(&response) * ciphertext.a.modpow(&inverse, &group.p) % &group.p;
+                let data = key.y.modpow(&response, &group.p)
+                    * Self::shifted(ciphertext, j).modpow(&inverse, &group.p) % &group.p;
+                ChaumPedersenProof { pad, data, challenge, response }
+            })
+            .collect();
+
+        let c = Self::challenge(key, ciphertext, &branches);
+        let simulated = branches.iter().fold(BigUint::default(), |sum, branch| (sum + &branch.challenge) % &group.q);
+        let real = &mut branches[m as usize];
+        real.challenge = (c + &group.q - simulated) % &group.q;
+        real.response = (nonce + &real.challenge * r) % &group.q;
+        RangeProof { branches }
+    }
+
+    /// Checks that `ciphertext`, a valid ciphertext under `key`, encrypts an integer in `0..=max`.
+    pub fn verify(&self, key: &PublicKey, ciphertext: &Ciphertext, max: u64) -> bool {
+        let group = group();
+        if self.branches.len() as u64 != max + 1 {
+            return false;
+        }
+        let sum = self.branches.iter().fold(BigUint::default(), |sum, branch| (sum + &branch.challenge) % &group.q);
+        sum == Self::challenge(key, ciphertext, &self.branches)
+            && self.branches.iter().enumerate()
+                .all(|(j, branch)| branch.satisfies(&ciphertext.a, &key.y, &Self::shifted(ciphertext, j as u64)))
+    }
+
+    /// `b / g^j`, which is `y^r` when the ciphertext encrypts `j`.
+    fn shifted(ciphertext: &Ciphertext, j: u64) -> BigUint {
+        let group = group();
+        &ciphertext.b * group.pow_g(&(&group.q - j)) % &group.p
+    }
+
+    /// The challenge the branch challenges must sum to, hashed from the key, the ciphertext and
+    /// every branch's commitments.
+    fn challenge(key: &PublicKey, ciphertext: &Ciphertext, branches: &[ChaumPedersenProof]) -> BigUint {
+        let mut elements = vec![&key.y, &ciphertext.a, &ciphertext.b];
+        elements.extend(branches.iter().flat_map(|branch| [&branch.pad, &branch.data]));
+        challenge("range", &elements)
+    }
+}
@@ -0,0 +1,61 @@
+use hmac::{Hmac, Mac, NewMac};
+use sha2::Sha256;
+use uuid::Uuid;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Environment variable holding the server secret that pseudonym keys are derived from.
+pub const PSEUDONYM_SECRET_VAR: &str = "PSEUDONYM_SECRET";
+
+/// Placeholder values from example configuration. Anyone who knows the secret can link
+/// pseudonyms back to voter ids, so these are refused.
+const PLACEHOLDER_SECRETS: [&str; 6] = [
+    "change-me",
+    "changeme",
+    "change_me",
+    "secret",
+    "development-pseudonym-secret",
+    "your-secret-here",
+];
+
+/// The server secret from `PSEUDONYM_SECRET`. Fails if it is unset, empty or a known placeholder.
+pub fn server_secret() -> Result<Vec<u8>, String> {
+    let secret = std::env::var(PSEUDONYM_SECRET_VAR)
+        .map_err(|_| format!("{} must be set to a private random value", PSEUDONYM_SECRET_VAR))?;
+    let trimmed = secret.trim();
+    if trimmed.is_empty() {
+        return Err(format!("{} must not be empty", PSEUDONYM_SECRET_VAR));
+    }
+    if PLACEHOLDER_SECRETS.iter().any(|placeholder| trimmed.eq_ignore_ascii_case(placeholder)) {
+        return Err(format!("{} is set to the placeholder '{}'; set it to a private random value", PSEUDONYM_SECRET_VAR, trimmed));
+    }
+    Ok(secret.into_bytes())
+}
+
+/// A random secret for services that do not need pseudonyms to outlive the process.
+pub fn ephemeral_secret() -> Vec<u8> {
+    new_salt().into_bytes()
+}
+
+/// A fresh random salt for a poll's pseudonym key.
+pub fn new_salt() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+fn hmac_sha256(key: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(message);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Derives a poll's pseudonym key from the server secret and the poll's current salt.
+pub fn poll_key(secret: &[u8], poll_id: &str, salt: &str) -> Vec<u8> {
+    hmac_sha256(secret, format!("{}:{}", poll_id, salt).as_bytes())
+}
+
+/// The pseudonym that stands in for a voter id on a poll's chain: a hex HMAC-SHA256 of the
+/// voter id under the poll's key. Pseudonyms are stable within a poll and unlinkable across
+/// polls or key rotations without the server secret.
+pub fn pseudonym(poll_key: &[u8], voter_id: &str) -> String {
+    hex::encode(hmac_sha256(poll_key, voter_id.as_bytes()))
+}
@@ -0,0 +1,67 @@
+use std::collections::BTreeMap;
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+use crate::blockchain::Blockchain;
+
+/// Per-option totals for a quadratic poll.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QuadraticTally {
+    /// Votes for minus votes against.
+    pub net_votes: i64,
+    /// Voice credits spent on this option (the sum of each voter's votes squared).
+    pub credits_spent: u64,
+    /// Number of ballots that allocated any votes to this option.
+    pub voters: u32,
+}
+
+/// Returns the voice credits a ballot costs: casting `k` votes on an option costs `k²` credits.
+pub fn credits_for(allocations: &BTreeMap<String, i64>) -> u64 {
+    allocations.values().map(|votes| votes.unsigned_abs().saturating_pow(2)).sum()
+}
+
+/// Validates a quadratic ballot of the form `{"allocations": {"Option A": 3, "Option B": -1}}`.
+///
+/// Negative votes count against an option. Returns the allocations if every option exists and
+/// the ballot's total cost fits within `credit_budget`.
+pub fn validate_ballot(options: &[String], credit_budget: u32, vote: &Value) -> Result<BTreeMap<String, i64>, String> {
+    let raw = vote.get("allocations")
+        .and_then(|a| a.as_object())
+        .ok_or_else(|| "Quadratic ballots must contain an \"allocations\" object".to_string())?;
+
+    let mut allocations = BTreeMap::new();
+    for (option, votes) in raw {
+        if !options.contains(option) {
+            return Err(format!("'{}' is not an option in this poll", option));
+        }
+        let votes = votes.as_i64()
+            .ok_or_else(|| format!("Votes for '{}' must be a whole number", option))?;
+        if votes != 0 {
+            allocations.insert(option.clone(), votes);
+        }
+    }
+
+    let cost = credits_for(&allocations);
+    if cost > credit_budget as u64 {
+        return Err(format!("Ballot costs {} credits but the budget is {}", cost, credit_budget));
+    }
+    Ok(allocations)
+}
+
+/// Tallies net votes and credits spent per option across every ballot on the chain.
+pub fn tally(blockchain: &Blockchain) -> BTreeMap<String, QuadraticTally> {
+    let mut totals: BTreeMap<String, QuadraticTally> = BTreeMap::new();
+    for (_, transaction) in blockchain.ballots() {
+        let Some(allocations) = transaction.get("allocations").and_then(|a| a.as_object()) else {
+            continue;
+        };
+        for (option, votes) in allocations {
+            if let Some(votes) = votes.as_i64() {
+                let entry = totals.entry(option.clone()).or_default();
+                entry.net_votes += votes;
+                entry.credits_spent += votes.unsigned_abs().saturating_pow(2);
+                entry.voters += 1;
+            }
+        }
+    }
+    totals
+}
@@ -0,0 +1,310 @@
+use std::collections::{BTreeMap, BTreeSet};
+use chrono::{DateTime, Duration, DurationRound, NaiveDate, Utc};
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+use crate::ballot::BallotStatus;
+use crate::election_blockchain::BALLOT_METADATA_FIELDS;
+use crate::jurisdiction::lookup_zip;
+
+/// Smallest group reported in a statistical breakdown unless a poll sets `min_cell_size`.
+pub const DEFAULT_MIN_CELL_SIZE: u32 = 10;
+
+/// Turnout of registered voters in one state or zip prefix. Counts are withheld (`None`) when the
+/// cell is suppressed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TurnoutCell {
+    pub key: String,
+    pub registered: Option<u64>,
+    pub voted: Option<u64>,
+    pub turnout_percent: Option<f64>,
+    pub suppressed: bool,
+}
+
+impl TurnoutCell {
+    /// Builds a cell, suppressing it unless at least `k` registered voters voted and at least `k`
+    /// did not, so the cell never reveals whether a particular voter took part.
+    fn new(key: String, registered: u64, voted: u64, k: u32) -> Self {
+        let k = k as u64;
+        if voted < k || registered - voted < k {
+            return TurnoutCell { key, registered: None, voted: None, turnout_percent: None, suppressed: true };
+        }
+        TurnoutCell {
+            key,
+            registered: Some(registered),
+            voted: Some(voted),
+            turnout_percent: Some(percent(voted, registered)),
+            suppressed: false,
+        }
+    }
+}
+
+/// Registered voters who had voted by the end of an hour. Hours in which fewer than `k` voters
+/// arrived are withheld and folded into the next published hour.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TurnoutBucket {
+    pub hour: DateTime<Utc>,
+    pub cumulative_voted: Option<u64>,
+    pub suppressed: bool,
+}
+
+/// Turnout of the registered voter roll in a poll.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TurnoutReport {
+    pub registered: u64,
+    pub voted: u64,
+    pub turnout_percent: f64,
+    pub min_cell_size: u32,
+    pub by_state: Vec<TurnoutCell>,
+    pub by_zip_prefix: Vec<TurnoutCell>,
+    pub hourly: Vec<TurnoutBucket>,
+}
+
+fn percent(part: u64, whole: u64) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 * 100.0 / whole as f64
+    }
+}
+
+/// Builds a turnout report from every registered voter's zip code and, if they voted, when
+/// their first ballot in the poll was cast.
+pub fn turnout_report(roll: &[(Option<String>, Option<DateTime<Utc>>)], k: u32) -> TurnoutReport {
+    let mut by_state: BTreeMap<String, (u64, u64)> = BTreeMap::new();
+    let mut by_zip_prefix: BTreeMap<String, (u64, u64)> = BTreeMap::new();
+    let mut arrivals: BTreeMap<DateTime<Utc>, u64> = BTreeMap::new();
+
+    for (zip_code, voted_at) in roll {
+        let voted = voted_at.is_some() as u64;
+        let zip_code = zip_code.as_deref().unwrap_or("").trim();
+        let state = lookup_zip(zip_code).map_or_else(|| "Unknown".to_string(), |j| j.state);
+        let prefix = match zip_code.get(..3) {
+            Some(prefix) if prefix.chars().all(|c| c.is_ascii_digit()) => prefix.to_string(),
+            _ => "Unknown".to_string(),
+        };
+        for (cells, key) in [(&mut by_state, state), (&mut by_zip_prefix, prefix)] {
+            let cell = cells.entry(key).or_default();
+            cell.0 += 1;
+            cell.1 += voted;
+        }
+        if let Some(hour) = voted_at.and_then(|at| at.duration_trunc(Duration::hours(1)).ok()) {
+            *arrivals.entry(hour).or_insert(0) += 1;
+        }
+    }
+
+    let cells = |counts: BTreeMap<String, (u64, u64)>| -> Vec<TurnoutCell> {
+        counts.into_iter()
+            .map(|(key, (registered, voted))| TurnoutCell::new(key, registered, voted, k))
+            .collect()
+    };
+
+    let mut hourly = Vec::new();
+    let (mut cumulative, mut unpublished) = (0, 0);
+    for (hour, count) in arrivals {
+        cumulative += count;
+        unpublished += count;
+        let suppressed = unpublished < k as u64;
+        if !suppressed {
+            unpublished = 0;
+        }
+        hourly.push(TurnoutBucket { hour, cumulative_voted: (!suppressed).then_some(cumulative), suppressed });
+    }
+
+    let registered = roll.len() as u64;
+    let voted = roll.iter().filter(|(_, voted_at)| voted_at.is_some()).count() as u64;
+    TurnoutReport {
+        registered,
+        voted,
+        turnout_percent: percent(voted, registered),
+        min_cell_size: k,
+        by_state: cells(by_state),
+        by_zip_prefix: cells(by_zip_prefix),
+        hourly,
+    }
+}
+
+/// Age bracket and region of a voter, taken from their registration when they cast a ballot in
+/// a poll that reports demographics.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Demographics {
+    pub age_bracket: String,
+    pub region: String,
+}
+
+impl Demographics {
+    /// Derives a voter's demographics on `today` from their birth date and zip code.
+    pub fn derive(birth_date: Option<NaiveDate>, zip_code: Option<&str>, today: NaiveDate) -> Self {
+        let age_bracket = match birth_date.and_then(|born| today.years_since(born)) {
+            Some(age) if age < 18 => "under 18",
+            Some(age) if age < 25 => "18-24",
+            Some(age) if age < 35 => "25-34",
+            Some(age) if age < 45 => "35-44",
+            Some(age) if age < 55 => "45-54",
+            Some(age) if age < 65 => "55-64",
+            Some(_) => "65+",
+            None => "Unknown",
+        };
+        let region = zip_code.and_then(lookup_zip).map_or_else(|| "Unknown".to_string(), |j| j.state);
+        Demographics { age_bracket: age_bracket.to_string(), region }
+    }
+}
+
+/// Votes for one option from voters in one age bracket and region. `votes` is withheld when
+/// the cell is suppressed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DemographicCell {
+    pub age_bracket: String,
+    pub region: String,
+    pub option: String,
+    pub votes: Option<u64>,
+    pub suppressed: bool,
+}
+
+/// Results cross-tabulated by age bracket and region.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DemographicBreakdown {
+    pub min_cell_size: u32,
+    pub cells: Vec<DemographicCell>,
+}
+
+/// The options a recorded vote counts for: its single choice, or for election ballots every
+/// contest selection as `"contest: candidate"`. Ballots that are not valid count for nothing.
+pub fn ballot_selections(vote: &Value) -> Vec<String> {
+    if BallotStatus::of_record(vote) != BallotStatus::Valid {
+        return Vec::new();
+    }
+    if let Some(choice) = vote.as_str() {
+        return vec![choice.to_string()];
+    }
+    let Some(fields) = vote.as_object() else { return Vec::new() };
+    if let Some(choice) = ["candidate", "choice", "default_choice"].iter().find_map(|field| fields.get(*field).and_then(|v| v.as_str())) {
+        return vec![choice.to_string()];
+    }
+    fields.iter()
+        .filter(|(key, _)| !BALLOT_METADATA_FIELDS.contains(&key.as_str()))
+        .filter_map(|(key, value)| value.as_str().map(|candidate| format!("{}: {}", key, candidate)))
+        .collect()
+}
+
+/// Cross-tabulates votes by age bracket and region.
+///
+/// Cells with fewer than `k` votes are suppressed. Because each option's total is published
+/// elsewhere, an option with exactly one suppressed cell also has its smallest published cell
+/// suppressed, so the hidden count cannot be recovered by subtraction.
+pub fn demographic_breakdown(ballots: &[(Demographics, Value)], k: u32) -> DemographicBreakdown {
+    let mut counts: BTreeMap<(String, String, String), u64> = BTreeMap::new();
+    for (demographics, vote) in ballots {
+        for option in ballot_selections(vote) {
+            let key = (option, demographics.age_bracket.clone(), demographics.region.clone());
+            *counts.entry(key).or_insert(0) += 1;
+        }
+    }
+
+    let mut cells: Vec<DemographicCell> = counts.into_iter()
+        .map(|((option, age_bracket, region), votes)| DemographicCell {
+            age_bracket,
+            region,
+            option,
+            votes: Some(votes),
+            suppressed: votes < k as u64,
+        })
+        .collect();
+
+    // Cells are grouped by option, so complementary suppression works one option at a time
+    let mut start = 0;
+    while start < cells.len() {
+        let end = start + cells[start..].iter().take_while(|cell| cell.option == cells[start].option).count();
+        let group = &mut cells[start..end];
+        if group.iter().filter(|cell| cell.suppressed).count() == 1 {
+            if let Some(smallest) = group.iter_mut().filter(|cell| !cell.suppressed).min_by_key(|cell| cell.votes) {
+                smallest.suppressed = true;
+            }
+        }
+        start = end;
+    }
+    for cell in cells.iter_mut().filter(|cell| cell.suppressed) {
+        cell.votes = None;
+    }
+
+    DemographicBreakdown { min_cell_size: k, cells }
+}
+
+/// Ballots choosing one candidate in each of two contests. `ballots` is withheld when the cell
+/// is suppressed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CrossTabCell {
+    pub first: String,
+    pub second: String,
+    pub ballots: Option<u64>,
+    pub suppressed: bool,
+}
+
+/// Joint distribution of the choices made in two contests on the same ballots.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CrossTab {
+    pub first_contest: String,
+    pub second_contest: String,
+    pub min_cell_size: u32,
+    pub cells: Vec<CrossTabCell>,
+}
+
+/// Label for a ballot that made no choice in a contest.
+pub const NO_SELECTION: &str = "(none)";
+
+/// Cross-tabulates pairs of choices, one pair per ballot, over every combination of the choices
+/// seen. Cells with at least one but fewer than `k` ballots are suppressed. Since each row and
+/// column sums to a published contest total, any row or column left with a single suppressed
+/// cell has its smallest other non-empty cell suppressed as well, repeated until none is left.
+pub fn cross_tab(first_contest: &str, second_contest: &str, pairs: &[(String, String)], k: u32) -> CrossTab {
+    let mut counts: BTreeMap<(String, String), u64> = BTreeMap::new();
+    for pair in pairs {
+        *counts.entry(pair.clone()).or_insert(0) += 1;
+    }
+    let firsts: BTreeSet<String> = counts.keys().map(|(first, _)| first.clone()).collect();
+    let seconds: BTreeSet<String> = counts.keys().map(|(_, second)| second.clone()).collect();
+
+    let mut cells: Vec<CrossTabCell> = Vec::new();
+    for first in &firsts {
+        for second in &seconds {
+            let ballots = counts.get(&(first.clone(), second.clone())).copied().unwrap_or(0);
+            cells.push(CrossTabCell {
+                first: first.clone(),
+                second: second.clone(),
+                ballots: Some(ballots),
+                suppressed: ballots > 0 && ballots < k as u64,
+            });
+        }
+    }
+
+    loop {
+        let mut changed = false;
+        let lines = firsts.iter().map(|f| (Some(f), None)).chain(seconds.iter().map(|s| (None, Some(s))));
+        for (first, second) in lines.collect::<Vec<_>>() {
+            let in_line = |cell: &CrossTabCell| first.is_none_or(|f| &cell.first == f) && second.is_none_or(|s| &cell.second == s);
+            if cells.iter().filter(|cell| in_line(cell) && cell.suppressed).count() != 1 {
+                continue;
+            }
+            // Prefer a cell with ballots in it: a suppressed empty cell hides little
+            if let Some(smallest) = cells.iter_mut()
+                .filter(|cell| in_line(cell) && !cell.suppressed)
+                .min_by_key(|cell| (cell.ballots == Some(0), cell.ballots))
+            {
+                smallest.suppressed = true;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    for cell in cells.iter_mut().filter(|cell| cell.suppressed) {
+        cell.ballots = None;
+    }
+
+    CrossTab {
+        first_contest: first_contest.to_string(),
+        second_contest: second_contest.to_string(),
+        min_cell_size: k,
+        cells,
+    }
+}
@@ -0,0 +1,402 @@
+use std::collections::{BTreeMap, VecDeque};
+use serde::{Serialize, Deserialize};
+use crate::tie_break::{TieBreaker, TieResolution};
+
+/// Differences smaller than this are treated as equal when comparing fractional vote totals.
+const EPSILON: f64 = 1e-9;
+
+/// Upper bound on Meek keep-factor iterations per round.
+const MEEK_MAX_ITERATIONS: usize = 1000;
+
+/// The rule used to pass an elected candidate's surplus on to later preferences.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SurplusTransfer {
+    /// Weighted inclusive Gregory: every ballot held by the elected candidate moves on at the
+    /// transfer value `surplus / votes`, using a fixed Droop quota.
+    #[default]
+    Gregory,
+    /// Meek's method: elected candidates keep a fraction of every ballot reaching them, the
+    /// quota is recalculated as votes exhaust, and earlier transfers are revised each round.
+    Meek,
+}
+
+/// What happened in a single round of an STV count.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum StvAction {
+    FirstPreferences,
+    Elected { candidates: Vec<String> },
+    SurplusTransfer { from: String, surplus: f64, transfer_value: f64 },
+    Exclusion { candidate: String },
+}
+
+/// Tallies at the end of a round, plus how many votes moved to each candidate during it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StvRound {
+    pub round: u32,
+    #[serde(flatten)]
+    pub action: StvAction,
+    pub quota: f64,
+    pub tallies: BTreeMap<String, f64>,
+    pub transfers: BTreeMap<String, f64>,
+    pub exhausted: f64,
+}
+
+/// The outcome of an STV contest with the full per-round transfer report.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StvResult {
+    pub seats: u32,
+    pub surplus_transfer: SurplusTransfer,
+    pub valid_ballots: u32,
+    pub quota: f64,
+    pub elected: Vec<String>,
+    pub rounds: Vec<StvRound>,
+    /// Ties met during the count and how each was settled.
+    #[serde(default)]
+    pub ties: Vec<TieResolution>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Status {
+    Hopeful,
+    Elected,
+    Excluded,
+}
+
+/// Rounds reported figures so the transfer report is readable and stable across platforms.
+fn round6(value: f64) -> f64 {
+    (value * 1_000_000.0).round() / 1_000_000.0
+}
+
+/// Counts ranked ballots for a multi-seat contest using single transferable vote.
+///
+/// Candidates not in `candidates` and repeated preferences are ignored; ballots left with no
+/// preferences are not counted. When candidates tie for exclusion, the one listed first loses.
+pub fn count_stv(candidates: &[String], ballots: &[Vec<String>], seats: u32, method: SurplusTransfer) -> StvResult {
+    count_stv_with_tie_break(candidates, ballots, seats, method, &TieBreaker::default())
+}
+
+/// Counts an STV contest, settling ties for exclusion or for the last seats with `tie_breaker`.
+/// Ties it cannot settle fall back to listing order and are reported as unresolved.
+pub fn count_stv_with_tie_break(
+    candidates: &[String],
+    ballots: &[Vec<String>],
+    seats: u32,
+    method: SurplusTransfer,
+    tie_breaker: &TieBreaker,
+) -> StvResult {
+    let rankings: Vec<Vec<usize>> = ballots
+        .iter()
+        .map(|ballot| {
+            let mut ranking: Vec<usize> = Vec::new();
+            for name in ballot {
+                if let Some(idx) = candidates.iter().position(|c| c == name) {
+                    if !ranking.contains(&idx) {
+                        ranking.push(idx);
+                    }
+                }
+            }
+            ranking
+        })
+        .filter(|ranking| !ranking.is_empty())
+        .collect();
+
+    let mut count = StvCount {
+        candidates,
+        ballots: rankings,
+        status: vec![Status::Hopeful; candidates.len()],
+        elected: Vec::new(),
+        seats: seats as usize,
+        rounds: Vec::new(),
+        last_tallies: vec![0.0; candidates.len()],
+        last_exhausted: 0.0,
+        tie_breaker,
+        ties: Vec::new(),
+    };
+
+    let quota = match method {
+        SurplusTransfer::Gregory => count.count_gregory(),
+        SurplusTransfer::Meek => count.count_meek(),
+    };
+
+    StvResult {
+        seats,
+        surplus_transfer: method,
+        valid_ballots: count.ballots.len() as u32,
+        quota: round6(quota),
+        elected: count.elected.iter().map(|&c| candidates[c].clone()).collect(),
+        rounds: count.rounds,
+        ties: count.ties,
+    }
+}
+
+struct StvCount<'a> {
+    candidates: &'a [String],
+    ballots: Vec<Vec<usize>>,
+    status: Vec<Status>,
+    elected: Vec<usize>,
+    seats: usize,
+    rounds: Vec<StvRound>,
+    last_tallies: Vec<f64>,
+    last_exhausted: f64,
+    tie_breaker: &'a TieBreaker,
+    ties: Vec<TieResolution>,
+}
+
+impl StvCount<'_> {
+    fn hopeful(&self) -> Vec<usize> {
+        (0..self.candidates.len())
+            .filter(|&c| self.status[c] == Status::Hopeful)
+            .collect()
+    }
+
+    /// Settles a tie using the earlier rounds' tallies as history, returning the tied candidates
+    /// from most to least favoured. Unresolved ties keep listing order.
+    fn break_tie(&mut self, tied: &[usize]) -> Vec<usize> {
+        let names: Vec<String> = tied.iter().map(|&c| self.candidates[c].clone()).collect();
+        let history: Vec<BTreeMap<String, f64>> = self.rounds.iter().map(|r| r.tallies.clone()).collect();
+        let resolution = self.tie_breaker.resolve(&names, &history);
+        let order = resolution.ranking.iter()
+            .filter_map(|name| tied.iter().copied().find(|&c| &self.candidates[c] == name))
+            .collect();
+        self.ties.push(resolution);
+        order
+    }
+
+    /// Marks candidates as elected in order of their current vote, highest first, never
+    /// filling more than the remaining seats.
+    fn elect(&mut self, mut winners: Vec<usize>, votes: &[f64]) -> Vec<String> {
+        winners.sort_by(|&a, &b| votes[b].partial_cmp(&votes[a]).unwrap_or(std::cmp::Ordering::Equal).then(a.cmp(&b)));
+        let remaining = self.seats - self.elected.len();
+        if winners.len() > remaining && (votes[winners[remaining - 1]] - votes[winners[remaining]]).abs() <= EPSILON {
+            // Candidates tied across the last seat: order them by the tie-break policy
+            let cutoff = votes[winners[remaining]];
+            let tied: Vec<usize> = winners.iter().copied().filter(|&c| (votes[c] - cutoff).abs() <= EPSILON).collect();
+            let start = winners.iter().position(|c| tied.contains(c)).unwrap_or(0);
+            let ordered = self.break_tie(&tied);
+            winners.splice(start..start + tied.len(), ordered);
+        }
+        winners.truncate(remaining);
+        for &c in &winners {
+            self.status[c] = Status::Elected;
+            self.elected.push(c);
+        }
+        winners.iter().map(|&c| self.candidates[c].clone()).collect()
+    }
+
+    /// Picks the hopeful candidate with the fewest votes for exclusion, settling ties with the
+    /// tie-break policy (or excluding the first listed if the tie stands).
+    fn lowest(&mut self, hopeful: &[usize], votes: &[f64]) -> usize {
+        let mut lowest = hopeful[0];
+        for &c in &hopeful[1..] {
+            if votes[c] < votes[lowest] - EPSILON {
+                lowest = c;
+            }
+        }
+        let tied: Vec<usize> = hopeful.iter().copied().filter(|&c| (votes[c] - votes[lowest]).abs() <= EPSILON).collect();
+        if tied.len() > 1 {
+            let ordered = self.break_tie(&tied);
+            if self.ties.last().is_some_and(|t| t.resolved) {
+                return *ordered.last().unwrap_or(&lowest);
+            }
+        }
+        lowest
+    }
+
+    fn record(&mut self, action: StvAction, quota: f64, votes: &[f64], exhausted: f64) {
+        let mut tallies = BTreeMap::new();
+        let mut transfers = BTreeMap::new();
+        for (c, name) in self.candidates.iter().enumerate() {
+            tallies.insert(name.clone(), round6(votes[c]));
+            let delta = round6(votes[c] - self.last_tallies[c]);
+            if delta != 0.0 {
+                transfers.insert(name.clone(), delta);
+            }
+        }
+        let exhausted_delta = round6(exhausted - self.last_exhausted);
+        if exhausted_delta != 0.0 {
+            transfers.insert("exhausted".to_string(), exhausted_delta);
+        }
+
+        self.rounds.push(StvRound {
+            round: self.rounds.len() as u32 + 1,
+            action,
+            quota: round6(quota),
+            tallies,
+            transfers,
+            exhausted: round6(exhausted),
+        });
+        self.last_tallies = votes.to_vec();
+        self.last_exhausted = exhausted;
+    }
+
+    /// Weighted inclusive Gregory count with a fixed Droop quota. Returns the quota used.
+    fn count_gregory(&mut self) -> f64 {
+        let quota = (self.ballots.len() / (self.seats + 1) + 1) as f64;
+        let mut weights = vec![1.0; self.ballots.len()];
+        let mut position = vec![0usize; self.ballots.len()];
+        let mut piles: Vec<Vec<usize>> = vec![Vec::new(); self.candidates.len()];
+        let mut votes = vec![0.0; self.candidates.len()];
+        let mut exhausted = 0.0;
+
+        for (b, ranking) in self.ballots.iter().enumerate() {
+            piles[ranking[0]].push(b);
+            votes[ranking[0]] += 1.0;
+        }
+        self.record(StvAction::FirstPreferences, quota, &votes, exhausted);
+
+        let mut pending: VecDeque<usize> = VecDeque::new();
+        while self.elected.len() < self.seats {
+            let hopeful = self.hopeful();
+            let remaining = self.seats - self.elected.len();
+            if hopeful.len() <= remaining {
+                let candidates = self.elect(hopeful, &votes);
+                self.record(StvAction::Elected { candidates }, quota, &votes, exhausted);
+                break;
+            }
+
+            let reached: Vec<usize> = hopeful.iter().copied().filter(|&c| votes[c] >= quota).collect();
+            if !reached.is_empty() {
+                let candidates = self.elect(reached, &votes);
+                pending.extend(self.elected[self.elected.len() - candidates.len()..].iter().copied());
+                self.record(StvAction::Elected { candidates }, quota, &votes, exhausted);
+                continue;
+            }
+
+            // Transfer the oldest outstanding surplus before excluding anyone.
+            if let Some(from) = pending.pop_front() {
+                let surplus = votes[from] - quota;
+                if surplus > EPSILON {
+                    let transfer_value = surplus / votes[from];
+                    for b in std::mem::take(&mut piles[from]) {
+                        weights[b] *= transfer_value;
+                        self.move_ballot(b, &mut position, &weights, &mut piles, &mut votes, &mut exhausted);
+                    }
+                    votes[from] = quota;
+                    let action = StvAction::SurplusTransfer {
+                        from: self.candidates[from].clone(),
+                        surplus: round6(surplus),
+                        transfer_value: round6(transfer_value),
+                    };
+                    self.record(action, quota, &votes, exhausted);
+                }
+                continue;
+            }
+
+            let excluded = self.lowest(&hopeful, &votes);
+            self.status[excluded] = Status::Excluded;
+            for b in std::mem::take(&mut piles[excluded]) {
+                self.move_ballot(b, &mut position, &weights, &mut piles, &mut votes, &mut exhausted);
+            }
+            votes[excluded] = 0.0;
+            let action = StvAction::Exclusion { candidate: self.candidates[excluded].clone() };
+            self.record(action, quota, &votes, exhausted);
+        }
+        quota
+    }
+
+    /// Passes a ballot to its next hopeful preference, or exhausts it if none is left.
+    fn move_ballot(
+        &self,
+        ballot: usize,
+        position: &mut [usize],
+        weights: &[f64],
+        piles: &mut [Vec<usize>],
+        votes: &mut [f64],
+        exhausted: &mut f64,
+    ) {
+        let ranking = &self.ballots[ballot];
+        while position[ballot] + 1 < ranking.len() {
+            position[ballot] += 1;
+            let next = ranking[position[ballot]];
+            if self.status[next] == Status::Hopeful {
+                piles[next].push(ballot);
+                votes[next] += weights[ballot];
+                return;
+            }
+        }
+        *exhausted += weights[ballot];
+    }
+
+    /// Meek count with keep factors recomputed until elected candidates sit at the quota.
+    /// Returns the quota from the first round.
+    fn count_meek(&mut self) -> f64 {
+        let mut keep = vec![1.0; self.candidates.len()];
+        let (votes, exhausted, first_quota) = self.converge_meek(&mut keep);
+        self.record(StvAction::FirstPreferences, first_quota, &votes, exhausted);
+        let mut votes = votes;
+        let mut quota = first_quota;
+
+        while self.elected.len() < self.seats {
+            let hopeful = self.hopeful();
+            let remaining = self.seats - self.elected.len();
+            if hopeful.len() <= remaining {
+                let candidates = self.elect(hopeful, &votes);
+                self.record(StvAction::Elected { candidates }, quota, &votes, self.last_exhausted);
+                break;
+            }
+
+            // Meek's quota is reached exactly once keep factors converge, so a hopeful
+            // candidate must strictly exceed it to be elected.
+            let reached: Vec<usize> = hopeful.iter().copied().filter(|&c| votes[c] > quota + EPSILON).collect();
+            let action = if !reached.is_empty() {
+                StvAction::Elected { candidates: self.elect(reached, &votes) }
+            } else {
+                let excluded = self.lowest(&hopeful, &votes);
+                self.status[excluded] = Status::Excluded;
+                keep[excluded] = 0.0;
+                StvAction::Exclusion { candidate: self.candidates[excluded].clone() }
+            };
+
+            let (new_votes, exhausted, new_quota) = self.converge_meek(&mut keep);
+            votes = new_votes;
+            quota = new_quota;
+            self.record(action, quota, &votes, exhausted);
+        }
+        first_quota
+    }
+
+    /// Distributes every ballot under the current keep factors, adjusting elected candidates'
+    /// keep factors until their votes converge on the quota.
+    fn converge_meek(&self, keep: &mut [f64]) -> (Vec<f64>, f64, f64) {
+        let total = self.ballots.len() as f64;
+        let mut result = (vec![0.0; self.candidates.len()], 0.0, total / (self.seats + 1) as f64);
+
+        for _ in 0..MEEK_MAX_ITERATIONS {
+            let mut votes = vec![0.0; self.candidates.len()];
+            let mut exhausted = 0.0;
+            for ranking in &self.ballots {
+                let mut remaining = 1.0;
+                for &c in ranking {
+                    if keep[c] <= 0.0 {
+                        continue;
+                    }
+                    let share = remaining * keep[c];
+                    votes[c] += share;
+                    remaining -= share;
+                    if remaining <= EPSILON {
+                        break;
+                    }
+                }
+                exhausted += remaining.max(0.0);
+            }
+            let quota = (total - exhausted) / (self.seats + 1) as f64;
+
+            let mut converged = true;
+            for &c in &self.elected {
+                if votes[c] > EPSILON {
+                    if (votes[c] - quota).abs() > EPSILON {
+                        converged = false;
+                    }
+                    keep[c] = (keep[c] * quota / votes[c]).min(1.0);
+                }
+            }
+            result = (votes, exhausted, quota);
+            if converged {
+                break;
+            }
+        }
+        result
+    }
+}
@@ -0,0 +1,176 @@
+use std::collections::BTreeMap;
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+
+/// Record type for a tie-break decision appended to a poll's chain.
+pub const TIE_BREAK_RECORD: &str = "tie_break";
+
+/// How a poll settles a tie between leading options, or between candidates in an STV count.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TieBreakPolicy {
+    /// The tie stands and is reported as such.
+    #[default]
+    DeclareTie,
+    /// The tied candidate ahead at the most recent earlier point in the count wins: the
+    /// previous STV round, or for plurality counts the earlier ballots in chain order.
+    EarlierRound,
+    /// Lots drawn from a seed taken from the chain's final block hash and recorded on-chain.
+    Lot,
+    /// An official's ranking of the tied candidates, recorded on-chain.
+    OfficialDecision,
+}
+
+/// A tie-break input recorded on-chain so anyone can reproduce how a tie was settled.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TieBreakRecord {
+    /// The election contest the tie is in; `None` for a poll's own outcome.
+    pub contest: Option<String>,
+    /// The tied candidates, sorted.
+    pub tied: Vec<String>,
+    pub policy: TieBreakPolicy,
+    /// The tied candidates from most to least favoured.
+    pub ranking: Vec<String>,
+    /// Final block hash the lots were drawn from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<String>,
+    pub official: String,
+}
+
+/// How a particular tie was settled.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TieResolution {
+    pub tied: Vec<String>,
+    pub policy: TieBreakPolicy,
+    /// Most to least favoured. Unresolved ties keep the order the candidates are listed in.
+    pub ranking: Vec<String>,
+    pub resolved: bool,
+    pub detail: String,
+}
+
+/// Orders tied candidates by lot. Each candidate's lot is the SHA-256 of
+/// `"{seed}:{contest}:{candidate}"` (with an empty contest for a poll's own outcome), and the
+/// lowest lot is most favoured.
+pub fn draw_lots(seed: &str, contest: Option<&str>, tied: &[String]) -> Vec<String> {
+    let mut lots: Vec<(String, String)> = tied.iter()
+        .map(|candidate| {
+            let mut hasher = Sha256::new();
+            hasher.update(format!("{}:{}:{}", seed, contest.unwrap_or(""), candidate));
+            (format!("{:x}", hasher.finalize()), candidate.clone())
+        })
+        .collect();
+    lots.sort();
+    lots.into_iter().map(|(_, candidate)| candidate).collect()
+}
+
+fn sorted(names: &[String]) -> Vec<String> {
+    let mut names = names.to_vec();
+    names.sort();
+    names
+}
+
+/// Applies a poll's tie-break policy, with the decisions recorded on its chain for one contest.
+#[derive(Debug, Clone, Default)]
+pub struct TieBreaker {
+    pub policy: TieBreakPolicy,
+    pub decisions: Vec<TieBreakRecord>,
+}
+
+impl TieBreaker {
+    /// Keeps the recorded decisions that apply to `contest`.
+    pub fn new(policy: TieBreakPolicy, contest: Option<&str>, records: &[TieBreakRecord]) -> Self {
+        let decisions = records.iter()
+            .filter(|record| record.contest.as_deref() == contest)
+            .cloned()
+            .collect();
+        TieBreaker { policy, decisions }
+    }
+
+    /// Settles a tie between `tied` (in listing order). `history` holds the tallies at earlier
+    /// points in the count, oldest first, and is only consulted for earlier-round comparison.
+    pub fn resolve(&self, tied: &[String], history: &[BTreeMap<String, f64>]) -> TieResolution {
+        let unresolved = |detail: String| TieResolution {
+            tied: tied.to_vec(),
+            policy: self.policy,
+            ranking: tied.to_vec(),
+            resolved: false,
+            detail,
+        };
+
+        match self.policy {
+            TieBreakPolicy::DeclareTie => unresolved("Tie declared".to_string()),
+            TieBreakPolicy::EarlierRound => {
+                // Compare the tallies point by point, starting from the most recent
+                let key = |candidate: &String| -> Vec<f64> {
+                    history.iter().rev()
+                        .map(|tallies| tallies.get(candidate).copied().unwrap_or(0.0))
+                        .collect()
+                };
+                let mut ranking = tied.to_vec();
+                ranking.sort_by(|a, b| key(b).partial_cmp(&key(a)).unwrap_or(std::cmp::Ordering::Equal));
+                let resolved = ranking.windows(2).all(|pair| key(&pair[0]) != key(&pair[1]));
+                if !resolved {
+                    return unresolved("Still tied at every earlier point in the count".to_string());
+                }
+                TieResolution {
+                    tied: tied.to_vec(),
+                    policy: self.policy,
+                    detail: format!("Ranked by earlier counts: {}", ranking.join(" > ")),
+                    ranking,
+                    resolved: true,
+                }
+            },
+            TieBreakPolicy::Lot | TieBreakPolicy::OfficialDecision => {
+                let key = sorted(tied);
+                match self.decisions.iter().rev().find(|record| record.tied == key) {
+                    Some(record) => TieResolution {
+                        tied: tied.to_vec(),
+                        policy: self.policy,
+                        ranking: record.ranking.clone(),
+                        resolved: true,
+                        detail: match &record.seed {
+                            Some(seed) => format!("Lots drawn from seed {} by {}", seed, record.official),
+                            None => format!("Decided by {}", record.official),
+                        },
+                    },
+                    None => unresolved("Awaiting a recorded tie-break".to_string()),
+                }
+            },
+        }
+    }
+}
+
+/// Builds the record for a tie-break input, checking it against the tied candidates. Lots are
+/// drawn from `seed`; official decisions must rank every tied candidate.
+pub fn build_record(
+    policy: TieBreakPolicy,
+    contest: Option<String>,
+    tied: &[String],
+    ranking: Option<Vec<String>>,
+    seed: &str,
+    official: &str,
+) -> Result<TieBreakRecord, String> {
+    let tied = sorted(tied);
+    if tied.len() < 2 || tied.windows(2).any(|pair| pair[0] == pair[1]) {
+        return Err("A tie needs at least two distinct candidates".to_string());
+    }
+    let (ranking, seed) = match policy {
+        TieBreakPolicy::Lot => {
+            if ranking.is_some() {
+                return Err("Lots are drawn by the server; do not supply a ranking".to_string());
+            }
+            (draw_lots(seed, contest.as_deref(), &tied), Some(seed.to_string()))
+        },
+        TieBreakPolicy::OfficialDecision => {
+            let ranking = ranking.ok_or_else(|| "An official decision must rank the tied candidates".to_string())?;
+            if sorted(&ranking) != tied {
+                return Err("The ranking must list each tied candidate exactly once".to_string());
+            }
+            (ranking, None)
+        },
+        TieBreakPolicy::DeclareTie | TieBreakPolicy::EarlierRound => {
+            return Err("This poll's tie-break policy does not take recorded input".to_string());
+        },
+    };
+    Ok(TieBreakRecord { contest, tied, policy, ranking, seed, official: official.to_string() })
+}
@@ -0,0 +1,63 @@
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use serde::{Serialize, Deserialize};
+
+/// Width of the time buckets in a results timeline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Bucket {
+    Minute,
+    #[default]
+    Hour,
+    Day,
+}
+
+impl Bucket {
+    /// Parses a `bucket` query parameter: `minute`, `hour` or `day`.
+    pub fn parse(bucket: &str) -> Result<Self, String> {
+        serde_json::from_value(serde_json::Value::String(bucket.to_string()))
+            .map_err(|_| format!("Unknown bucket '{}'; expected minute, hour or day", bucket))
+    }
+
+    fn duration(&self) -> Duration {
+        match self {
+            Bucket::Minute => Duration::minutes(1),
+            Bucket::Hour => Duration::hours(1),
+            Bucket::Day => Duration::days(1),
+        }
+    }
+
+    /// Start of the bucket holding a block timestamp in milliseconds.
+    pub fn start(&self, timestamp: i64) -> DateTime<Utc> {
+        let at = DateTime::from_timestamp_millis(timestamp).unwrap_or_default();
+        at.duration_trunc(self.duration()).unwrap_or(at)
+    }
+}
+
+/// Cumulative counts at the end of one time bucket.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimelinePoint<T> {
+    pub bucket_start: DateTime<Utc>,
+    pub counts: T,
+}
+
+/// Applies timestamped events to running counts in time order and records the counts at the end
+/// of every bucket an event fell in.
+pub fn cumulative<T: Clone, E>(
+    bucket: Bucket,
+    mut counts: T,
+    events: Vec<(i64, E)>,
+    mut apply: impl FnMut(&mut T, E),
+) -> Vec<TimelinePoint<T>> {
+    let mut events = events;
+    events.sort_by_key(|(timestamp, _)| *timestamp);
+    let mut points: Vec<TimelinePoint<T>> = Vec::new();
+    for (timestamp, event) in events {
+        apply(&mut counts, event);
+        let bucket_start = bucket.start(timestamp);
+        match points.last_mut() {
+            Some(point) if point.bucket_start == bucket_start => point.counts = counts.clone(),
+            _ => points.push(TimelinePoint { bucket_start, counts: counts.clone() }),
+        }
+    }
+    points
+}
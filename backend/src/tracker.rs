@@ -0,0 +1,164 @@
+use chrono::Utc;
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+use crate::ballot::{superseded_block, BallotStatus};
+use crate::commit_reveal::{revealed_block, COMMITMENT_RECORD};
+use crate::election_blockchain::RECORD_TYPE_FIELD;
+use crate::encrypted_tally::DECRYPTED_TALLY_RECORD;
+use crate::mixnet::MIX_DECRYPTION_RECORD;
+use crate::poll_manager::Poll;
+
+/// Number of words in a tracker code. Each word encodes one byte of the block hash.
+pub const TRACKER_CODE_WORDS: usize = 6;
+
+/// The 256 words tracker codes are written in, one per byte value.
+const WORDS: [&str; 256] = [
+    "acid", "acorn", "actor", "adobe", "agent", "alarm", "album", "alley",
+    "amber", "anchor", "angle", "apple", "apron", "arena", "arrow", "aspen",
+    "atlas", "attic", "autumn", "award", "bacon", "badge", "bagel", "baker",
+    "bamboo", "banjo", "barley", "basil", "basin", "beach", "beacon", "beard",
+    "berry", "bison", "blade", "blaze", "bloom", "board", "bonus", "bottle",
+    "bread", "brick", "bridge", "brook", "brush", "bucket", "bugle", "cabin",
+    "cable", "cactus", "camel", "candle", "canoe", "canyon", "carbon", "cargo",
+    "carpet", "castle", "cedar", "cello", "chalk", "charm", "cherry", "chess",
+    "chief", "cider", "cinema", "circle", "citrus", "clay", "cliff", "clock",
+    "cloud", "clover", "cobalt", "cocoa", "comet", "coral", "cotton", "crane",
+    "crater", "crayon", "daisy", "delta", "denim", "desert", "domino", "donkey",
+    "dragon", "dream", "drum", "eagle", "easel", "echo", "elbow", "ember",
+    "engine", "falcon", "fern", "ferry", "fiddle", "field", "flame", "flute",
+    "forest", "fossil", "fox", "galaxy", "garden", "garlic", "gecko", "ginger",
+    "globe", "goblet", "grape", "gravel", "guitar", "hammer", "harbor", "hazel",
+    "helmet", "heron", "honey", "hornet", "hotel", "husky", "igloo", "indigo",
+    "island", "ivory", "jacket", "jaguar", "jelly", "jewel", "jigsaw", "jungle",
+    "kayak", "kernel", "kettle", "kitten", "koala", "ladder", "lagoon", "lantern",
+    "laser", "lemon", "lentil", "lily", "linen", "lizard", "locket", "lotus",
+    "magnet", "mango", "maple", "marble", "meadow", "melon", "meteor", "mint",
+    "mirror", "monkey", "mosaic", "muffin", "museum", "nectar", "needle", "nickel",
+    "noodle", "nutmeg", "oasis", "ocean", "olive", "onion", "opal", "orbit",
+    "orchid", "otter", "oyster", "paddle", "palace", "panda", "paper", "parrot",
+    "pastel", "peach", "pebble", "pepper", "piano", "pillow", "pilot", "planet",
+    "plum", "pocket", "polar", "pony", "poppy", "potato", "prism", "puzzle",
+    "quartz", "quill", "rabbit", "radar", "radish", "raven", "reef", "ribbon",
+    "river", "robin", "rocket", "saddle", "salmon", "sandal", "satin", "scarf",
+    "shadow", "shell", "silver", "sketch", "sled", "slope", "socket", "spider",
+    "sponge", "spruce", "squid", "stamp", "statue", "summit", "sunset", "swan",
+    "table", "tango", "teapot", "temple", "tiger", "timber", "tomato", "topaz",
+    "tulip", "tundra", "turtle", "valley", "velvet", "violin", "wagon", "walnut",
+    "walrus", "willow", "window", "winter", "wizard", "yogurt", "zebra", "zipper",
+];
+
+/// What was cast in the block a tracker code refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrackedEntry {
+    /// A ballot.
+    Ballot,
+    /// A commit-reveal poll's commitment to a ballot revealed later.
+    Commitment,
+}
+
+/// What the public tracker reports about a cast ballot: where it is on the chain and whether it
+/// counts, but not who cast it or what it chose.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BallotTracker {
+    pub poll_id: String,
+    pub tracker_code: String,
+    pub entry: TrackedEntry,
+    pub block_index: u32,
+    pub block_hash: String,
+    pub timestamp: i64,
+    /// The ballot's status; for a commitment, that of the ballot revealing it, if there is one.
+    pub ballot_status: Option<BallotStatus>,
+    /// Whether the voter recast and a later ballot replaced this one.
+    pub superseded: bool,
+    /// Whether the ballot is among those the poll's tally counts.
+    pub counted: bool,
+    /// Whether the tally is final: voting and revealing are over and, for encrypted polls, the
+    /// tally or mixed ballots have been decrypted.
+    pub tally_final: bool,
+}
+
+/// The tracker code of the ballot in a block: the first bytes of the block's hash written as
+/// words, which a voter can note down and look up without identifying themselves.
+pub fn tracker_code(block_hash: &str) -> String {
+    (0..TRACKER_CODE_WORDS)
+        .map(|i| block_hash.get(2 * i..2 * i + 2).and_then(|byte| u8::from_str_radix(byte, 16).ok()).unwrap_or(0))
+        .map(|byte| WORDS[byte as usize])
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Reads a tracker code as a voter typed it: case does not matter and words may be separated
+/// by hyphens or spaces. Returns the code in its canonical form.
+pub fn parse_tracker_code(code: &str) -> Result<String, String> {
+    let words: Vec<String> = code.split(|c: char| c == '-' || c.is_whitespace())
+        .filter(|word| !word.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect();
+    if words.len() != TRACKER_CODE_WORDS {
+        return Err(format!("A tracker code has {} words", TRACKER_CODE_WORDS));
+    }
+    if let Some(word) = words.iter().find(|word| !WORDS.contains(&word.as_str())) {
+        return Err(format!("\"{}\" is not a tracker code word", word));
+    }
+    Ok(words.join("-"))
+}
+
+/// The tracker code of the latest block on a poll's chain, issued for the ballot just cast.
+pub fn latest_tracker_code(poll: &Poll) -> Option<String> {
+    let hash = match poll {
+        Poll::Election { blockchain, .. } => blockchain.chain.last().map(|block| &block.hash),
+        Poll::Normal { blockchain, .. } => blockchain.chain.last().map(|block| &block.hash),
+    };
+    hash.map(|hash| tracker_code(hash))
+}
+
+/// Looks up the ballot or commitment with a canonical tracker code on a poll's chain.
+pub fn track(poll_id: &str, poll: &Poll, code: &str) -> Option<BallotTracker> {
+    let entries: Vec<(u32, &str, i64, &Value)> = match poll {
+        Poll::Election { blockchain, .. } => blockchain.chain.iter()
+            .skip(1)
+            .map(|block| (block.index, block.hash.as_str(), block.timestamp, &block.transactions))
+            .collect(),
+        Poll::Normal { blockchain, .. } => blockchain.chain.iter()
+            .skip(1)
+            .flat_map(|block| block.transactions.iter().map(move |transaction| (block.index as u32, block.hash.as_str(), block.timestamp, transaction)))
+            .collect(),
+    };
+    let (block_index, block_hash, timestamp, transaction) = *entries.iter().find(|(_, hash, _, _)| tracker_code(hash) == code)?;
+    let record_type = |transaction: &Value| transaction.get(RECORD_TYPE_FIELD).and_then(Value::as_str).map(str::to_string);
+    let entry = match record_type(transaction).as_deref() {
+        None if transaction.is_object() => TrackedEntry::Ballot,
+        Some(COMMITMENT_RECORD) => TrackedEntry::Commitment,
+        _ => return None,
+    };
+    let is_superseded = |index: u32| entries.iter().any(|(_, _, _, transaction)| superseded_block(transaction) == Some(index as u64));
+
+    // A commitment counts through the ballot that reveals it
+    let ballot = match entry {
+        TrackedEntry::Ballot => Some((block_index, transaction)),
+        TrackedEntry::Commitment => entries.iter()
+            .find(|(_, _, _, transaction)| revealed_block(transaction) == Some(block_index as u64))
+            .map(|(index, _, _, transaction)| (*index, *transaction)),
+    };
+    let ballot_status = ballot.map(|(_, transaction)| BallotStatus::of_record(transaction));
+    let counted = ballot.is_some_and(|(index, transaction)| !is_superseded(index) && BallotStatus::of_record(transaction) == BallotStatus::Valid);
+
+    let settings = &poll.metadata().settings;
+    let closed = settings.reveal_until.or(settings.closes_at).is_some_and(|until| Utc::now() > until);
+    let decrypted = !settings.encrypted || entries.iter().any(|(_, _, _, transaction)| {
+        record_type(transaction).is_some_and(|record_type| [DECRYPTED_TALLY_RECORD, MIX_DECRYPTION_RECORD].contains(&record_type.as_str()))
+    });
+    Some(BallotTracker {
+        poll_id: poll_id.to_string(),
+        tracker_code: code.to_string(),
+        entry,
+        block_index,
+        block_hash: block_hash.to_string(),
+        timestamp,
+        ballot_status,
+        superseded: entry == TrackedEntry::Ballot && is_superseded(block_index),
+        counted,
+        tally_final: closed && decrypted,
+    })
+}
@@ -0,0 +1,314 @@
+use std::collections::{BTreeMap, BTreeSet};
+use num_bigint_dig::BigUint;
+use num_traits::{One, Zero};
+use serde::{Serialize, Deserialize};
+use crate::crypto::{self, hex_biguint, hex_biguint_option, hex_biguint_vec};
+use crate::elgamal::{group, Ciphertext, KeyPair, PublicKey};
+use crate::encrypted_tally::EncryptedSelections;
+use crate::mixnet::{MixedBallot, PartialMix};
+use crate::proofs::{ChaumPedersenProof, SchnorrProof};
+
+/// The trustees who hold shares of an encrypted poll's election key, and how many of them must
+/// cooperate to decrypt the tally.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrusteeConfig {
+    pub trustees: Vec<String>,
+    pub threshold: u32,
+}
+
+/// What a trustee publishes to open the key ceremony: the key other trustees encrypt its shares
+/// to, Feldman commitments `g^a_k` to the coefficients of its secret polynomial, and a proof
+/// that it knows the constant coefficient.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrusteeCommitments {
+    pub transport_key: PublicKey,
+    #[serde(with = "hex_biguint_vec")]
+    pub commitments: Vec<BigUint>,
+    pub proof: SchnorrProof,
+}
+
+/// A trustee's share for another trustee, encrypted to the recipient's transport key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncryptedShare {
+    #[serde(with = "hex_biguint")]
+    pub ephemeral: BigUint,
+    #[serde(with = "hex_biguint")]
+    pub masked: BigUint,
+}
+
+/// A trustee's decryption factor `a^x_j` for one ciphertext, with a proof that it used the
+/// key share behind its verification key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PartialDecryption {
+    #[serde(with = "hex_biguint")]
+    pub factor: BigUint,
+    pub proof: ChaumPedersenProof,
+}
+
+/// One partial decryption per aggregate, keyed by contest and then option.
+pub type PartialTally = BTreeMap<String, BTreeMap<String, PartialDecryption>>;
+
+/// Public progress of an encrypted poll's key ceremony and decryption.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Ceremony {
+    pub poll_id: String,
+    pub threshold: u32,
+    pub trustees: Vec<CeremonyTrustee>,
+    /// The joint election key, once every trustee has published commitments and shares.
+    pub election_key: Option<PublicKey>,
+}
+
+/// One trustee's part in a key ceremony.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CeremonyTrustee {
+    pub trustee: String,
+    pub index: u64,
+    pub commitments: Option<TrusteeCommitments>,
+    pub shares_distributed: bool,
+    /// `g^x_j` for the trustee's key share `x_j`, known once every trustee has committed.
+    #[serde(with = "hex_biguint_option")]
+    pub verification_key: Option<BigUint>,
+    pub decrypted: bool,
+}
+
+/// A trustee's private state across the ceremony, kept by the trustee CLI and never sent to
+/// the server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrusteeState {
+    pub poll_id: String,
+    pub trustee: String,
+    pub index: u64,
+    #[serde(with = "hex_biguint_vec")]
+    pub coefficients: Vec<BigUint>,
+    pub transport: KeyPair,
+    /// The trustee's share of the election key, once it has received every other trustee's share.
+    #[serde(default, with = "hex_biguint_option")]
+    pub key_share: Option<BigUint>,
+}
+
+impl TrusteeConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.trustees.is_empty() {
+            return Err("At least one trustee is required".to_string());
+        }
+        if self.trustees.iter().collect::<BTreeSet<_>>().len() != self.trustees.len() {
+            return Err("Trustee names must be unique".to_string());
+        }
+        if self.threshold == 0 || self.threshold as usize > self.trustees.len() {
+            return Err(format!("Threshold must be between 1 and the {} trustees", self.trustees.len()));
+        }
+        Ok(())
+    }
+
+    /// A trustee's index, counting from 1, which its shares are evaluated at.
+    pub fn index_of(&self, trustee: &str) -> Option<u64> {
+        self.trustees.iter().position(|t| t == trustee).map(|position| position as u64 + 1)
+    }
+}
+
+impl TrusteeCommitments {
+    /// Checks the commitments are group elements for a polynomial of the threshold's degree and
+    /// that the trustee knows its constant coefficient.
+    pub fn verify(&self, threshold: u32) -> Result<(), String> {
+        let group = group();
+        if self.commitments.len() != threshold as usize {
+            return Err(format!("Expected {} coefficient commitments", threshold));
+        }
+        if !group.is_element(&self.transport_key.y) || self.commitments.iter().any(|c| !group.is_element(c)) {
+            return Err("Commitments must be group elements".to_string());
+        }
+        if !self.proof.verify(&self.commitments[0]) {
+            return Err("Proof of knowledge of the constant coefficient is invalid".to_string());
+        }
+        Ok(())
+    }
+
+    /// `g^f(index)`, the commitment to this trustee's share for the trustee at `index`.
+    pub fn share_commitment(&self, index: u64) -> BigUint {
+        let group = group();
+        let index = BigUint::from(index);
+        let mut power = BigUint::one();
+        let mut product = BigUint::one();
+        for commitment in &self.commitments {
+            product = product * commitment.modpow(&power, &group.p) % &group.p;
+            power = power * &index % &group.q;
+        }
+        product
+    }
+}
+
+impl Ceremony {
+    /// The commitments of every trustee, if all have published them.
+    fn all_commitments(&self) -> Option<Vec<&TrusteeCommitments>> {
+        self.trustees.iter().map(|t| t.commitments.as_ref()).collect()
+    }
+
+    /// The trustee at `index`.
+    pub fn trustee(&self, index: u64) -> Option<&CeremonyTrustee> {
+        self.trustees.iter().find(|t| t.index == index)
+    }
+
+    /// Fills in the verification keys and, once every share is out, the election key.
+    pub fn derive_keys(&mut self) {
+        let Some(commitments) = self.all_commitments() else { return };
+        let keys: Vec<BigUint> = self.trustees.iter()
+            .map(|trustee| verification_key(&commitments, trustee.index))
+            .collect();
+        let election_key = election_key(&commitments);
+        for (trustee, key) in self.trustees.iter_mut().zip(keys) {
+            trustee.verification_key = Some(key);
+        }
+        if self.trustees.iter().all(|t| t.shares_distributed) {
+            self.election_key = Some(election_key);
+        }
+    }
+}
+
+/// The joint election key, the product of every trustee's committed constant coefficient.
+pub fn election_key(commitments: &[&TrusteeCommitments]) -> PublicKey {
+    let p = &group().p;
+    let y = commitments.iter().fold(BigUint::one(), |y, c| y * &c.commitments[0] % p);
+    PublicKey { y }
+}
+
+/// `g^x_j` for the key share of the trustee at `index`, computed from everyone's commitments.
+pub fn verification_key(commitments: &[&TrusteeCommitments], index: u64) -> BigUint {
+    let p = &group().p;
+    commitments.iter().fold(BigUint::one(), |key, c| key * c.share_commitment(index) % p)
+}
+
+/// The Lagrange coefficient at zero for the trustee at `index` among the cooperating `indices`.
+pub fn lagrange_coefficient(index: u64, indices: &[u64]) -> BigUint {
+    let q = &group().q;
+    let mut numerator = BigUint::one();
+    let mut denominator = BigUint::one();
+    for &other in indices.iter().filter(|&&other| other != index) {
+        numerator = numerator * BigUint::from(other) % q;
+        denominator = denominator * ((BigUint::from(other) + q - BigUint::from(index)) % q) % q;
+    }
+    let inverse = crypto::mod_inverse(&denominator, q).expect("trustee indices are distinct and below q");
+    numerator * inverse % q
+}
+
+/// Combines the decryption factors of at least a threshold of trustees, given by index, into
+/// the full decryption factor `a^x`.
+pub fn combine_factors(factors: &[(u64, &BigUint)]) -> BigUint {
+    let p = &group().p;
+    let indices: Vec<u64> = factors.iter().map(|(index, _)| *index).collect();
+    factors.iter().fold(BigUint::one(), |combined, (index, factor)| {
+        combined * factor.modpow(&lagrange_coefficient(*index, &indices), p) % p
+    })
+}
+
+/// Checks a trustee's partial decryption of `ciphertext` against its verification key.
+pub fn verify_partial(verification_key: &BigUint, ciphertext: &Ciphertext, partial: &PartialDecryption) -> bool {
+    group().is_element(&partial.factor) && partial.proof.verify(verification_key, &ciphertext.a, &partial.factor)
+}
+
+/// The one-time pad that hides a share in transit, derived from the key agreed with the
+/// recipient.
+fn share_pad(shared: &BigUint, sender: u64, recipient: u64) -> BigUint {
+    crypto::hash_to_below(&group().q, &[
+        b"trustee-share",
+        &shared.to_bytes_be(),
+        &sender.to_be_bytes(),
+        &recipient.to_be_bytes(),
+    ])
+}
+
+impl TrusteeState {
+    /// Starts a trustee's part in the ceremony with a random polynomial of degree
+    /// `threshold - 1` and a fresh transport key.
+    pub fn new(poll_id: &str, trustee: &str, index: u64, threshold: u32) -> Self {
+        let group = group();
+        TrusteeState {
+            poll_id: poll_id.to_string(),
+            trustee: trustee.to_string(),
+            index,
+            coefficients: (0..threshold).map(|_| group.random_exponent()).collect(),
+            transport: KeyPair::generate(),
+            key_share: None,
+        }
+    }
+
+    /// `f(index)` for this trustee's polynomial.
+    fn evaluate(&self, index: u64) -> BigUint {
+        let q = &group().q;
+        let index = BigUint::from(index);
+        self.coefficients.iter().rev().fold(BigUint::zero(), |value, coefficient| (value * &index + coefficient) % q)
+    }
+
+    /// The commitments to publish.
+    pub fn commitments(&self) -> TrusteeCommitments {
+        let group = group();
+        let commitments: Vec<BigUint> = self.coefficients.iter().map(|a| group.pow_g(a)).collect();
+        let proof = SchnorrProof::prove(&self.coefficients[0], &commitments[0]);
+        TrusteeCommitments { transport_key: self.transport.public.clone(), commitments, proof }
+    }
+
+    /// This trustee's share for every other trustee, each encrypted to its recipient's
+    /// transport key. Every trustee must have committed first.
+    pub fn shares_for(&self, ceremony: &Ceremony) -> Result<BTreeMap<String, EncryptedShare>, String> {
+        let group = group();
+        let mut shares = BTreeMap::new();
+        for recipient in ceremony.trustees.iter().filter(|t| t.index != self.index) {
+            let commitments = recipient.commitments.as_ref()
+                .ok_or_else(|| format!("Trustee {} has not published commitments yet", recipient.trustee))?;
+            let k = group.random_exponent();
+            let shared = commitments.transport_key.y.modpow(&k, &group.p);
+            let masked = (self.evaluate(recipient.index) + share_pad(&shared, self.index, recipient.index)) % &group.q;
+            shares.insert(recipient.trustee.clone(), EncryptedShare { ephemeral: group.pow_g(&k), masked });
+        }
+        Ok(shares)
+    }
+
+    /// Decrypts and checks the shares sent by every other trustee against their commitments,
+    /// then sums them with this trustee's own share into its share of the election key.
+    pub fn receive_shares(&mut self, ceremony: &Ceremony, shares: &BTreeMap<String, EncryptedShare>) -> Result<(), String> {
+        let group = group();
+        let mut key_share = self.evaluate(self.index);
+        for sender in ceremony.trustees.iter().filter(|t| t.index != self.index) {
+            let commitments = sender.commitments.as_ref()
+                .ok_or_else(|| format!("Trustee {} has not published commitments yet", sender.trustee))?;
+            let share = shares.get(&sender.trustee)
+                .ok_or_else(|| format!("No share from trustee {}", sender.trustee))?;
+            let shared = share.ephemeral.modpow(&self.transport.x, &group.p);
+            let value = (&share.masked + &group.q - share_pad(&shared, sender.index, self.index)) % &group.q;
+            if group.pow_g(&value) != commitments.share_commitment(self.index) {
+                return Err(format!("The share from trustee {} does not match its commitments", sender.trustee));
+            }
+            key_share = (key_share + value) % &group.q;
+        }
+        self.key_share = Some(key_share);
+        Ok(())
+    }
+
+    /// Partially decrypts every aggregate with this trustee's key share, proving each factor.
+    pub fn partially_decrypt(&self, aggregates: &EncryptedSelections) -> Result<PartialTally, String> {
+        let key_share = self.key_share.as_ref().ok_or("This trustee has not received its shares yet")?;
+        Ok(aggregates.iter()
+            .map(|(contest, options)| {
+                let partials = options.iter()
+                    .map(|(option, ciphertext)| (option.clone(), Self::partial(key_share, ciphertext)))
+                    .collect();
+                (contest.clone(), partials)
+            })
+            .collect())
+    }
+
+    /// Partially decrypts every chunk of every mixed ballot, proving each factor.
+    pub fn partially_decrypt_ballots(&self, ballots: &[MixedBallot]) -> Result<PartialMix, String> {
+        let key_share = self.key_share.as_ref().ok_or("This trustee has not received its shares yet")?;
+        Ok(ballots.iter()
+            .map(|ballot| ballot.iter().map(|ciphertext| Self::partial(key_share, ciphertext)).collect())
+            .collect())
+    }
+
+    /// The factor `a^x_j` for one ciphertext with its proof.
+    fn partial(key_share: &BigUint, ciphertext: &Ciphertext) -> PartialDecryption {
+        let group = group();
+        let factor = ciphertext.a.modpow(key_share, &group.p);
+        let proof = ChaumPedersenProof::prove(key_share, &group.pow_g(key_share), &ciphertext.a, &factor);
+        PartialDecryption { factor, proof }
+    }
+}
@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 use sqlx::{Pool, Postgres};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
+use crate::jurisdiction::lookup_zip;
 
 /// User structure for database operations
 #[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
@@ -74,6 +75,13 @@ fn handle_db_error(error: sqlx::Error) -> UserError {
     }
 }
 
+/// Checks a zip code resolves to a jurisdiction, which decides the voter's election ballot style.
+fn validate_zip_code(zip_code: &str) -> Result<(), UserError> {
+    lookup_zip(zip_code)
+        .map(|_| ())
+        .ok_or_else(|| UserError::ValidationError(format!("Unknown zip code: {}", zip_code)))
+}
+
 /// User manager to handle all user-related database operations
 pub struct UserManager {
     pool: Pool<Postgres>,
@@ -91,6 +99,7 @@ impl UserManager {
         if registration.name.trim().is_empty() {
             return Err(UserError::ValidationError("Name cannot be empty".to_string()));
         }
+        validate_zip_code(&registration.zip_code)?;
 
         // Parse birth date
         let birth_date = chrono::NaiveDate::parse_from_str(&registration.birth_date, "%Y-%m-%d")
@@ -174,6 +183,9 @@ impl UserManager {
     pub async fn update_user(&self, voter_id: &str, name: Option<String>, zip_code: Option<String>) -> Result<User, UserError> {
         // Check if the user exists.
         let _user = self.get_user_by_voter_id(voter_id).await?;
+        if let Some(zip_code) = &zip_code {
+            validate_zip_code(zip_code)?;
+        }
     
         // Update the user in the database.
         let updated_user = sqlx::query_as::<_, User>(
@@ -1,16 +1,42 @@
 use sqlx::{Pool, Postgres};
 use serde::{Serialize, Deserialize};
 use serde_json::{Value, json};
-use chrono::{DateTime, Utc};
-use std::collections::HashMap;
+use chrono::{DateTime, DurationRound, NaiveDate, TimeDelta, Utc};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+use std::collections::{BTreeMap, HashMap};
+use crate::ballot::{BallotStatus, Turnout, BALLOT_TAG_FIELD};
+use crate::credential::{CredentialKey, CREDENTIAL_KEY_BITS};
+use crate::elgamal::KeyPair;
+use crate::mixnet::PartialMix;
+use crate::challenge::{BallotOpening, PreparedBallot};
+use crate::trustee::{EncryptedShare, PartialTally, TrusteeCommitments};
+use crate::jurisdiction::{lookup_zip, Jurisdiction};
+use crate::poll_manager::ChainPosition;
+use crate::pseudonym;
+use crate::statistics::Demographics;
 
+/// A ballot in the ballot box. Ballots carry a random id and nothing that links them to the
+/// voter who cast them.
 #[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
-pub struct VoteRecord {
-    pub id: i32,
+pub struct BallotRecord {
+    pub ballot_id: String,
     pub poll_id: String,
-    pub voter_id: String,
     pub vote: Value,
-    pub created_at: DateTime<Utc>,
+}
+
+/// That a voter took part in a poll, without how they voted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Participation {
+    pub poll_id: String,
+    pub voter_id: String,
+    /// 1 for a voter who cast one ballot, incremented each time they recast.
+    pub ballots_cast: i32,
+    /// The hour of the voter's first ballot; finer times are not kept so participation cannot
+    /// be matched to ballots.
+    pub voted_hour: DateTime<Utc>,
+    /// Receipt for the voter's current ballot, which they can match against the public chain.
+    pub receipt: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,6 +44,31 @@ pub struct VoteRequest {
     pub poll_id: String,
     pub voter_id: String,
     pub vote_data: Value,
+    /// The voter's demographics at cast time, recorded only for polls that report them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub demographics: Option<Demographics>,
+    /// Where the ballot was chained. The chain publishes it anyway; it orders the ballot when
+    /// the chain is rebuilt.
+    pub position: ChainPosition,
+}
+
+/// The receipt for a ballot: the SHA-256 of the ballot exactly as it appears in its block.
+pub fn ballot_receipt(vote: &Value) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(serde_json::to_string(vote).unwrap_or_default().as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Truncates a time to the hour, the finest time participation is recorded at.
+fn voted_hour(at: DateTime<Utc>) -> DateTime<Utc> {
+    at.duration_trunc(TimeDelta::hours(1)).unwrap_or(at)
+}
+
+/// A voter's weight in a weighted poll, as imported into the registry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoterWeight {
+    pub voter_id: String,
+    pub weight: f64,
 }
 
 #[derive(Debug)]
@@ -41,62 +92,195 @@ impl std::error::Error for VoteServiceError {}
 
 pub struct VoteService {
     pool: Pool<Postgres>,
+    /// Server secret that per-poll pseudonym keys are derived from.
+    pseudonym_secret: Vec<u8>,
 }
 
 impl VoteService {
+    /// Create a vote service with a random pseudonym secret. Pseudonyms it derives cannot be
+    /// recomputed by another process, so servers use `with_pseudonym_secret`.
     pub fn new(pool: Pool<Postgres>) -> Self {
-        Self { pool }
+        Self::with_pseudonym_secret(pool, pseudonym::ephemeral_secret())
     }
 
-    /// Record a vote in the database.
-    pub async fn record_vote(&self, vote_request: VoteRequest) -> Result<VoteRecord, VoteServiceError> {
-        let existing_vote = sqlx::query_scalar::<_, i32>("SELECT id FROM votes WHERE poll_id = $1 AND voter_id = $2")
-            .bind(&vote_request.poll_id)
-            .bind(&vote_request.voter_id)
-            .fetch_optional(&self.pool)
+    /// Create a vote service deriving voter pseudonyms from the given server secret.
+    pub fn with_pseudonym_secret(pool: Pool<Postgres>, pseudonym_secret: Vec<u8>) -> Self {
+        Self { pool, pseudonym_secret }
+    }
+
+    /// Record a voter's participation and their ballot, unlinked, in the database.
+    pub async fn record_vote(&self, vote_request: VoteRequest) -> Result<Participation, VoteServiceError> {
+        let mut tx = self.pool.begin()
             .await
             .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
 
-        if existing_vote.is_some() {
+        let inserted = sqlx::query(
+            r#"
+            INSERT INTO participation (poll_id, voter_id, voted_hour)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (poll_id, voter_id) DO NOTHING
+            "#
+        )
+        .bind(&vote_request.poll_id)
+        .bind(&vote_request.voter_id)
+        .bind(voted_hour(Utc::now()))
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?
+        .rows_affected();
+
+        if inserted == 0 {
             return Err(VoteServiceError::AlreadyVoted(
                 format!("Voter {} has already voted in poll {}", vote_request.voter_id, vote_request.poll_id)
             ));
         }
 
-        let vote_record = sqlx::query_as::<_, VoteRecord>(
+        Self::insert_ballot(&mut tx, &vote_request.poll_id, &vote_request.vote_data, vote_request.demographics.as_ref(), vote_request.position).await?;
+        tx.commit()
+            .await
+            .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+
+        self.participation_with_receipt(&vote_request).await
+    }
+
+    /// Record a recast ballot that supersedes the voter's current one. The superseded ballot is
+    /// found by the ballot tag the recast carries, which only the voter's ballot handle yields;
+    /// ballots hold no voter identifier.
+    pub async fn record_revote(&self, vote_request: VoteRequest) -> Result<Participation, VoteServiceError> {
+        let ballot_tag = vote_request.vote_data.get(BALLOT_TAG_FIELD)
+            .and_then(Value::as_str)
+            .ok_or_else(|| VoteServiceError::ValidationError("A recast ballot must carry its ballot tag".to_string()))?;
+        let mut tx = self.pool.begin()
+            .await
+            .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+
+        let updated = sqlx::query(
+            "UPDATE participation SET ballots_cast = ballots_cast + 1 WHERE poll_id = $1 AND voter_id = $2"
+        )
+        .bind(&vote_request.poll_id)
+        .bind(&vote_request.voter_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?
+        .rows_affected();
+        if updated == 0 {
+            return Err(VoteServiceError::ValidationError(
+                format!("Voter {} has no ballot to recast in poll {}", vote_request.voter_id, vote_request.poll_id)
+            ));
+        }
+
+        let superseded = sqlx::query(
             r#"
-            INSERT INTO votes (poll_id, voter_id, vote)
-            VALUES ($1, $2, $3)
-            RETURNING id, poll_id, voter_id, vote, created_at
+            UPDATE ballots SET superseded = TRUE
+            WHERE poll_id = $1 AND vote->>'ballot_tag' = $2 AND NOT superseded
             "#
         )
         .bind(&vote_request.poll_id)
-        .bind(&vote_request.voter_id)
-        .bind(&vote_request.vote_data)
-        .fetch_one(&self.pool)
+        .bind(ballot_tag)
+        .execute(&mut *tx)
         .await
-        .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+        .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?
+        .rows_affected();
+        if superseded != 1 {
+            return Err(VoteServiceError::ValidationError(
+                format!("No current ballot in poll {} matches the ballot handle", vote_request.poll_id)
+            ));
+        }
+
+        Self::insert_ballot(&mut tx, &vote_request.poll_id, &vote_request.vote_data, vote_request.demographics.as_ref(), vote_request.position).await?;
+        tx.commit()
+            .await
+            .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
 
-        Ok(vote_record)
+        self.participation_with_receipt(&vote_request).await
     }
 
-    /// Get votes for a specific poll.
-    pub async fn get_poll_votes(&self, poll_id: &str) -> Result<Vec<VoteRecord>, VoteServiceError> {
-        let votes = sqlx::query_as::<_, VoteRecord>(
-            "SELECT id, poll_id, voter_id, vote, created_at FROM votes WHERE poll_id = $1"
+    /// Record a voter's participation in a commit-reveal poll along with the commitment they
+    /// chained. Their ballot enters the ballot box only once it is revealed.
+    pub async fn record_commitment(&self, poll_id: &str, voter_id: &str, record: &Value, position: ChainPosition) -> Result<Participation, VoteServiceError> {
+        let mut tx = self.pool.begin()
+            .await
+            .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+
+        let inserted = sqlx::query(
+            r#"
+            INSERT INTO participation (poll_id, voter_id, voted_hour)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (poll_id, voter_id) DO NOTHING
+            "#
         )
         .bind(poll_id)
-        .fetch_all(&self.pool)
+        .bind(voter_id)
+        .bind(voted_hour(Utc::now()))
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?
+        .rows_affected();
+
+        if inserted == 0 {
+            return Err(VoteServiceError::AlreadyVoted(
+                format!("Voter {} has already voted in poll {}", voter_id, poll_id)
+            ));
+        }
+
+        Self::insert_chain_record(&mut *tx, poll_id, record, position).await?;
+        tx.commit()
+            .await
+            .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+
+        self.get_participation(poll_id, voter_id).await?
+            .ok_or_else(|| VoteServiceError::DatabaseError("Participation was not recorded".to_string()))
+    }
+
+    /// Put a revealed ballot in the ballot box, returning its receipt.
+    pub async fn record_revealed_ballot(&self, poll_id: &str, vote: &Value, position: ChainPosition) -> Result<String, VoteServiceError> {
+        let mut tx = self.pool.begin()
+            .await
+            .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+        Self::insert_ballot(&mut tx, poll_id, vote, None, position).await?;
+        tx.commit()
+            .await
+            .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+        Ok(ballot_receipt(vote))
+    }
+
+    /// Put a ballot in the ballot box under a random id.
+    async fn insert_ballot(
+        tx: &mut sqlx::Transaction<'_, Postgres>,
+        poll_id: &str,
+        vote: &Value,
+        demographics: Option<&Demographics>,
+        position: ChainPosition,
+    ) -> Result<(), VoteServiceError> {
+        sqlx::query(
+            r#"
+            INSERT INTO ballots (ballot_id, poll_id, vote, demographics, chain_seq, chained_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(poll_id)
+        .bind(vote)
+        .bind(demographics.map(|d| json!(d)))
+        .bind(position.sequence)
+        .bind(position.chained_at)
+        .execute(&mut **tx)
         .await
         .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
 
-        Ok(votes)
+        Ok(())
+    }
+
+    async fn participation_with_receipt(&self, vote_request: &VoteRequest) -> Result<Participation, VoteServiceError> {
+        let mut participation = self.get_participation(&vote_request.poll_id, &vote_request.voter_id).await?
+            .ok_or_else(|| VoteServiceError::DatabaseError("Participation was not recorded".to_string()))?;
+        participation.receipt = Some(ballot_receipt(&vote_request.vote_data));
+        Ok(participation)
     }
 
-    /// Get a vote by voter_id and poll_id.
-    pub async fn get_vote(&self, poll_id: &str, voter_id: &str) -> Result<Option<VoteRecord>, VoteServiceError> {
-        let vote = sqlx::query_as::<_, VoteRecord>(
-            "SELECT id, poll_id, voter_id, vote, created_at FROM votes WHERE poll_id = $1 AND voter_id = $2"
+    async fn get_participation(&self, poll_id: &str, voter_id: &str) -> Result<Option<Participation>, VoteServiceError> {
+        let row = sqlx::query_as::<_, (i32, DateTime<Utc>)>(
+            "SELECT ballots_cast, voted_hour FROM participation WHERE poll_id = $1 AND voter_id = $2"
         )
         .bind(poll_id)
         .bind(voter_id)
@@ -104,36 +288,92 @@ impl VoteService {
         .await
         .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
 
-        Ok(vote)
+        Ok(row.map(|(ballots_cast, voted_hour)| Participation {
+            poll_id: poll_id.to_string(),
+            voter_id: voter_id.to_string(),
+            ballots_cast,
+            voted_hour,
+            receipt: None,
+        }))
     }
 
-    /// Get vote counts for a poll.
+    /// Get the current ballots in a poll, in random order. Superseded ballots are left out.
+    pub async fn get_poll_votes(&self, poll_id: &str) -> Result<Vec<BallotRecord>, VoteServiceError> {
+        let votes = sqlx::query_as::<_, BallotRecord>(
+            r#"
+            SELECT ballot_id, poll_id, vote
+            FROM ballots WHERE poll_id = $1 AND NOT superseded
+            ORDER BY ballot_id
+            "#
+        )
+        .bind(poll_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(votes)
+    }
+
+    /// Get whether a voter took part in a poll. Ballots hold no voter identifier, so neither how
+    /// they voted nor a receipt for their ballot can be looked up; the receipt is only issued
+    /// when the ballot is cast.
+    pub async fn get_vote(&self, poll_id: &str, voter_id: &str) -> Result<Option<Participation>, VoteServiceError> {
+        self.get_participation(poll_id, voter_id).await
+    }
+
+    /// Get vote counts for a poll. Only valid ballots naming a choice are counted; abstentions,
+    /// blank and spoiled ballots are reported by `get_turnout` instead.
     pub async fn get_vote_counts(&self, poll_id: &str) -> Result<Value, VoteServiceError> {
         let votes = self.get_poll_votes(poll_id).await?;
         let mut counts = HashMap::new();
         for vote in votes {
+            if BallotStatus::of_record(&vote.vote) != BallotStatus::Valid {
+                continue;
+            }
             let key = match vote.vote {
                 Value::String(s) => s,
                 Value::Object(ref obj) => {
-                    if let Some(Value::String(candidate)) = obj.get("candidate") {
-                        candidate.clone()
-                    } else if let Some(Value::String(choice)) = obj.get("choice") {
-                        choice.clone()
-                    } else {
-                        serde_json::to_string(&vote.vote).unwrap_or_else(|_| "unknown".to_string())
+                    let choice = ["candidate", "choice", "default_choice"].iter()
+                        .find_map(|field| obj.get(*field).and_then(|v| v.as_str()));
+                    match choice {
+                        Some(choice) => choice.to_string(),
+                        None => continue,
                     }
                 },
-                _ => serde_json::to_string(&vote.vote).unwrap_or_else(|_| "unknown".to_string()),
+                _ => continue,
             };
             *counts.entry(key).or_insert(0) += 1;
         }
         Ok(json!(counts))
     }
 
+    /// Count the current ballots in a poll by status.
+    pub async fn get_turnout(&self, poll_id: &str) -> Result<Turnout, VoteServiceError> {
+        let rows = sqlx::query_as::<_, (String, i64)>(
+            r#"
+            SELECT COALESCE(vote->>'ballot_status', 'valid') AS status, COUNT(*)
+            FROM ballots
+            WHERE poll_id = $1 AND NOT superseded
+            GROUP BY 1
+            "#
+        )
+        .bind(poll_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+
+        let mut turnout = Turnout::default();
+        for (status, count) in rows {
+            let status: BallotStatus = serde_json::from_value(Value::String(status)).unwrap_or_default();
+            turnout.add(status, count as u32);
+        }
+        Ok(turnout)
+    }
+
     /// Check if a voter has already voted in a specific poll.
     pub async fn has_voted(&self, poll_id: &str, voter_id: &str) -> Result<bool, VoteServiceError> {
         let exists = sqlx::query_scalar::<_, bool>(
-            "SELECT EXISTS(SELECT 1 FROM votes WHERE poll_id = $1 AND voter_id = $2)"
+            "SELECT EXISTS(SELECT 1 FROM participation WHERE poll_id = $1 AND voter_id = $2)"
         )
         .bind(poll_id)
         .bind(voter_id)
@@ -144,9 +384,579 @@ impl VoteService {
         Ok(exists)
     }
 
+    /// Import weights into the registry for a weighted poll. Each entry takes effect now and
+    /// supersedes earlier weights for the same voter; votes already cast keep their weight.
+    pub async fn import_voter_weights(&self, poll_id: &str, weights: &[VoterWeight]) -> Result<u64, VoteServiceError> {
+        let mut tx = self.pool.begin()
+            .await
+            .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+
+        for entry in weights {
+            if !entry.weight.is_finite() || entry.weight < 0.0 {
+                return Err(VoteServiceError::ValidationError(
+                    format!("Weight for voter {} must be a non-negative number", entry.voter_id)
+                ));
+            }
+            sqlx::query("INSERT INTO voter_weights (poll_id, voter_id, weight) VALUES ($1, $2, $3)")
+                .bind(poll_id)
+                .bind(&entry.voter_id)
+                .bind(entry.weight)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+        Ok(weights.len() as u64)
+    }
+
+    /// Get the weight currently in effect for a voter in a weighted poll, if they are eligible.
+    pub async fn get_voter_weight(&self, poll_id: &str, voter_id: &str) -> Result<Option<f64>, VoteServiceError> {
+        let weight = sqlx::query_scalar::<_, f64>(
+            r#"
+            SELECT weight FROM voter_weights
+            WHERE poll_id = $1 AND voter_id = $2 AND effective_from <= NOW()
+            ORDER BY effective_from DESC, id DESC
+            LIMIT 1
+            "#
+        )
+        .bind(poll_id)
+        .bind(voter_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(weight)
+    }
+
+    /// Get a poll's credential signing key, generating one on first use.
+    pub async fn get_credential_key(&self, poll_id: &str) -> Result<CredentialKey, VoteServiceError> {
+        let stored = sqlx::query_scalar::<_, Value>("SELECT key FROM credential_keys WHERE poll_id = $1")
+            .bind(poll_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+        if stored.is_none() {
+            let key = tokio::task::spawn_blocking(|| CredentialKey::generate(CREDENTIAL_KEY_BITS))
+                .await
+                .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+            // Another request may have generated a key first; whichever was stored wins
+            sqlx::query("INSERT INTO credential_keys (poll_id, key) VALUES ($1, $2) ON CONFLICT (poll_id) DO NOTHING")
+                .bind(poll_id)
+                .bind(json!(key))
+                .execute(&self.pool)
+                .await
+                .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+        }
+        let key = sqlx::query_scalar::<_, Value>("SELECT key FROM credential_keys WHERE poll_id = $1")
+            .bind(poll_id)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+
+        serde_json::from_value(key).map_err(|e| VoteServiceError::DatabaseError(e.to_string()))
+    }
+
+    /// Get an encrypted poll's election key, generating one on first use.
+    pub async fn get_encryption_key(&self, poll_id: &str) -> Result<KeyPair, VoteServiceError> {
+        let stored = sqlx::query_scalar::<_, Value>("SELECT key FROM encryption_keys WHERE poll_id = $1")
+            .bind(poll_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+        if stored.is_none() {
+            // Another request may have generated a key first; whichever was stored wins
+            sqlx::query("INSERT INTO encryption_keys (poll_id, key) VALUES ($1, $2) ON CONFLICT (poll_id) DO NOTHING")
+                .bind(poll_id)
+                .bind(json!(KeyPair::generate()))
+                .execute(&self.pool)
+                .await
+                .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+        }
+        let key = sqlx::query_scalar::<_, Value>("SELECT key FROM encryption_keys WHERE poll_id = $1")
+            .bind(poll_id)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+
+        serde_json::from_value(key).map_err(|e| VoteServiceError::DatabaseError(e.to_string()))
+    }
+
+    /// Record a trustee's published commitments. Each trustee publishes once.
+    pub async fn record_trustee_commitments(&self, poll_id: &str, trustee: &str, commitments: &TrusteeCommitments) -> Result<(), VoteServiceError> {
+        let inserted = sqlx::query(
+            "INSERT INTO trustee_commitments (poll_id, trustee, commitments) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING"
+        )
+        .bind(poll_id)
+        .bind(trustee)
+        .bind(json!(commitments))
+        .execute(&self.pool)
+        .await
+        .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?
+        .rows_affected();
+
+        if inserted == 0 {
+            return Err(VoteServiceError::ValidationError(
+                format!("Trustee {} has already published commitments for poll {}", trustee, poll_id)
+            ));
+        }
+        Ok(())
+    }
+
+    /// Get the commitments published so far in a poll's key ceremony, keyed by trustee.
+    pub async fn get_trustee_commitments(&self, poll_id: &str) -> Result<HashMap<String, TrusteeCommitments>, VoteServiceError> {
+        let rows = sqlx::query_as::<_, (String, Value)>(
+            "SELECT trustee, commitments FROM trustee_commitments WHERE poll_id = $1"
+        )
+        .bind(poll_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+
+        rows.into_iter()
+            .map(|(trustee, commitments)| {
+                let commitments = serde_json::from_value(commitments)
+                    .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+                Ok((trustee, commitments))
+            })
+            .collect()
+    }
+
+    /// Record the encrypted shares a trustee sends the other trustees. Each trustee sends its
+    /// shares once.
+    pub async fn record_trustee_shares(&self, poll_id: &str, sender: &str, shares: &BTreeMap<String, EncryptedShare>) -> Result<(), VoteServiceError> {
+        let mut tx = self.pool.begin()
+            .await
+            .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+        for (recipient, share) in shares {
+            let inserted = sqlx::query(
+                "INSERT INTO trustee_shares (poll_id, sender, recipient, share) VALUES ($1, $2, $3, $4) ON CONFLICT DO NOTHING"
+            )
+            .bind(poll_id)
+            .bind(sender)
+            .bind(recipient)
+            .bind(json!(share))
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?
+            .rows_affected();
+
+            if inserted == 0 {
+                return Err(VoteServiceError::ValidationError(
+                    format!("Trustee {} has already distributed its shares for poll {}", sender, poll_id)
+                ));
+            }
+        }
+        tx.commit()
+            .await
+            .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))
+    }
+
+    /// Get the trustees that have distributed their shares in a poll's key ceremony.
+    pub async fn get_share_senders(&self, poll_id: &str) -> Result<Vec<String>, VoteServiceError> {
+        sqlx::query_scalar::<_, String>("SELECT DISTINCT sender FROM trustee_shares WHERE poll_id = $1")
+            .bind(poll_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))
+    }
+
+    /// Get the encrypted shares sent to a trustee, keyed by sender.
+    pub async fn get_trustee_shares(&self, poll_id: &str, recipient: &str) -> Result<BTreeMap<String, EncryptedShare>, VoteServiceError> {
+        let rows = sqlx::query_as::<_, (String, Value)>(
+            "SELECT sender, share FROM trustee_shares WHERE poll_id = $1 AND recipient = $2"
+        )
+        .bind(poll_id)
+        .bind(recipient)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+
+        rows.into_iter()
+            .map(|(sender, share)| {
+                let share = serde_json::from_value(share)
+                    .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+                Ok((sender, share))
+            })
+            .collect()
+    }
+
+    /// Record a trustee's partial decryption of a poll's tally. Each trustee submits once.
+    pub async fn record_partial_decryption(&self, poll_id: &str, trustee: &str, partial: &PartialTally) -> Result<(), VoteServiceError> {
+        let inserted = sqlx::query(
+            "INSERT INTO partial_decryptions (poll_id, trustee, decryption) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING"
+        )
+        .bind(poll_id)
+        .bind(trustee)
+        .bind(json!(partial))
+        .execute(&self.pool)
+        .await
+        .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?
+        .rows_affected();
+
+        if inserted == 0 {
+            return Err(VoteServiceError::ValidationError(
+                format!("Trustee {} has already submitted a partial decryption for poll {}", trustee, poll_id)
+            ));
+        }
+        Ok(())
+    }
+
+    /// Get the partial decryptions submitted for a poll's tally, keyed by trustee.
+    pub async fn get_partial_decryptions(&self, poll_id: &str) -> Result<BTreeMap<String, PartialTally>, VoteServiceError> {
+        let rows = sqlx::query_as::<_, (String, Value)>(
+            "SELECT trustee, decryption FROM partial_decryptions WHERE poll_id = $1"
+        )
+        .bind(poll_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+
+        rows.into_iter()
+            .map(|(trustee, decryption)| {
+                let decryption = serde_json::from_value(decryption)
+                    .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+                Ok((trustee, decryption))
+            })
+            .collect()
+    }
+
+    /// Record a trustee's proven partial decryption of a poll's mixed ballots. Each trustee
+    /// submits one.
+    pub async fn record_mix_decryption(&self, poll_id: &str, trustee: &str, partial: &PartialMix) -> Result<(), VoteServiceError> {
+        let inserted = sqlx::query(
+            "INSERT INTO mix_decryptions (poll_id, trustee, decryption) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING"
+        )
+        .bind(poll_id)
+        .bind(trustee)
+        .bind(json!(partial))
+        .execute(&self.pool)
+        .await
+        .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?
+        .rows_affected();
+
+        if inserted == 0 {
+            return Err(VoteServiceError::ValidationError(
+                format!("Trustee {} has already submitted a partial decryption of the mix for poll {}", trustee, poll_id)
+            ));
+        }
+        Ok(())
+    }
+
+    /// Get the partial decryptions submitted for a poll's mixed ballots, keyed by trustee.
+    pub async fn get_mix_decryptions(&self, poll_id: &str) -> Result<BTreeMap<String, PartialMix>, VoteServiceError> {
+        let rows = sqlx::query_as::<_, (String, Value)>(
+            "SELECT trustee, decryption FROM mix_decryptions WHERE poll_id = $1"
+        )
+        .bind(poll_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+
+        rows.into_iter()
+            .map(|(trustee, decryption)| {
+                let decryption = serde_json::from_value(decryption)
+                    .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+                Ok((trustee, decryption))
+            })
+            .collect()
+    }
+
+    /// Hold a ballot prepared for a voter, with its opening, until they cast or challenge it.
+    pub async fn record_prepared_ballot(&self, poll_id: &str, voter_id: &str, prepared: &PreparedBallot, opening: &BallotOpening) -> Result<(), VoteServiceError> {
+        sqlx::query(
+            "INSERT INTO prepared_ballots (tracking_code, poll_id, voter_id, ballot, opening) VALUES ($1, $2, $3, $4, $5)"
+        )
+        .bind(&prepared.tracking_code)
+        .bind(poll_id)
+        .bind(voter_id)
+        .bind(&prepared.ballot)
+        .bind(json!(opening))
+        .execute(&self.pool)
+        .await
+        .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Get a ballot prepared for a voter by its tracking code.
+    pub async fn get_prepared_ballot(&self, poll_id: &str, voter_id: &str, tracking_code: &str) -> Result<Option<Value>, VoteServiceError> {
+        sqlx::query_scalar::<_, Value>(
+            "SELECT ballot FROM prepared_ballots WHERE tracking_code = $1 AND poll_id = $2 AND voter_id = $3"
+        )
+        .bind(tracking_code)
+        .bind(poll_id)
+        .bind(voter_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))
+    }
+
+    /// Remove a ballot prepared for a voter, returning it with its opening.
+    pub async fn take_prepared_ballot(&self, poll_id: &str, voter_id: &str, tracking_code: &str) -> Result<Option<(Value, BallotOpening)>, VoteServiceError> {
+        let row = sqlx::query_as::<_, (Value, Value)>(
+            "DELETE FROM prepared_ballots WHERE tracking_code = $1 AND poll_id = $2 AND voter_id = $3 RETURNING ballot, opening"
+        )
+        .bind(tracking_code)
+        .bind(poll_id)
+        .bind(voter_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+
+        row.map(|(ballot, opening)| {
+            let opening = serde_json::from_value(opening).map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+            Ok((ballot, opening))
+        })
+        .transpose()
+    }
+
+    /// Remove every ballot prepared for a voter in a poll, once they have cast one.
+    pub async fn delete_prepared_ballots(&self, poll_id: &str, voter_id: &str) -> Result<(), VoteServiceError> {
+        sqlx::query("DELETE FROM prepared_ballots WHERE poll_id = $1 AND voter_id = $2")
+            .bind(poll_id)
+            .bind(voter_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Record that a voter was issued a credential for a poll. Each voter gets one.
+    pub async fn record_credential_issuance(&self, poll_id: &str, voter_id: &str) -> Result<(), VoteServiceError> {
+        let inserted = sqlx::query(
+            "INSERT INTO credential_issuance (poll_id, voter_id) VALUES ($1, $2) ON CONFLICT DO NOTHING"
+        )
+        .bind(poll_id)
+        .bind(voter_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?
+        .rows_affected();
+
+        if inserted == 0 {
+            return Err(VoteServiceError::AlreadyVoted(
+                format!("Voter {} has already been issued a credential for poll {}", voter_id, poll_id)
+            ));
+        }
+        Ok(())
+    }
+
+    /// Check whether a credential has already been spent in a poll.
+    pub async fn is_credential_spent(&self, poll_id: &str, nullifier: &str) -> Result<bool, VoteServiceError> {
+        sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS(SELECT 1 FROM spent_credentials WHERE poll_id = $1 AND nullifier = $2)"
+        )
+        .bind(poll_id)
+        .bind(nullifier)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))
+    }
+
+    /// Spend a credential and put the ballot cast with it in the ballot box. Returns the ballot's
+    /// receipt.
+    pub async fn record_anonymous_vote(&self, poll_id: &str, nullifier: &str, vote: &Value, position: ChainPosition) -> Result<String, VoteServiceError> {
+        let mut tx = self.pool.begin()
+            .await
+            .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+
+        let inserted = sqlx::query(
+            "INSERT INTO spent_credentials (poll_id, nullifier) VALUES ($1, $2) ON CONFLICT DO NOTHING"
+        )
+        .bind(poll_id)
+        .bind(nullifier)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?
+        .rows_affected();
+
+        if inserted == 0 {
+            return Err(VoteServiceError::AlreadyVoted(
+                format!("Credential has already been spent in poll {}", poll_id)
+            ));
+        }
+
+        Self::insert_ballot(&mut tx, poll_id, vote, None, position).await?;
+        tx.commit()
+            .await
+            .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+        Ok(ballot_receipt(vote))
+    }
+
+    /// Store a non-ballot chain record (such as a write-in adjudication) so it is replayed
+    /// with the poll's votes when the chain is rebuilt.
+    pub async fn record_chain_record(&self, poll_id: &str, record: &Value, position: ChainPosition) -> Result<(), VoteServiceError> {
+        Self::insert_chain_record(&self.pool, poll_id, record, position).await
+    }
+
+    async fn insert_chain_record<'e, E>(executor: E, poll_id: &str, record: &Value, position: ChainPosition) -> Result<(), VoteServiceError>
+    where
+        E: sqlx::Executor<'e, Database = Postgres>,
+    {
+        sqlx::query("INSERT INTO chain_records (poll_id, record, chain_seq, chained_at) VALUES ($1, $2, $3, $4)")
+            .bind(poll_id)
+            .bind(record)
+            .bind(position.sequence)
+            .bind(position.chained_at)
+            .execute(executor)
+            .await
+            .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Count the voters eligible in a poll: those in its weight registry for weighted polls,
+    /// otherwise every registered voter.
+    pub async fn count_eligible_voters(&self, poll_id: &str) -> Result<u64, VoteServiceError> {
+        let count = sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT CASE
+                WHEN EXISTS(SELECT 1 FROM voter_weights WHERE poll_id = $1)
+                    THEN (SELECT COUNT(DISTINCT voter_id) FROM voter_weights WHERE poll_id = $1)
+                ELSE (SELECT COUNT(*) FROM voters)
+            END
+            "#
+        )
+        .bind(poll_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(count as u64)
+    }
+
+    /// Look up the jurisdiction of a registered voter from the zip code on their registration.
+    pub async fn get_voter_jurisdiction(&self, voter_id: &str) -> Result<Option<Jurisdiction>, VoteServiceError> {
+        let zip_code = sqlx::query_scalar::<_, String>("SELECT zip_code FROM voters WHERE voter_id = $1")
+            .bind(voter_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(zip_code.and_then(|zip| lookup_zip(&zip)))
+    }
+
+    /// Derive a registered voter's demographics on today's date from their birth date and zip code.
+    pub async fn get_voter_demographics(&self, voter_id: &str) -> Result<Option<Demographics>, VoteServiceError> {
+        let voter = sqlx::query_as::<_, (Option<NaiveDate>, Option<String>)>(
+            "SELECT birth_date, zip_code FROM voters WHERE voter_id = $1"
+        )
+        .bind(voter_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(voter.map(|(birth_date, zip_code)| {
+            Demographics::derive(birth_date, zip_code.as_deref(), Utc::now().date_naive())
+        }))
+    }
+
+    /// Get the current ballots in a poll together with the demographics recorded when they were
+    /// cast. Ballots cast without demographics are left out.
+    pub async fn get_demographic_ballots(&self, poll_id: &str) -> Result<Vec<(Demographics, Value)>, VoteServiceError> {
+        let rows = sqlx::query_as::<_, (Value, Option<Value>)>(
+            r#"
+            SELECT vote, demographics FROM ballots
+            WHERE poll_id = $1 AND NOT superseded
+            ORDER BY ballot_id
+            "#
+        )
+        .bind(poll_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(rows.into_iter()
+            .filter_map(|(vote, demographics)| Some((serde_json::from_value(demographics?).ok()?, vote)))
+            .collect())
+    }
+
+    /// List every registered voter's zip code with the hour of their first ballot in a poll, or
+    /// `None` if they have not voted in it.
+    pub async fn get_voter_roll(&self, poll_id: &str) -> Result<Vec<(Option<String>, Option<DateTime<Utc>>)>, VoteServiceError> {
+        let roll = sqlx::query_as::<_, (Option<String>, Option<DateTime<Utc>>)>(
+            r#"
+            SELECT v.zip_code, p.voted_hour
+            FROM voters v
+            LEFT JOIN participation p ON p.voter_id = v.voter_id AND p.poll_id = $1
+            "#
+        )
+        .bind(poll_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(roll)
+    }
+
+    /// Fetch the ballots and other chain records of a poll with their block timestamps in
+    /// milliseconds, in the order they were added to its chain.
+    pub async fn get_chain_entries(&self, poll_id: &str) -> Result<Vec<(Value, i64)>, VoteServiceError> {
+        let rows = sqlx::query_as::<_, (Value, i64)>(
+            r#"
+            SELECT vote, chained_at FROM (
+                SELECT vote, chained_at, chain_seq FROM ballots WHERE poll_id = $1
+                UNION ALL
+                SELECT record, chained_at, chain_seq FROM chain_records WHERE poll_id = $1
+            ) entries
+            ORDER BY chain_seq
+            "#
+        )
+        .bind(poll_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(rows)
+    }
+
+    /// The key a poll's voter pseudonyms are derived with, salting the poll on first use.
+    async fn pseudonym_key(&self, poll_id: &str) -> Result<Vec<u8>, VoteServiceError> {
+        sqlx::query("INSERT INTO pseudonym_salts (poll_id, salt) VALUES ($1, $2) ON CONFLICT (poll_id) DO NOTHING")
+            .bind(poll_id)
+            .bind(pseudonym::new_salt())
+            .execute(&self.pool)
+            .await
+            .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+        let salt = sqlx::query_scalar::<_, String>("SELECT salt FROM pseudonym_salts WHERE poll_id = $1")
+            .bind(poll_id)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(pseudonym::poll_key(&self.pseudonym_secret, poll_id, &salt))
+    }
+
+    /// The pseudonym that stands in for a voter's id on a poll's chain.
+    pub async fn voter_pseudonym(&self, poll_id: &str, voter_id: &str) -> Result<String, VoteServiceError> {
+        Ok(pseudonym::pseudonym(&self.pseudonym_key(poll_id).await?, voter_id))
+    }
+
+    /// Replace a poll's pseudonym key with a fresh one. Nothing stored on the chain is derived
+    /// from the key, so no ballot, record or block hash changes.
+    pub async fn rotate_pseudonym_key(&self, poll_id: &str) -> Result<(), VoteServiceError> {
+        sqlx::query(
+            r#"
+            INSERT INTO pseudonym_salts (poll_id, salt) VALUES ($1, $2)
+            ON CONFLICT (poll_id) DO UPDATE SET salt = EXCLUDED.salt, rotated_at = NOW()
+            "#
+        )
+        .bind(poll_id)
+        .bind(pseudonym::new_salt())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
     /// Get all polls that have votes.
     pub async fn get_active_polls(&self) -> Result<Vec<String>, VoteServiceError> {
-        let poll_ids = sqlx::query_scalar::<_, String>("SELECT DISTINCT poll_id FROM votes")
+        let poll_ids = sqlx::query_scalar::<_, String>("SELECT DISTINCT poll_id FROM participation")
             .fetch_all(&self.pool)
             .await
             .map_err(|e| VoteServiceError::DatabaseError(e.to_string()))?;
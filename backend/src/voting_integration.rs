@@ -1,8 +1,32 @@
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use tokio::sync::Mutex;
-use serde_json::{Value, json};
-use crate::vote_service::{VoteService, VoteRequest, VoteServiceError};
-use crate::poll_manager::{PollManager, Poll};
+use serde::{Serialize, Deserialize};
+use serde_json::{Map, Value, json};
+use crate::vote_service::{self, VoteService, VoteRequest, VoteServiceError, VoterWeight};
+use crate::poll_manager::{PollManager, PollInput, Poll};
+use crate::blockchain::Blockchain;
+use crate::ballot::{self, is_abstention, is_blank_selection, BallotStatus, BALLOT_STATUS_FIELD, BALLOT_TAG_FIELD, SUPERSEDES_FIELD};
+use chrono::Utc;
+use crate::outcome::{self, Outcome, OutcomeStatus};
+use crate::tie_break::{self, TieBreakPolicy, TieBreakRecord, TieBreaker, TIE_BREAK_RECORD};
+use crate::quadratic;
+use crate::budgeting;
+use crate::contest::{normalize_write_in, parse_contests, ContestMethod, INVALID_WRITE_IN};
+use crate::election_blockchain::{ADJUDICATION_RECORD, RECORD_TYPE_FIELD};
+use crate::jurisdiction::{self, BallotStyle, Jurisdiction};
+use crate::statistics::{self, CrossTab, TurnoutReport, DEFAULT_MIN_CELL_SIZE};
+use crate::timeline::Bucket;
+use crate::credential::{self, Credential, CredentialPublicKey, NULLIFIER_FIELD};
+use crate::crypto;
+use crate::elgamal::{self, PublicKey};
+use crate::trustee::{self, Ceremony, CeremonyTrustee, EncryptedShare, PartialTally, TrusteeCommitments, TrusteeConfig};
+use num_bigint_dig::BigUint;
+use crate::encrypted_tally::{DecryptedTally, EncryptedLayout, EncryptedTally, DECRYPTED_TALLY_RECORD};
+use crate::challenge::{self, ChallengedBallot, PreparedBallot, CHALLENGED_BALLOT_RECORD};
+use crate::commit_reveal::{self, COMMITMENT_FIELD, COMMITMENT_RECORD, REVEALS_FIELD, SALT_FIELD};
+use crate::mixnet::{self, MixDecryption, PartialMix, ShuffleRecord, MIX_DECRYPTION_RECORD, SHUFFLE_RECORD};
+use crate::tracker::{self, BallotTracker};
 
 #[derive(Debug)]
 pub enum VotingError {
@@ -39,6 +63,51 @@ impl std::fmt::Display for VotingError {
 
 impl std::error::Error for VotingError {}
 
+/// Input for settling a tie by lot or by official decision.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TieBreakRequest {
+    /// The election contest the tie is in; omitted for a poll's own outcome.
+    #[serde(default)]
+    pub contest: Option<String>,
+    pub tied: Vec<String>,
+    /// The official's ranking, most favoured first; omitted when drawing lots.
+    #[serde(default)]
+    pub ranking: Option<Vec<String>>,
+    pub official: String,
+}
+
+/// A voter's ballot in a commit-reveal poll with the salt it was committed with and the
+/// ballot handle from the commitment's receipt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BallotReveal {
+    pub voter_id: String,
+    pub vote: Value,
+    pub salt: String,
+    pub ballot_handle: String,
+}
+
+/// What a voter is given for a ballot, which is stored without their voter id: its receipt and
+/// the tracker code to look it up by.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BallotReceipt {
+    pub receipt: String,
+    pub tracker_code: String,
+    /// In polls open to revoting, the handle the voter recasts with; in commit-reveal polls, the
+    /// handle they reveal with. It is not stored, so only the voter can link their ballots.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ballot_handle: Option<String>,
+}
+
+/// An official's decision on what a write-in counts for.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WriteInAdjudication {
+    pub contest: String,
+    pub write_in: String,
+    /// A candidate eligible in the contest, or `"invalid"`.
+    pub resolution: String,
+    pub official: String,
+}
+
 pub struct VotingIntegration {
     pub poll_manager: Arc<Mutex<PollManager>>,
     pub vote_service: Arc<VoteService>,
@@ -52,14 +121,25 @@ impl VotingIntegration {
         Self { poll_manager, vote_service }
     }
 
-    /// Cast a vote that is recorded both in the blockchain and database.
-    pub async fn cast_vote(&self, poll_id: &str, voter_id: &str, vote_data: Value) -> Result<(), VotingError> {
+    /// Cast a vote that is recorded both in the blockchain and database. Returns the ballot's
+    /// receipt, the tracker code of the block it was chained in and, in polls open to revoting,
+    /// the handle to recast it with.
+    pub async fn cast_vote(&self, poll_id: &str, voter_id: &str, vote_data: Value) -> Result<BallotReceipt, VotingError> {
+        self.cast(poll_id, voter_id, vote_data, None).await
+    }
+
+    /// Recast a voter's ballot in a poll open to revoting, superseding the ballot cast with
+    /// `ballot_handle`. The new ballot keeps the same handle.
+    pub async fn recast_vote(&self, poll_id: &str, voter_id: &str, ballot_handle: &str, vote_data: Value) -> Result<BallotReceipt, VotingError> {
+        self.cast(poll_id, voter_id, vote_data, Some(ballot_handle)).await
+    }
+
+    async fn cast(&self, poll_id: &str, voter_id: &str, mut vote_data: Value, ballot_handle: Option<&str>) -> Result<BallotReceipt, VotingError> {
         // Determine poll type using poll manager
-        let poll_type = {
+        let metadata = {
             let pm = self.poll_manager.lock().await;
             match pm.get_poll(poll_id) {
-                Some(Poll::Election { .. }) => "election",
-                Some(Poll::Normal { .. }) => "normal",
+                Some(poll) => poll.metadata().clone(),
                 None => {
                     return Err(VotingError::ValidationError(format!(
                         "Poll {} does not exist", poll_id
@@ -67,149 +147,1341 @@ impl VotingIntegration {
                 }
             }
         };
+        let poll_type = metadata.poll_type.clone().unwrap_or_else(|| "normal".to_string());
+        Self::ensure_open(poll_id, &metadata)?;
+        if metadata.settings.anonymous_credentials {
+            return Err(VotingError::ValidationError(format!(
+                "Poll {} only accepts anonymous ballots cast with a credential", poll_id
+            )));
+        }
+        // Ballots can only be encrypted once the poll has an election key
+        let election_key = if metadata.settings.encrypted {
+            Some(self.election_key(poll_id, &metadata).await?)
+        } else {
+            None
+        };
+        // Election ballots are styled by the jurisdiction derived from the voter's registered
+        // zip code; any state or district the client sends is ignored
+        let jurisdiction = if poll_type == "election" {
+            if let Some(vote_obj) = vote_data.as_object_mut() {
+                vote_obj.remove("state");
+                vote_obj.remove("district");
+            }
+            Some(self.voter_jurisdiction(voter_id).await?)
+        } else {
+            None
+        };
+        // Commit-reveal polls chain only a commitment until the ballot is revealed
+        if metadata.settings.reveal_until.is_some() {
+            if ballot_handle.is_some() {
+                return Err(VotingError::ValidationError(format!("Poll {} does not accept recast ballots", poll_id)));
+            }
+            return self.commit_vote(poll_id, voter_id, &vote_data).await;
+        }
+        // Abstentions, blank and spoiled ballots are recorded for turnout but carry no selections
+        let status = BallotStatus::classify(&vote_data);
+        let annotations = if status == BallotStatus::Valid {
+            Self::validate_ballot(&poll_type, &metadata, &vote_data, jurisdiction.as_ref(), election_key.as_ref())?
+        } else {
+            Map::new()
+        };
     
-        // Check if the voter has already voted in the database
+        // Check if the voter has already voted in the database; polls with an open revote
+        // window accept a new ballot that supersedes the one cast with the voter's ballot handle
+        let revote_open = metadata.settings.revote_until.is_some_and(|until| Utc::now() <= until);
+        if ballot_handle.is_some() && !revote_open {
+            return Err(VotingError::ValidationError(format!("Poll {} does not accept recast ballots", poll_id)));
+        }
         let already_voted = self.vote_service.has_voted(poll_id, voter_id).await?;
-        if already_voted {
-            return Err(VotingError::AlreadyVoted(
+        match (already_voted, ballot_handle) {
+            (true, None) if revote_open => return Err(VotingError::AlreadyVoted(format!(
+                "Voter {} has already voted in poll {}; recast with the ballot handle from the receipt", voter_id, poll_id
+            ))),
+            (true, None) => return Err(VotingError::AlreadyVoted(
                 format!("Voter {} has already voted in poll {}", voter_id, poll_id)
-            ));
+            )),
+            (false, Some(_)) => return Err(VotingError::ValidationError(
+                format!("Voter {} has no ballot to recast in poll {}", voter_id, poll_id)
+            )),
+            _ => {}
         }
     
-        // Process vote data into a structured JSON object
-        let processed_vote = if vote_data.is_string() {
-            json!({
-                "voter_id": voter_id,
-                "default_choice": vote_data.as_str().unwrap(),
-                "poll_type": poll_type
-            })
-        } else if vote_data.is_object() {
-            let mut vote_obj = vote_data.as_object().unwrap().clone();
-            
-            // Add voter_id and poll_type to the vote data
-            vote_obj.insert("voter_id".to_string(), Value::String(voter_id.to_string()));
-            vote_obj.insert("poll_type".to_string(), Value::String(poll_type.to_string()));
-            
-            Value::Object(vote_obj)
+        // Weighted polls record the voter's weight in effect at cast time
+        let weight = if poll_type == "weighted" {
+            let weight = self.vote_service.get_voter_weight(poll_id, voter_id).await?;
+            Some(weight.ok_or_else(|| VotingError::ValidationError(
+                format!("Voter {} has no weight in poll {}", voter_id, poll_id)
+            ))?)
         } else {
-            // For other types, convert to a default field
-            json!({
-                "voter_id": voter_id,
-                "default_choice": serde_json::to_string(&vote_data).unwrap_or("unknown".to_string()),
-                "poll_type": poll_type
-            })
+            None
         };
     
-        // Add vote to blockchain using the processed vote
-        {
+        // Polls reporting demographics record the voter's age bracket and region off-chain
+        let demographics = if metadata.settings.demographics {
+            self.vote_service.get_voter_demographics(voter_id).await?
+        } else {
+            None
+        };
+    
+        // Ballots carry no voter identifier. In polls open to revoting they carry a tag derived
+        // from a random handle that only the voter keeps, which links their recasts
+        let ballot_handle = match ballot_handle {
+            Some(handle) => Some(handle.to_string()),
+            None if revote_open => Some(ballot::new_ballot_handle()),
+            None => None,
+        };
+        let ballot_tag = ballot_handle.as_deref().map(|handle| ballot::ballot_tag(poll_id, voter_id, handle));
+    
+        // Process vote data into a structured JSON object
+        let mut processed_vote = Self::process_ballot(&poll_type, status, &vote_data, annotations, ballot_tag.as_deref().map(|tag| (BALLOT_TAG_FIELD, tag)));
+        if let Some(vote_obj) = processed_vote.as_object_mut() {
+            if let Some(weight) = weight {
+                vote_obj.insert("weight".to_string(), json!(weight));
+            }
+        }
+        if let Some(jurisdiction) = &jurisdiction {
+            Self::stamp_jurisdiction(&mut processed_vote, jurisdiction);
+        }
+    
+        // Add vote to blockchain using the processed vote, referencing the ballot a recast
+        // supersedes
+        let recast = already_voted;
+        let (position, tracker_code) = {
             let mut pm = self.poll_manager.lock().await;
-            pm.add_vote(poll_id, processed_vote.clone())
+            if let (true, Some(ballot_tag)) = (recast, &ballot_tag) {
+                let previous = match pm.get_poll(poll_id) {
+                    Some(Poll::Election { blockchain, .. }) => blockchain.find_tagged_ballot(ballot_tag),
+                    Some(Poll::Normal { blockchain, .. }) => blockchain.find_tagged_ballot(ballot_tag),
+                    None => None,
+                };
+                let (block_index, block_hash) = previous.ok_or_else(|| VotingError::ValidationError(
+                    format!("No current ballot in poll {} matches the ballot handle", poll_id)
+                ))?;
+                if let Some(vote_obj) = processed_vote.as_object_mut() {
+                    vote_obj.insert(SUPERSEDES_FIELD.to_string(), json!({
+                        "block_index": block_index,
+                        "block_hash": block_hash
+                    }));
+                }
+            }
+            let position = pm.add_vote_at(poll_id, processed_vote.clone(), ballot::ballot_timestamp(Utc::now()))
                 .map_err(|e| VotingError::PollManagerError(e.to_string()))?;
-        }
+            (position, Self::latest_tracker_code(&pm, poll_id)?)
+        };
     
-        // Record vote in database using the same processed vote
+        // Record the voter's participation and, unlinked from it, the same processed vote
         let vote_request = VoteRequest {
             poll_id: poll_id.to_string(),
             voter_id: voter_id.to_string(),
             vote_data: processed_vote,
+            demographics,
+            position,
+        };
+        let participation = if recast {
+            self.vote_service.record_revote(vote_request).await?
+        } else {
+            self.vote_service.record_vote(vote_request).await?
         };
-        self.vote_service.record_vote(vote_request).await?;
     
-        Ok(())
+        Ok(BallotReceipt {
+            receipt: participation.receipt.unwrap_or_default(),
+            tracker_code,
+            ballot_handle,
+        })
+    }
+
+    /// Chain a voter's commitment to a ballot in a commit-reveal poll. The ballot itself is
+    /// only known once the voter reveals it with its salt after the poll closes. Like a ballot,
+    /// the commitment carries no voter identifier, only the tag of a ballot handle returned to
+    /// the voter to reveal with.
+    async fn commit_vote(&self, poll_id: &str, voter_id: &str, vote_data: &Value) -> Result<BallotReceipt, VotingError> {
+        let commitment = commit_reveal::parse_commitment(vote_data).map_err(VotingError::ValidationError)?;
+        if self.vote_service.has_voted(poll_id, voter_id).await? {
+            return Err(VotingError::AlreadyVoted(
+                format!("Voter {} has already voted in poll {}", voter_id, poll_id)
+            ));
+        }
+        let ballot_handle = ballot::new_ballot_handle();
+        let record = json!({
+            RECORD_TYPE_FIELD: COMMITMENT_RECORD,
+            BALLOT_TAG_FIELD: ballot::ballot_tag(poll_id, voter_id, &ballot_handle),
+            COMMITMENT_FIELD: commitment
+        });
+        let (position, tracker_code) = {
+            let mut pm = self.poll_manager.lock().await;
+            let position = pm.add_vote_at(poll_id, record.clone(), ballot::ballot_timestamp(Utc::now()))
+                .map_err(|e| VotingError::PollManagerError(e.to_string()))?;
+            (position, Self::latest_tracker_code(&pm, poll_id)?)
+        };
+        self.vote_service.record_commitment(poll_id, voter_id, &record, position).await?;
+        Ok(BallotReceipt { receipt: vote_service::ballot_receipt(&record), tracker_code, ballot_handle: Some(ballot_handle) })
     }
 
-    /// Verify a vote in both blockchain and database.
-    pub async fn verify_vote(&self, poll_id: &str, voter_id: &str) -> Result<Value, VotingError> {
-        let blockchain_result = {
+    /// Reveal the ballot behind a voter's commitment once a commit-reveal poll has closed. The
+    /// ballot must hash with the salt to the commitment on the chain and follow the poll's rules;
+    /// it is chained referencing the commitment's block, with the salt so anyone holding the
+    /// ballot can recheck the commitment. Returns the revealed ballot's receipt and tracker code.
+    pub async fn reveal_vote(&self, poll_id: &str, reveal: &BallotReveal) -> Result<BallotReceipt, VotingError> {
+        let BallotReveal { voter_id, vote: vote_data, salt, ballot_handle } = reveal;
+        let metadata = {
             let pm = self.poll_manager.lock().await;
-            match pm.get_poll(poll_id) {
-                Some(Poll::Election { blockchain, .. }) => {
-                    blockchain.find_vote(voter_id).map(|(block_index, vote_hash)| {
-                        json!({
-                            "verified": true,
-                            "block_index": block_index,
-                            "vote_hash": vote_hash,
-                            "timestamp": blockchain.chain[block_index as usize].timestamp
-                        })
-                    })
-                },
-                Some(Poll::Normal { blockchain, .. }) => {
-                    blockchain.find_vote(voter_id).map(|(block_index, vote_hash)| {
-                        json!({
-                            "verified": true,
-                            "block_index": block_index,
-                            "vote_hash": vote_hash,
-                            "timestamp": blockchain.chain[block_index as usize].timestamp
-                        })
-                    })
-                },
+            pm.get_poll(poll_id).map(|poll| poll.metadata().clone())
+                .ok_or_else(|| VotingError::PollNotFound(poll_id.to_string()))?
+        };
+        let Some(reveal_until) = metadata.settings.reveal_until else {
+            return Err(VotingError::ValidationError(format!("Poll {} does not take commitments", poll_id)));
+        };
+        let now = Utc::now();
+        if let Some(closes_at) = metadata.settings.closes_at.filter(|closes_at| now <= *closes_at) {
+            return Err(VotingError::ValidationError(format!(
+                "Ballots in poll {} are revealed after it closes at {}", poll_id, closes_at.to_rfc3339()
+            )));
+        }
+        if now > reveal_until {
+            return Err(VotingError::ValidationError(format!(
+                "Ballots in poll {} could be revealed until {}", poll_id, reveal_until.to_rfc3339()
+            )));
+        }
+        let poll_type = metadata.poll_type.clone().unwrap_or_else(|| "normal".to_string());
+        let ballot_tag = ballot::ballot_tag(poll_id, voter_id, ballot_handle);
+
+        let (block_index, block_hash, commitment) = {
+            let pm = self.poll_manager.lock().await;
+            let committed = match pm.get_poll(poll_id) {
+                Some(Poll::Election { blockchain, .. }) => blockchain.find_tagged_record(COMMITMENT_RECORD, &ballot_tag),
+                Some(Poll::Normal { blockchain, .. }) => blockchain.find_tagged_record(COMMITMENT_RECORD, &ballot_tag),
                 None => None,
+            };
+            committed
+                .map(|(index, hash, record)| (index, hash, record[COMMITMENT_FIELD].as_str().unwrap_or_default().to_string()))
+                .ok_or_else(|| VotingError::ValidationError(format!("No commitment in poll {} matches voter {}'s ballot handle", poll_id, voter_id)))?
+        };
+        if !commit_reveal::opens(poll_id, &commitment, vote_data, salt) {
+            return Err(VotingError::ValidationError(format!(
+                "The ballot and salt do not open voter {}'s commitment in poll {}", voter_id, poll_id
+            )));
+        }
+
+        // The commitment opens the ballot as sent, but as when casting any state or district in
+        // it is ignored in favour of the voter's registered jurisdiction
+        let mut vote_data = vote_data.clone();
+        let jurisdiction = if poll_type == "election" {
+            if let Some(vote_obj) = vote_data.as_object_mut() {
+                vote_obj.remove("state");
+                vote_obj.remove("district");
+            }
+            Some(self.voter_jurisdiction(voter_id).await?)
+        } else {
+            None
+        };
+        let status = BallotStatus::classify(&vote_data);
+        let annotations = if status == BallotStatus::Valid {
+            Self::validate_ballot(&poll_type, &metadata, &vote_data, jurisdiction.as_ref(), None)?
+        } else {
+            Map::new()
+        };
+        // The revealed ballot references its commitment but, like every ballot, carries no voter
+        // identifier
+        let mut processed_vote = Self::process_ballot(&poll_type, status, &vote_data, annotations, None);
+        if let Some(jurisdiction) = &jurisdiction {
+            Self::stamp_jurisdiction(&mut processed_vote, jurisdiction);
+        }
+        if let Some(vote_obj) = processed_vote.as_object_mut() {
+            vote_obj.insert(REVEALS_FIELD.to_string(), json!({
+                "block_index": block_index,
+                "block_hash": block_hash
+            }));
+            vote_obj.insert(SALT_FIELD.to_string(), json!(salt));
+        }
+
+        // The commitment is checked again under the lock so a concurrent reveal of it cannot
+        // reach the chain twice
+        let (position, tracker_code) = {
+            let mut pm = self.poll_manager.lock().await;
+            let revealed = match pm.get_poll(poll_id) {
+                Some(Poll::Election { blockchain, .. }) => blockchain.ballots()
+                    .any(|block| commit_reveal::revealed_block(&block.transactions) == Some(block_index as u64)),
+                Some(Poll::Normal { blockchain, .. }) => blockchain.ballots()
+                    .any(|(_, transaction)| commit_reveal::revealed_block(transaction) == Some(block_index as u64)),
+                None => false,
+            };
+            if revealed {
+                return Err(VotingError::AlreadyVoted(format!("Voter {} has already revealed their ballot in poll {}", voter_id, poll_id)));
+            }
+            let position = pm.add_vote_at(poll_id, processed_vote.clone(), ballot::ballot_timestamp(Utc::now()))
+                .map_err(|e| VotingError::PollManagerError(e.to_string()))?;
+            (position, Self::latest_tracker_code(&pm, poll_id)?)
+        };
+        let receipt = self.vote_service.record_revealed_ballot(poll_id, &processed_vote, position).await?;
+        Ok(BallotReceipt { receipt, tracker_code, ballot_handle: None })
+    }
+
+    /// Stamps an election ballot with the jurisdiction its voter is registered in.
+    fn stamp_jurisdiction(vote: &mut Value, jurisdiction: &Jurisdiction) {
+        if let Some(vote_obj) = vote.as_object_mut() {
+            vote_obj.insert("state".to_string(), json!(jurisdiction.state));
+            if let Some(district) = &jurisdiction.district {
+                vote_obj.insert("district".to_string(), json!(district));
             }
+        }
+    }
+
+    /// Cast a ballot anonymously with a blind-signed credential. The ballot carries the
+    /// credential's nullifier instead of a voter id, so neither the chain nor the database can
+    /// link it to whoever was issued the credential. Returns the ballot's receipt and tracker code.
+    pub async fn cast_anonymous_vote(&self, poll_id: &str, credential: &Credential, vote_data: Value) -> Result<BallotReceipt, VotingError> {
+        let metadata = self.anonymous_poll(poll_id).await?;
+        let poll_type = metadata.poll_type.clone().unwrap_or_else(|| "normal".to_string());
+        Self::ensure_open(poll_id, &metadata)?;
+        // Ballots can only be encrypted once the poll has an election key
+        let election_key = if metadata.settings.encrypted {
+            Some(self.election_key(poll_id, &metadata).await?)
+        } else {
+            None
+        };
+        let key = self.vote_service.get_credential_key(poll_id).await?;
+        if !key.public.verify(poll_id, credential) {
+            return Err(VotingError::ValidationError("Credential signature is not valid for this poll".to_string()));
+        }
+        let nullifier = credential::nullifier(poll_id, &credential.token);
+        if self.vote_service.is_credential_spent(poll_id, &nullifier).await? {
+            return Err(VotingError::AlreadyVoted(format!("Credential has already been spent in poll {}", poll_id)));
+        }
+        let status = BallotStatus::classify(&vote_data);
+        let annotations = if status == BallotStatus::Valid {
+            Self::validate_ballot(&poll_type, &metadata, &vote_data, None, election_key.as_ref())?
+        } else {
+            Map::new()
+        };
+        let processed_vote = Self::process_ballot(&poll_type, status, &vote_data, annotations, Some((NULLIFIER_FIELD, &nullifier)));
+
+        // The credential is spent under the poll manager lock so a concurrent ballot with the
+        // same credential cannot reach the chain
+        let mut pm = self.poll_manager.lock().await;
+        if self.vote_service.is_credential_spent(poll_id, &nullifier).await? {
+            return Err(VotingError::AlreadyVoted(format!("Credential has already been spent in poll {}", poll_id)));
+        }
+        let position = pm.add_vote_at(poll_id, processed_vote.clone(), ballot::ballot_timestamp(Utc::now()))
+            .map_err(|e| VotingError::PollManagerError(e.to_string()))?;
+        let tracker_code = Self::latest_tracker_code(&pm, poll_id)?;
+        let receipt = self.vote_service.record_anonymous_vote(poll_id, &nullifier, &processed_vote, position).await?;
+        Ok(BallotReceipt { receipt, tracker_code, ballot_handle: None })
+    }
+
+    /// The public half of an anonymous poll's credential key, which voters blind their tokens
+    /// with and anyone can verify spent credentials against.
+    pub async fn get_credential_key(&self, poll_id: &str) -> Result<CredentialPublicKey, VotingError> {
+        self.anonymous_poll(poll_id).await?;
+        Ok(self.vote_service.get_credential_key(poll_id).await?.public)
+    }
+
+    /// Sign a voter's blinded token, issuing them the poll's one credential. The server records
+    /// who was issued a credential but never sees the token it signed.
+    pub async fn issue_credential(&self, poll_id: &str, voter_id: &str, blinded: &str) -> Result<Value, VotingError> {
+        self.anonymous_poll(poll_id).await?;
+        let blinded = crypto::parse_hex(blinded)
+            .ok_or_else(|| VotingError::ValidationError("Blinded token must be a hexadecimal integer".to_string()))?;
+        let key = self.vote_service.get_credential_key(poll_id).await?;
+        let blind_signature = key.sign_blinded(&blinded).map_err(VotingError::ValidationError)?;
+        self.vote_service.record_credential_issuance(poll_id, voter_id).await?;
+        Ok(json!({
+            "poll_id": poll_id,
+            "blind_signature": crypto::to_hex(&blind_signature)
+        }))
+    }
+
+    /// The definition of a poll that takes anonymous ballots.
+    async fn anonymous_poll(&self, poll_id: &str) -> Result<PollInput, VotingError> {
+        let pm = self.poll_manager.lock().await;
+        match pm.get_poll(poll_id) {
+            Some(poll) if poll.metadata().settings.anonymous_credentials => Ok(poll.metadata().clone()),
+            Some(_) => Err(VotingError::ValidationError(format!("Poll {} does not use anonymous credentials", poll_id))),
+            None => Err(VotingError::PollNotFound(poll_id.to_string())),
+        }
+    }
+
+    /// Rejects ballots for a poll that has closed.
+    fn ensure_open(poll_id: &str, metadata: &PollInput) -> Result<(), VotingError> {
+        match metadata.settings.closes_at {
+            Some(closes_at) if Utc::now() > closes_at => Err(VotingError::ValidationError(format!(
+                "Poll {} closed at {}", poll_id, closes_at.to_rfc3339()
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Builds the transaction recorded for a ballot, identified on the chain by `identity`: the
+    /// tag of the voter's ballot handle or an anonymous ballot's nullifier. Other ballots have
+    /// none.
+    fn process_ballot(
+        poll_type: &str,
+        status: BallotStatus,
+        vote_data: &Value,
+        annotations: Map<String, Value>,
+        identity: Option<(&str, &str)>,
+    ) -> Value {
+        let mut vote_obj = if status != BallotStatus::Valid {
+            Map::new()
+        } else if let Some(choice) = vote_data.as_str() {
+            let mut vote_obj = Map::new();
+            vote_obj.insert("default_choice".to_string(), json!(choice));
+            vote_obj
+        } else {
+            vote_data.as_object().cloned().unwrap_or_default()
         };
+        vote_obj.remove("voter_id");
+        if let Some((identity_field, identity)) = identity {
+            vote_obj.insert(identity_field.to_string(), json!(identity));
+        }
+        vote_obj.insert("poll_type".to_string(), json!(poll_type));
+        vote_obj.extend(annotations);
+        vote_obj.insert(BALLOT_STATUS_FIELD.to_string(), json!(status.as_str()));
+        Value::Object(vote_obj)
+    }
 
-        let db_vote = self.vote_service.get_vote(poll_id, voter_id).await?;
-        
-        match (blockchain_result, db_vote) {
-            (Some(blockchain_data), Some(db_record)) => {
-                Ok(json!({
-                    "verified": true,
-                    "blockchain": blockchain_data,
-                    "database": {
-                        "id": db_record.id,
-                        "created_at": db_record.created_at,
-                        "vote": db_record.vote
+    /// Checks a ballot against the rules of its poll type before anything is recorded.
+    /// Returns normalized fields to stamp into the vote transaction.
+    fn validate_ballot(
+        poll_type: &str,
+        metadata: &PollInput,
+        vote_data: &Value,
+        jurisdiction: Option<&Jurisdiction>,
+        election_key: Option<&PublicKey>,
+    ) -> Result<Map<String, Value>, VotingError> {
+        let mut annotations = Map::new();
+        for reserved in [RECORD_TYPE_FIELD, SUPERSEDES_FIELD, NULLIFIER_FIELD, REVEALS_FIELD, SALT_FIELD, BALLOT_TAG_FIELD] {
+            if vote_data.get(reserved).is_some() {
+                return Err(VotingError::ValidationError(format!("Ballots cannot carry a \"{}\" field", reserved)));
+            }
+        }
+        // Mixed ballots are sealed whole and checked against the poll's rules once decrypted
+        if let (Some(_), true) = (election_key, metadata.settings.mixnet) {
+            mixnet::parse_ballot(vote_data).map_err(VotingError::ValidationError)?;
+            return Ok(annotations);
+        }
+        // Encrypted ballots carry only ciphertexts, with proofs that each selection is 0 or 1
+        // and each contest within its selection limit in place of the poll's other rules
+        if let Some(key) = election_key {
+            let layout = EncryptedLayout::for_poll(metadata).map_err(VotingError::ValidationError)?;
+            layout.parse_ballot(vote_data, key).map_err(VotingError::ValidationError)?;
+            return Ok(annotations);
+        }
+        match poll_type {
+            "election" => {
+                // Ranked contests must carry a valid ranking so the STV count can use it, and
+                // write-ins are stored normalized so they can be adjudicated once per spelling
+                let contests = parse_contests(&metadata.options).unwrap_or_default();
+                for contest in &contests {
+                    let Some(selection) = vote_data.get(&contest.name) else { continue };
+                    if let Some(jurisdiction) = jurisdiction.filter(|j| !contest.is_eligible(j)) {
+                        return Err(VotingError::ValidationError(format!(
+                            "Contest '{}' is not on the ballot for voters in {}",
+                            contest.name,
+                            jurisdiction.district.as_deref().unwrap_or(&jurisdiction.state)
+                        )));
+                    }
+                    // Blank and abstained contests are stored in one canonical form
+                    if is_blank_selection(selection) {
+                        annotations.insert(contest.name.clone(), Value::Null);
+                        continue;
                     }
-                }))
+                    if is_abstention(selection) {
+                        annotations.insert(contest.name.clone(), json!({ "abstain": true }));
+                        continue;
+                    }
+                    if contest.method != ContestMethod::Stv && contest.write_in_slots == 0 {
+                        continue;
+                    }
+                    let normalized = contest.validate_selection(selection).map_err(VotingError::ValidationError)?;
+                    annotations.insert(contest.name.clone(), normalized);
+                }
             },
-            (Some(blockchain_data), None) => {
-                Ok(json!({
-                    "verified": true,
-                    "blockchain": blockchain_data,
-                    "database": null,
-                    "warning": "Vote found in blockchain but not in database"
-                }))
+            "quadratic" => {
+                let budget = metadata.settings.credit_budget.unwrap_or(0);
+                let allocations = quadratic::validate_ballot(&metadata.options, budget, vote_data)
+                    .map_err(VotingError::ValidationError)?;
+                annotations.insert("credits_spent".to_string(), json!(quadratic::credits_for(&allocations)));
+                annotations.insert("allocations".to_string(), json!(allocations));
             },
-            (None, Some(db_record)) => {
-                Ok(json!({
-                    "verified": true,
-                    "blockchain": null,
-                    "database": {
-                        "id": db_record.id,
-                        "created_at": db_record.created_at,
-                        "vote": db_record.vote
-                    },
-                    "warning": "Vote found in database but not in blockchain"
-                }))
+            "budgeting" => {
+                let costs = metadata.settings.costs.clone().unwrap_or_default();
+                let projects = budgeting::projects_from_options(&metadata.options, &costs)
+                    .map_err(VotingError::ValidationError)?;
+                let selected = budgeting::validate_ballot(&projects, metadata.settings.budget.unwrap_or(0), vote_data)
+                    .map_err(VotingError::ValidationError)?;
+                annotations.insert("projects".to_string(), json!(selected));
             },
-            (None, None) => {
-                Ok(json!({
-                    "verified": false,
-                    "error": "Vote not found in either blockchain or database"
-                }))
+            _ => {},
+        }
+        Ok(annotations)
+    }
+
+    /// Look up a voter's jurisdiction, which every election ballot they cast is stamped with.
+    async fn voter_jurisdiction(&self, voter_id: &str) -> Result<Jurisdiction, VotingError> {
+        self.vote_service.get_voter_jurisdiction(voter_id).await?
+            .ok_or_else(|| VotingError::ValidationError(format!(
+                "Voter {} is not registered with a zip code in a known jurisdiction", voter_id
+            )))
+    }
+
+    /// The contests a voter is eligible to vote in for an election poll.
+    pub async fn get_ballot_style(&self, poll_id: &str, voter_id: &str) -> Result<BallotStyle, VotingError> {
+        let contests = {
+            let pm = self.poll_manager.lock().await;
+            match pm.get_poll(poll_id) {
+                Some(Poll::Election { metadata, .. }) => parse_contests(&metadata.options).map_err(VotingError::ValidationError)?,
+                Some(_) => return Err(VotingError::ValidationError(format!("Poll {} is not an election", poll_id))),
+                None => return Err(VotingError::PollNotFound(poll_id.to_string())),
+            }
+        };
+        let jurisdiction = self.voter_jurisdiction(voter_id).await?;
+        Ok(jurisdiction::ballot_style(&contests, &jurisdiction))
+    }
+
+    /// Cumulative results at the end of each time bucket, replayed from the chain: per option
+    /// for normal polls and per contest for elections.
+    pub async fn get_results_timeline(&self, poll_id: &str, bucket: Bucket) -> Result<Value, VotingError> {
+        let pm = self.poll_manager.lock().await;
+        match pm.get_poll(poll_id) {
+            Some(Poll::Election { blockchain, .. }) => Ok(json!(blockchain.timeline(bucket))),
+            Some(Poll::Normal { metadata, blockchain }) => Ok(json!(blockchain.timeline(&metadata.options, bucket))),
+            None => Err(VotingError::PollNotFound(poll_id.to_string())),
+        }
+    }
+
+    /// Cross-tabulate how ballots in an election split between two of its contests, with
+    /// cells smaller than the poll's minimum cell size suppressed.
+    pub async fn get_cross_tab(&self, poll_id: &str, first: &str, second: &str) -> Result<CrossTab, VotingError> {
+        let pm = self.poll_manager.lock().await;
+        let Some(Poll::Election { metadata, blockchain }) = pm.get_poll(poll_id) else {
+            return Err(match pm.get_poll(poll_id) {
+                Some(_) => VotingError::ValidationError(format!("Poll {} is not an election", poll_id)),
+                None => VotingError::PollNotFound(poll_id.to_string()),
+            });
+        };
+        if first == second {
+            return Err(VotingError::ValidationError("A cross-tab needs two different contests".to_string()));
+        }
+        let contests = parse_contests(&metadata.options).map_err(VotingError::ValidationError)?;
+        for name in [first, second] {
+            match contests.iter().find(|c| c.name == name) {
+                Some(contest) if contest.method == ContestMethod::Plurality => {},
+                Some(_) => return Err(VotingError::ValidationError(format!("Contest '{}' is not a plurality contest", name))),
+                None => return Err(VotingError::ValidationError(format!("Contest '{}' does not exist", name))),
+            }
+        }
+        // Encrypted ballots can only be cross-tabulated once a mix has decrypted them
+        let counted = if metadata.settings.mixnet { mixnet::counted_chain(blockchain) } else { None };
+        let blockchain = match (&counted, metadata.settings.encrypted) {
+            (Some(counted), _) => counted,
+            (None, false) => blockchain,
+            (None, true) => return Err(VotingError::ValidationError(format!(
+                "The ballots of poll {} are encrypted and have not been decrypted", poll_id
+            ))),
+        };
+        let k = metadata.settings.min_cell_size.unwrap_or(DEFAULT_MIN_CELL_SIZE);
+        Ok(statistics::cross_tab(first, second, &blockchain.get_selection_pairs(first, second), k))
+    }
+
+    /// Report turnout of the registered voter roll in a poll: overall, by state, by zip prefix
+    /// and hour by hour, with cells smaller than the poll's minimum cell size suppressed.
+    pub async fn get_turnout_report(&self, poll_id: &str) -> Result<TurnoutReport, VotingError> {
+        let min_cell_size = {
+            let pm = self.poll_manager.lock().await;
+            let poll = pm.get_poll(poll_id).ok_or_else(|| VotingError::PollNotFound(poll_id.to_string()))?;
+            poll.metadata().settings.min_cell_size.unwrap_or(DEFAULT_MIN_CELL_SIZE)
+        };
+        let roll = self.vote_service.get_voter_roll(poll_id).await?;
+        Ok(statistics::turnout_report(&roll, min_cell_size))
+    }
+
+    /// Look up a ballot on a poll's chain by its tracker code, reporting whether it is current
+    /// and counted without revealing who cast it.
+    pub async fn track_ballot(&self, poll_id: &str, code: &str) -> Result<BallotTracker, VotingError> {
+        let code = tracker::parse_tracker_code(code).map_err(VotingError::ValidationError)?;
+        let pm = self.poll_manager.lock().await;
+        let poll = pm.get_poll(poll_id).ok_or_else(|| VotingError::PollNotFound(poll_id.to_string()))?;
+        tracker::track(poll_id, poll, &code)
+            .ok_or_else(|| VotingError::ValidationError(format!("No ballot with tracker code {} in poll {}", code, poll_id)))
+    }
+
+    /// The tracker code of the block just added to a poll's chain.
+    fn latest_tracker_code(pm: &PollManager, poll_id: &str) -> Result<String, VotingError> {
+        pm.get_poll(poll_id)
+            .and_then(tracker::latest_tracker_code)
+            .ok_or_else(|| VotingError::PollNotFound(poll_id.to_string()))
+    }
+
+    /// Rotate a poll's pseudonym key. Ballots and commitments carry ballot tags rather than
+    /// pseudonyms, so the chain is untouched and tracker codes already issued still resolve.
+    pub async fn rotate_pseudonyms(&self, poll_id: &str) -> Result<Value, VotingError> {
+        if self.poll_manager.lock().await.get_poll(poll_id).is_none() {
+            return Err(VotingError::PollNotFound(poll_id.to_string()));
+        }
+        self.vote_service.rotate_pseudonym_key(poll_id).await?;
+        Ok(json!({
+            "poll_id": poll_id,
+            "status": "Pseudonym key rotated"
+        }))
+    }
+
+    /// The public key voters encrypt their selections to in an encrypted poll.
+    pub async fn get_encryption_key(&self, poll_id: &str) -> Result<PublicKey, VotingError> {
+        let metadata = self.encrypted_poll(poll_id).await?;
+        self.election_key(poll_id, &metadata).await
+    }
+
+    /// An encrypted poll's election key: the trustees' joint key once their ceremony is
+    /// complete, or else a key the server holds.
+    async fn election_key(&self, poll_id: &str, metadata: &PollInput) -> Result<PublicKey, VotingError> {
+        match &metadata.settings.trustees {
+            Some(config) => self.ceremony(poll_id, config).await?.election_key.ok_or_else(|| VotingError::ValidationError(
+                format!("The key ceremony for poll {} is not complete", poll_id)
+            )),
+            None => Ok(self.vote_service.get_encryption_key(poll_id).await?.public),
+        }
+    }
+
+    /// Encrypt a voter's plaintext vote as a ballot of an encrypted poll. The voter notes the
+    /// tracking code and then either casts the ballot or challenges it to check that it
+    /// encrypts their choice; a ballot cannot be both.
+    pub async fn prepare_ballot(&self, poll_id: &str, voter_id: &str, vote_data: &Value) -> Result<PreparedBallot, VotingError> {
+        let metadata = self.encrypted_poll(poll_id).await?;
+        Self::ensure_open(poll_id, &metadata)?;
+        if metadata.settings.anonymous_credentials {
+            return Err(VotingError::ValidationError(format!(
+                "Poll {} only accepts anonymous ballots cast with a credential", poll_id
+            )));
+        }
+        let key = self.election_key(poll_id, &metadata).await?;
+        let (prepared, opening) = challenge::prepare(&metadata, &key, vote_data).map_err(VotingError::ValidationError)?;
+        self.vote_service.record_prepared_ballot(poll_id, voter_id, &prepared, &opening).await?;
+        Ok(prepared)
+    }
+
+    /// Cast a ballot prepared for the voter. Their other prepared ballots are discarded. Returns
+    /// the cast ballot's tracker code.
+    pub async fn cast_prepared_ballot(&self, poll_id: &str, voter_id: &str, tracking_code: &str) -> Result<BallotReceipt, VotingError> {
+        let ballot = self.vote_service.get_prepared_ballot(poll_id, voter_id, tracking_code).await?
+            .ok_or_else(|| VotingError::ValidationError(format!("Voter {} has no prepared ballot {}", voter_id, tracking_code)))?;
+        let cast = self.cast_vote(poll_id, voter_id, ballot).await?;
+        self.vote_service.delete_prepared_ballots(poll_id, voter_id).await?;
+        Ok(cast)
+    }
+
+    /// Challenge a ballot prepared for the voter instead of casting it: the ballot is spoiled
+    /// and published on the chain with the randomness behind its ciphertexts, so the voter, or
+    /// anyone, can re-encrypt it and check it encoded their choice.
+    pub async fn challenge_ballot(&self, poll_id: &str, voter_id: &str, tracking_code: &str) -> Result<ChallengedBallot, VotingError> {
+        let metadata = self.encrypted_poll(poll_id).await?;
+        Self::ensure_open(poll_id, &metadata)?;
+        let (ballot, opening) = self.vote_service.take_prepared_ballot(poll_id, voter_id, tracking_code).await?
+            .ok_or_else(|| VotingError::ValidationError(format!("Voter {} has no prepared ballot {}", voter_id, tracking_code)))?;
+        let challenged = ChallengedBallot { tracking_code: tracking_code.to_string(), ballot, opening };
+
+        let mut transaction = json!(challenged);
+        if let Some(obj) = transaction.as_object_mut() {
+            obj.insert(RECORD_TYPE_FIELD.to_string(), json!(CHALLENGED_BALLOT_RECORD));
+        }
+        let position = {
+            let mut pm = self.poll_manager.lock().await;
+            pm.add_vote(poll_id, transaction.clone())
+                .map_err(|e| VotingError::PollManagerError(e.to_string()))?
+        };
+        self.vote_service.record_chain_record(poll_id, &transaction, position).await?;
+        Ok(challenged)
+    }
+
+    /// The running homomorphic aggregate of an encrypted poll's current ballots, which anyone
+    /// can recompute from the ciphertexts on the chain, with the decrypted tally once there is one.
+    pub async fn get_encrypted_tally(&self, poll_id: &str) -> Result<Value, VotingError> {
+        let metadata = self.encrypted_poll(poll_id).await?;
+        let public_key = self.election_key(poll_id, &metadata).await.ok();
+        let pm = self.poll_manager.lock().await;
+        let poll = pm.get_poll(poll_id).ok_or_else(|| VotingError::PollNotFound(poll_id.to_string()))?;
+        let (_, tally, decrypted) = Self::encrypted_state(poll)?;
+        Ok(json!({
+            "poll_id": poll_id,
+            "public_key": public_key,
+            "ballots": tally.ballots,
+            "aggregates": tally.aggregates,
+            "decrypted": decrypted
+        }))
+    }
+
+    /// Decrypt an encrypted poll's aggregate tally once the poll has closed. The decrypted
+    /// counts are recorded on the chain with the aggregates they came from; a tally is only
+    /// ever decrypted once.
+    pub async fn decrypt_tally(&self, poll_id: &str) -> Result<DecryptedTally, VotingError> {
+        let metadata = self.encrypted_poll(poll_id).await?;
+        Self::ensure_closed(poll_id, &metadata)?;
+        // Polls held by trustees combine a threshold of their proven partial decryptions;
+        // other polls are decrypted with the key the server holds
+        let (key, partials) = match &metadata.settings.trustees {
+            Some(_) => (None, self.vote_service.get_partial_decryptions(poll_id).await?),
+            None => (Some(self.vote_service.get_encryption_key(poll_id).await?), BTreeMap::new()),
+        };
+        let (decrypted, transaction, position) = {
+            let mut pm = self.poll_manager.lock().await;
+            let poll = pm.get_poll(poll_id).ok_or_else(|| VotingError::PollNotFound(poll_id.to_string()))?;
+            let (_, tally, previous) = Self::encrypted_state(poll)?;
+            if previous.is_some() {
+                return Err(VotingError::ValidationError(format!("The tally of poll {} has already been decrypted", poll_id)));
+            }
+
+            let cooperating: Vec<(u64, &PartialTally)> = match &metadata.settings.trustees {
+                Some(config) => {
+                    if partials.len() < config.threshold as usize {
+                        return Err(VotingError::ValidationError(format!(
+                            "{} of the {} partial decryptions needed for poll {} have been submitted",
+                            partials.len(), config.threshold, poll_id
+                        )));
+                    }
+                    partials.iter()
+                        .filter_map(|(trustee, partial)| Some((config.index_of(trustee)?, partial)))
+                        .take(config.threshold as usize)
+                        .collect()
+                },
+                None => Vec::new(),
+            };
+
+            let mut counts: BTreeMap<String, BTreeMap<String, u64>> = BTreeMap::new();
+            for (contest, aggregates) in &tally.aggregates {
+                for (option, aggregate) in aggregates {
+                    let factor = match &key {
+                        Some(key) => key.decryption_factor(aggregate),
+                        None => {
+                            // Partial decryptions were checked against these aggregates when
+                            // submitted, after the poll closed
+                            let factors: Option<Vec<(u64, &BigUint)>> = cooperating.iter()
+                                .map(|(index, partial)| Some((*index, &partial.get(contest)?.get(option)?.factor)))
+                                .collect();
+                            let factors = factors.ok_or_else(|| VotingError::ValidationError(format!(
+                                "A partial decryption is missing '{}' in contest '{}'", option, contest
+                            )))?;
+                            trustee::combine_factors(&factors)
+                        },
+                    };
+                    let count = aggregate.plaintext_with(&factor, tally.ballots).ok_or_else(|| VotingError::BlockchainError(format!(
+                        "The aggregate for '{}' in contest '{}' is not a count of at most {} ballots", option, contest, tally.ballots
+                    )))?;
+                    counts.entry(contest.clone()).or_default().insert(option.clone(), count);
+                }
+            }
+            let decrypted = DecryptedTally {
+                ballots: tally.ballots,
+                aggregates: tally.aggregates,
+                counts,
+                partial_decryptions: partials.clone(),
+            };
+
+            let mut transaction = json!(decrypted);
+            if let Some(obj) = transaction.as_object_mut() {
+                obj.insert(RECORD_TYPE_FIELD.to_string(), json!(DECRYPTED_TALLY_RECORD));
+            }
+            let position = pm.add_vote(poll_id, transaction.clone())
+                .map_err(|e| VotingError::PollManagerError(e.to_string()))?;
+            (decrypted, transaction, position)
+        };
+
+        self.vote_service.record_chain_record(poll_id, &transaction, position).await?;
+        Ok(decrypted)
+    }
+
+    /// Progress of an encrypted poll's key ceremony and decryption by its trustees.
+    pub async fn get_ceremony(&self, poll_id: &str) -> Result<Ceremony, VotingError> {
+        let metadata = self.encrypted_poll(poll_id).await?;
+        let config = Self::trustee_config(poll_id, &metadata)?;
+        self.ceremony(poll_id, config).await
+    }
+
+    /// Publish a trustee's commitments, opening its part in the key ceremony.
+    pub async fn publish_trustee_commitments(&self, poll_id: &str, trustee: &str, commitments: &TrusteeCommitments) -> Result<Ceremony, VotingError> {
+        let metadata = self.encrypted_poll(poll_id).await?;
+        let config = Self::trustee_config(poll_id, &metadata)?;
+        Self::trustee_index(poll_id, config, trustee)?;
+        commitments.verify(config.threshold).map_err(VotingError::ValidationError)?;
+        self.vote_service.record_trustee_commitments(poll_id, trustee, commitments).await?;
+        self.ceremony(poll_id, config).await
+    }
+
+    /// Relay a trustee's encrypted shares to every other trustee once all have committed.
+    /// The server cannot read the shares.
+    pub async fn distribute_trustee_shares(&self, poll_id: &str, trustee: &str, shares: &BTreeMap<String, EncryptedShare>) -> Result<Ceremony, VotingError> {
+        let metadata = self.encrypted_poll(poll_id).await?;
+        let config = Self::trustee_config(poll_id, &metadata)?;
+        Self::trustee_index(poll_id, config, trustee)?;
+        let ceremony = self.ceremony(poll_id, config).await?;
+        if ceremony.trustees.iter().any(|t| t.commitments.is_none()) {
+            return Err(VotingError::ValidationError("Shares can be sent once every trustee has published commitments".to_string()));
+        }
+        let recipients: Vec<&String> = config.trustees.iter().filter(|t| *t != trustee).collect();
+        if shares.len() != recipients.len() || recipients.iter().any(|recipient| !shares.contains_key(*recipient)) {
+            return Err(VotingError::ValidationError("A trustee must send one share to every other trustee".to_string()));
+        }
+        let group = elgamal::group();
+        if shares.values().any(|share| !group.is_element(&share.ephemeral) || share.masked >= group.q) {
+            return Err(VotingError::ValidationError("Encrypted shares must be group elements".to_string()));
+        }
+        self.vote_service.record_trustee_shares(poll_id, trustee, shares).await?;
+        self.ceremony(poll_id, config).await
+    }
+
+    /// The encrypted shares sent to a trustee, keyed by sender.
+    pub async fn get_trustee_shares(&self, poll_id: &str, trustee: &str) -> Result<BTreeMap<String, EncryptedShare>, VotingError> {
+        let metadata = self.encrypted_poll(poll_id).await?;
+        let config = Self::trustee_config(poll_id, &metadata)?;
+        Self::trustee_index(poll_id, config, trustee)?;
+        Ok(self.vote_service.get_trustee_shares(poll_id, trustee).await?)
+    }
+
+    /// Accept a trustee's partial decryption of a closed poll's aggregates after checking the
+    /// proof behind every factor against the trustee's verification key.
+    pub async fn submit_partial_decryption(&self, poll_id: &str, trustee: &str, partial: &PartialTally) -> Result<Ceremony, VotingError> {
+        let metadata = self.encrypted_poll(poll_id).await?;
+        let config = Self::trustee_config(poll_id, &metadata)?;
+        let index = Self::trustee_index(poll_id, config, trustee)?;
+        Self::ensure_closed(poll_id, &metadata)?;
+        let ceremony = self.ceremony(poll_id, config).await?;
+        let verification_key = ceremony.trustee(index)
+            .and_then(|t| t.verification_key.clone())
+            .filter(|_| ceremony.election_key.is_some())
+            .ok_or_else(|| VotingError::ValidationError(format!("The key ceremony for poll {} is not complete", poll_id)))?;
+
+        let tally = {
+            let pm = self.poll_manager.lock().await;
+            let poll = pm.get_poll(poll_id).ok_or_else(|| VotingError::PollNotFound(poll_id.to_string()))?;
+            let (_, tally, decrypted) = Self::encrypted_state(poll)?;
+            if decrypted.is_some() {
+                return Err(VotingError::ValidationError(format!("The tally of poll {} has already been decrypted", poll_id)));
+            }
+            tally
+        };
+        if partial.len() != tally.aggregates.len() {
+            return Err(VotingError::ValidationError("A partial decryption must cover every contest".to_string()));
+        }
+        for (contest, aggregates) in &tally.aggregates {
+            let factors = partial.get(contest).filter(|factors| factors.len() == aggregates.len());
+            for (option, aggregate) in aggregates {
+                let valid = factors.and_then(|factors| factors.get(option))
+                    .is_some_and(|factor| trustee::verify_partial(&verification_key, aggregate, factor));
+                if !valid {
+                    return Err(VotingError::ValidationError(format!(
+                        "Partial decryption of '{}' in contest '{}' is missing or its proof is invalid", option, contest
+                    )));
+                }
+            }
+        }
+        self.vote_service.record_partial_decryption(poll_id, trustee, partial).await?;
+        self.ceremony(poll_id, config).await
+    }
+
+    /// Shuffle a closed mixed poll's encrypted ballots once more: re-encrypt and secretly permute
+    /// the cast ballots, or the previous pass's output, and record the result on the chain with
+    /// a proof that anyone can check. The server runs every pass, so the proof only shows that
+    /// each shuffle kept exactly the same ballots; it does not keep the permutation from the
+    /// server itself.
+    pub async fn mix_ballots(&self, poll_id: &str) -> Result<ShuffleRecord, VotingError> {
+        let metadata = self.mixed_poll(poll_id).await?;
+        Self::ensure_closed(poll_id, &metadata)?;
+        let key = self.election_key(poll_id, &metadata).await?;
+        if !self.vote_service.get_mix_decryptions(poll_id).await?.is_empty() {
+            return Err(VotingError::ValidationError(format!("The trustees have begun decrypting the mix of poll {}", poll_id)));
+        }
+        let (record, transaction, position) = {
+            let mut pm = self.poll_manager.lock().await;
+            let Some(Poll::Election { blockchain, .. }) = pm.get_poll(poll_id) else {
+                return Err(VotingError::PollNotFound(poll_id.to_string()));
+            };
+            let input = match mixnet::mixed_ballots(blockchain) {
+                Some(ballots) => ballots,
+                None => mixnet::cast_ballots(blockchain).map_err(VotingError::BlockchainError)?,
+            };
+            let pass = mixnet::shuffles(blockchain).len() as u32;
+            let (ballots, proof) = mixnet::shuffle(&key, &input).map_err(VotingError::ValidationError)?;
+            let record = ShuffleRecord { pass, ballots, proof };
+
+            let mut transaction = json!(record);
+            if let Some(obj) = transaction.as_object_mut() {
+                obj.insert(RECORD_TYPE_FIELD.to_string(), json!(SHUFFLE_RECORD));
+            }
+            let position = pm.add_vote(poll_id, transaction.clone())
+                .map_err(|e| VotingError::PollManagerError(e.to_string()))?;
+            (record, transaction, position)
+        };
+
+        self.vote_service.record_chain_record(poll_id, &transaction, position).await?;
+        Ok(record)
+    }
+
+    /// The shuffles of a mixed poll and its decrypted ballots, once there are any.
+    pub async fn get_mix(&self, poll_id: &str) -> Result<Value, VotingError> {
+        self.mixed_poll(poll_id).await?;
+        let pm = self.poll_manager.lock().await;
+        let Some(Poll::Election { blockchain, .. }) = pm.get_poll(poll_id) else {
+            return Err(VotingError::PollNotFound(poll_id.to_string()));
+        };
+        let cast = mixnet::cast_ballots(blockchain).map_err(VotingError::BlockchainError)?;
+        Ok(json!({
+            "poll_id": poll_id,
+            "ballots": cast.len(),
+            "shuffles": mixnet::shuffles(blockchain),
+            "decrypted": mixnet::decryption(blockchain)
+        }))
+    }
+
+    /// Accept a trustee's partial decryption of every ballot in a mixed poll's final mix after
+    /// checking the proof behind every factor against the trustee's verification key.
+    pub async fn submit_mix_decryption(&self, poll_id: &str, trustee: &str, partial: &PartialMix) -> Result<Ceremony, VotingError> {
+        let metadata = self.mixed_poll(poll_id).await?;
+        let config = Self::trustee_config(poll_id, &metadata)?;
+        let index = Self::trustee_index(poll_id, config, trustee)?;
+        Self::ensure_closed(poll_id, &metadata)?;
+        let ceremony = self.ceremony(poll_id, config).await?;
+        let verification_key = ceremony.trustee(index)
+            .and_then(|t| t.verification_key.clone())
+            .filter(|_| ceremony.election_key.is_some())
+            .ok_or_else(|| VotingError::ValidationError(format!("The key ceremony for poll {} is not complete", poll_id)))?;
+
+        let ballots = {
+            let pm = self.poll_manager.lock().await;
+            let Some(Poll::Election { blockchain, .. }) = pm.get_poll(poll_id) else {
+                return Err(VotingError::PollNotFound(poll_id.to_string()));
+            };
+            if mixnet::decryption(blockchain).is_some() {
+                return Err(VotingError::ValidationError(format!("The ballots of poll {} have already been decrypted", poll_id)));
+            }
+            mixnet::mixed_ballots(blockchain)
+                .ok_or_else(|| VotingError::ValidationError(format!("The ballots of poll {} have not been mixed yet", poll_id)))?
+        };
+        if partial.len() != ballots.len() {
+            return Err(VotingError::ValidationError("A partial decryption must cover every mixed ballot".to_string()));
+        }
+        for (position, (ballot, factors)) in ballots.iter().zip(partial).enumerate() {
+            let valid = factors.len() == ballot.len()
+                && ballot.iter().zip(factors).all(|(ciphertext, factor)| trustee::verify_partial(&verification_key, ciphertext, factor));
+            if !valid {
+                return Err(VotingError::ValidationError(format!(
+                    "Partial decryption of mixed ballot {} is incomplete or its proof is invalid", position
+                )));
+            }
+        }
+        self.vote_service.record_mix_decryption(poll_id, trustee, partial).await?;
+        self.ceremony(poll_id, config).await
+    }
+
+    /// Decrypt each ballot of a mixed poll's final mix by combining a threshold of the trustees'
+    /// partial decryptions. Ballots are checked against the poll's rules once readable; those
+    /// that break them are counted as spoiled. The decrypted ballots are recorded on the chain,
+    /// in mixed order and without voter identities, and the poll's results are counted from them.
+    pub async fn decrypt_mix(&self, poll_id: &str) -> Result<MixDecryption, VotingError> {
+        let metadata = self.mixed_poll(poll_id).await?;
+        let config = Self::trustee_config(poll_id, &metadata)?;
+        Self::ensure_closed(poll_id, &metadata)?;
+        let partials = self.vote_service.get_mix_decryptions(poll_id).await?;
+        if partials.len() < config.threshold as usize {
+            return Err(VotingError::ValidationError(format!(
+                "{} of the {} partial decryptions needed for poll {} have been submitted",
+                partials.len(), config.threshold, poll_id
+            )));
+        }
+        let cooperating = mixnet::cooperating(config, &partials);
+
+        let (decrypted, transaction, position) = {
+            let mut pm = self.poll_manager.lock().await;
+            let Some(Poll::Election { blockchain, .. }) = pm.get_poll(poll_id) else {
+                return Err(VotingError::PollNotFound(poll_id.to_string()));
+            };
+            if mixnet::decryption(blockchain).is_some() {
+                return Err(VotingError::ValidationError(format!("The ballots of poll {} have already been decrypted", poll_id)));
+            }
+            let mixed = mixnet::mixed_ballots(blockchain)
+                .ok_or_else(|| VotingError::ValidationError(format!("The ballots of poll {} have not been mixed yet", poll_id)))?;
+
+            let mut ballots = Vec::with_capacity(mixed.len());
+            for (position, ballot) in mixed.iter().enumerate() {
+                // Partial decryptions were checked against this mix when submitted
+                let vote_data = mixnet::decrypt_ballot(ballot, position, &cooperating).ok_or_else(|| VotingError::BlockchainError(format!(
+                    "Mixed ballot {} could not be decrypted from the partial decryptions", position
+                )))?;
+                ballots.push(Self::counted_mixed_ballot(&metadata, &vote_data));
             }
+            let decrypted = MixDecryption { ballots, partial_decryptions: partials.clone() };
+
+            let mut transaction = json!(decrypted);
+            if let Some(obj) = transaction.as_object_mut() {
+                obj.insert(RECORD_TYPE_FIELD.to_string(), json!(MIX_DECRYPTION_RECORD));
+            }
+            let position = pm.add_vote(poll_id, transaction.clone())
+                .map_err(|e| VotingError::PollManagerError(e.to_string()))?;
+            (decrypted, transaction, position)
+        };
+
+        self.vote_service.record_chain_record(poll_id, &transaction, position).await?;
+        Ok(decrypted)
+    }
+
+    /// The ballot recorded for a decrypted mixed ballot's selections. They are checked against
+    /// the poll's rules now that they are readable, and counted as spoiled if they break them.
+    pub fn counted_mixed_ballot(metadata: &PollInput, vote_data: &Value) -> Value {
+        let poll_type = metadata.poll_type.as_deref().unwrap_or("election");
+        let mut status = BallotStatus::classify(vote_data);
+        let annotations = if status == BallotStatus::Valid {
+            Self::validate_ballot(poll_type, metadata, vote_data, None, None).unwrap_or_else(|_| {
+                status = BallotStatus::Spoiled;
+                Map::new()
+            })
+        } else {
+            Map::new()
+        };
+        Self::process_ballot(poll_type, status, vote_data, annotations, None)
+    }
+
+    /// Builds the public state of a poll's key ceremony from what the trustees have submitted.
+    async fn ceremony(&self, poll_id: &str, config: &TrusteeConfig) -> Result<Ceremony, VotingError> {
+        let mut commitments = self.vote_service.get_trustee_commitments(poll_id).await?;
+        let senders = self.vote_service.get_share_senders(poll_id).await?;
+        let partials = self.vote_service.get_partial_decryptions(poll_id).await?;
+        let mix_partials = self.vote_service.get_mix_decryptions(poll_id).await?;
+        let mut ceremony = Ceremony {
+            poll_id: poll_id.to_string(),
+            threshold: config.threshold,
+            trustees: config.trustees.iter()
+                .enumerate()
+                .map(|(position, trustee)| CeremonyTrustee {
+                    trustee: trustee.clone(),
+                    index: position as u64 + 1,
+                    commitments: commitments.remove(trustee),
+                    shares_distributed: senders.contains(trustee),
+                    verification_key: None,
+                    decrypted: partials.contains_key(trustee) || mix_partials.contains_key(trustee),
+                })
+                .collect(),
+            election_key: None,
+        };
+        ceremony.derive_keys();
+        Ok(ceremony)
+    }
+
+    /// The trustees of an encrypted poll held by trustees.
+    fn trustee_config<'a>(poll_id: &str, metadata: &'a PollInput) -> Result<&'a TrusteeConfig, VotingError> {
+        metadata.settings.trustees.as_ref()
+            .ok_or_else(|| VotingError::ValidationError(format!("Poll {} has no trustees", poll_id)))
+    }
+
+    /// A trustee's index in a poll's ceremony.
+    fn trustee_index(poll_id: &str, config: &TrusteeConfig, trustee: &str) -> Result<u64, VotingError> {
+        config.index_of(trustee)
+            .ok_or_else(|| VotingError::ValidationError(format!("{} is not a trustee of poll {}", trustee, poll_id)))
+    }
+
+    /// Rejects tally decryption before a poll has closed.
+    fn ensure_closed(poll_id: &str, metadata: &PollInput) -> Result<(), VotingError> {
+        if metadata.settings.closes_at.is_some_and(|closes_at| Utc::now() <= closes_at) {
+            return Err(VotingError::ValidationError(format!("Poll {} has not closed yet", poll_id)));
         }
+        Ok(())
+    }
+
+    /// The definition of a poll whose ballots are encrypted.
+    async fn encrypted_poll(&self, poll_id: &str) -> Result<PollInput, VotingError> {
+        let pm = self.poll_manager.lock().await;
+        match pm.get_poll(poll_id) {
+            Some(poll) if poll.metadata().settings.encrypted => Ok(poll.metadata().clone()),
+            Some(_) => Err(VotingError::ValidationError(format!("Poll {} is not encrypted", poll_id))),
+            None => Err(VotingError::PollNotFound(poll_id.to_string())),
+        }
+    }
+
+    /// The definition of an encrypted election whose ballots are mixed and decrypted individually.
+    async fn mixed_poll(&self, poll_id: &str) -> Result<PollInput, VotingError> {
+        let metadata = self.encrypted_poll(poll_id).await?;
+        if !metadata.settings.mixnet {
+            return Err(VotingError::ValidationError(format!("Poll {} does not mix its ballots", poll_id)));
+        }
+        Ok(metadata)
+    }
+
+    /// An encrypted poll's ballot layout, the aggregate of its current ballots and its
+    /// decrypted tally, if there is one.
+    fn encrypted_state(poll: &Poll) -> Result<(EncryptedLayout, EncryptedTally, Option<DecryptedTally>), VotingError> {
+        if poll.metadata().settings.mixnet {
+            return Err(VotingError::ValidationError(
+                "Mixed polls decrypt their ballots individually, not as a tally".to_string()
+            ));
+        }
+        let layout = EncryptedLayout::for_poll(poll.metadata()).map_err(VotingError::ValidationError)?;
+        let (tally, decrypted) = match poll {
+            Poll::Election { blockchain, .. } => (
+                layout.aggregate(blockchain.ballots().map(|block| &block.transactions)),
+                blockchain.records(DECRYPTED_TALLY_RECORD).last().cloned(),
+            ),
+            Poll::Normal { blockchain, .. } => (
+                layout.aggregate(blockchain.ballots().map(|(_, transaction)| transaction)),
+                blockchain.records(DECRYPTED_TALLY_RECORD).last().cloned(),
+            ),
+        };
+        let decrypted = decrypted.and_then(|record| serde_json::from_value(record).ok());
+        Ok((layout, tally, decrypted))
+    }
+
+    /// Import voter weights for a weighted poll.
+    pub async fn import_voter_weights(&self, poll_id: &str, weights: &[VoterWeight]) -> Result<u64, VotingError> {
+        {
+            let pm = self.poll_manager.lock().await;
+            match pm.get_poll(poll_id) {
+                Some(Poll::Normal { metadata, .. }) if metadata.poll_type.as_deref() == Some("weighted") => {},
+                Some(_) => return Err(VotingError::ValidationError(format!("Poll {} is not a weighted poll", poll_id))),
+                None => return Err(VotingError::PollNotFound(poll_id.to_string())),
+            }
+        }
+        Ok(self.vote_service.import_voter_weights(poll_id, weights).await?)
+    }
+
+    /// Record an official's resolution of a write-in in an election contest. The resolution is
+    /// either a candidate eligible in the contest or `"invalid"`; a later adjudication of the
+    /// same write-in replaces an earlier one.
+    pub async fn adjudicate_write_in(&self, poll_id: &str, adjudication: &WriteInAdjudication) -> Result<Value, VotingError> {
+        let WriteInAdjudication { contest: contest_name, resolution, official, .. } = adjudication;
+        let write_in = normalize_write_in(&adjudication.write_in).map_err(VotingError::ValidationError)?;
+        let (record, position) = {
+            let mut pm = self.poll_manager.lock().await;
+            let Some(Poll::Election { metadata, blockchain }) = pm.get_poll(poll_id) else {
+                return Err(match pm.get_poll(poll_id) {
+                    Some(_) => VotingError::ValidationError(format!("Poll {} is not an election", poll_id)),
+                    None => VotingError::PollNotFound(poll_id.to_string()),
+                });
+            };
+            let contests = parse_contests(&metadata.options).map_err(VotingError::ValidationError)?;
+            let contest = contests.iter()
+                .find(|c| &c.name == contest_name && c.write_in_slots > 0)
+                .ok_or_else(|| VotingError::ValidationError(
+                    format!("Contest '{}' does not accept write-ins", contest_name)
+                ))?;
+            if resolution != INVALID_WRITE_IN && !contest.eligible_candidates().iter().any(|c| c == resolution) {
+                return Err(VotingError::ValidationError(
+                    format!("'{}' is not an eligible candidate in contest '{}'", resolution, contest_name)
+                ));
+            }
+            // Write-ins in a mixed election are only readable once its ballots are decrypted
+            let counted = if metadata.settings.mixnet { mixnet::counted_chain(blockchain) } else { None };
+            let cast = counted.as_ref().unwrap_or(blockchain).get_write_in_report()
+                .get(contest_name)
+                .is_some_and(|write_ins| write_ins.contains_key(&write_in));
+            if !cast {
+                return Err(VotingError::ValidationError(
+                    format!("No ballot contains the write-in '{}' in contest '{}'", write_in, contest_name)
+                ));
+            }
+
+            let record = json!({
+                RECORD_TYPE_FIELD: ADJUDICATION_RECORD,
+                "contest": contest_name,
+                "write_in": write_in,
+                "resolution": resolution,
+                "official": official
+            });
+            let position = pm.add_vote(poll_id, record.clone())
+                .map_err(|e| VotingError::PollManagerError(e.to_string()))?;
+            (record, position)
+        };
+
+        self.vote_service.record_chain_record(poll_id, &record, position).await?;
+        Ok(record)
+    }
+
+    /// Record the input that settles a tie: lots drawn from the chain's final block hash, or an
+    /// official's ranking, depending on the poll's tie-break policy. The record is appended to
+    /// the chain so the outcome can be reproduced from it.
+    pub async fn record_tie_break(&self, poll_id: &str, request: &TieBreakRequest) -> Result<TieBreakRecord, VotingError> {
+        let (record, transaction, position) = {
+            let mut pm = self.poll_manager.lock().await;
+            let (metadata, seed) = match pm.get_poll(poll_id) {
+                Some(Poll::Election { metadata, blockchain }) => (metadata, blockchain.chain.last().map(|b| b.hash.clone())),
+                Some(Poll::Normal { metadata, blockchain }) => (metadata, blockchain.chain.last().map(|b| b.hash.clone())),
+                None => return Err(VotingError::PollNotFound(poll_id.to_string())),
+            };
+
+            let candidates = match (&request.contest, metadata.poll_type.as_deref()) {
+                (Some(contest_name), Some("election")) => {
+                    let contests = parse_contests(&metadata.options).map_err(VotingError::ValidationError)?;
+                    contests.iter()
+                        .find(|c| &c.name == contest_name)
+                        .map(|c| c.eligible_candidates())
+                        .ok_or_else(|| VotingError::ValidationError(format!("Contest '{}' does not exist", contest_name)))?
+                },
+                (None, Some("election")) => {
+                    return Err(VotingError::ValidationError("Election tie-breaks must name a contest".to_string()));
+                },
+                (Some(_), _) => {
+                    return Err(VotingError::ValidationError(format!("Poll {} has no contests", poll_id)));
+                },
+                (None, _) => metadata.options.clone(),
+            };
+            if let Some(unknown) = request.tied.iter().find(|name| !candidates.contains(name)) {
+                return Err(VotingError::ValidationError(format!("'{}' is not a candidate in this poll", unknown)));
+            }
+
+            let record = tie_break::build_record(
+                metadata.settings.tie_break.unwrap_or_default(),
+                request.contest.clone(),
+                &request.tied,
+                request.ranking.clone(),
+                &seed.unwrap_or_default(),
+                &request.official,
+            ).map_err(VotingError::ValidationError)?;
+
+            let mut transaction = json!(record);
+            if let Some(obj) = transaction.as_object_mut() {
+                obj.insert(RECORD_TYPE_FIELD.to_string(), json!(TIE_BREAK_RECORD));
+            }
+            let position = pm.add_vote(poll_id, transaction.clone())
+                .map_err(|e| VotingError::PollManagerError(e.to_string()))?;
+            (record, transaction, position)
+        };
+
+        self.vote_service.record_chain_record(poll_id, &transaction, position).await?;
+        Ok(record)
     }
 
     /// Get poll results from both blockchain and database.
     pub async fn get_poll_results(&self, poll_id: &str) -> Result<Value, VotingError> {
+        let mut extra_results = Map::new();
+
+        // Outcome rules may need the size of the electorate, which lives in the database
+        let settings = {
+            let pm = self.poll_manager.lock().await;
+            pm.get_poll(poll_id).map(|poll| poll.metadata().settings.clone()).unwrap_or_default()
+        };
+        let rules = settings.rules.clone();
+        let members = match &rules {
+            Some(rules) if rules.members.is_none() => self.vote_service.count_eligible_voters(poll_id).await?,
+            _ => 0,
+        };
+
         let blockchain_counts_json = {
             let pm = self.poll_manager.lock().await;
+            // Mixed elections are counted from their individually decrypted ballots
+            let counted = match pm.get_poll(poll_id) {
+                Some(Poll::Election { metadata, blockchain }) if metadata.settings.mixnet => mixnet::counted_chain(blockchain),
+                _ => None,
+            };
             match pm.get_poll(poll_id) {
-                Some(Poll::Election { blockchain, .. }) => {
-                    json!(blockchain.get_vote_counts())
+                // Encrypted ballots are unreadable, so nothing is counted from them: a mixed
+                // election is counted once its ballots are decrypted, and other encrypted polls
+                // report only their decrypted tally, below
+                Some(poll) if poll.metadata().settings.encrypted && counted.is_none() => json!({}),
+                Some(Poll::Election { metadata, blockchain }) => {
+                    let blockchain = counted.as_ref().unwrap_or(blockchain);
+                    let contests = parse_contests(&metadata.options).unwrap_or_default();
+                    let policy = metadata.settings.tie_break.unwrap_or_default();
+                    let tie_breaks = blockchain.get_tie_break_records();
+                    extra_results.insert("stv_results".to_string(), json!(blockchain.get_stv_results_with_tie_break(&contests, policy)));
+                    extra_results.insert("write_ins".to_string(), json!(blockchain.get_write_in_report()));
+                    let electoral_results = blockchain.get_electoral_results(&contests);
+                    if !electoral_results.is_empty() {
+                        extra_results.insert("electoral_results".to_string(), json!(electoral_results));
+                    }
+                    extra_results.insert("turnout".to_string(), json!(blockchain.get_turnout()));
+                    let contest_turnout = blockchain.get_contest_turnout(&contests);
+                    let counts = blockchain.get_vote_counts();
+                    if let Some(rules) = &rules {
+                        let outcomes: BTreeMap<String, Outcome> = contests.iter()
+                            .filter(|contest| contest.method == ContestMethod::Plurality)
+                            .map(|contest| {
+                                let mut tally: BTreeMap<String, u64> = contest.eligible_candidates().into_iter()
+                                    .map(|candidate| (candidate, 0))
+                                    .collect();
+                                for (candidate, votes) in counts.get(&contest.name).into_iter().flatten() {
+                                    tally.insert(candidate.clone(), *votes as u64);
+                                }
+                                let turnout = contest_turnout.get(&contest.name).cloned().unwrap_or_default();
+                                let mut outcome = outcome::decide(rules, &tally, &turnout, members);
+                                if outcome.outcome == OutcomeStatus::Tie {
+                                    let history = if policy == TieBreakPolicy::EarlierRound {
+                                        blockchain.running_tallies(&contest.name, &outcome.leading_options)
+                                    } else {
+                                        Vec::new()
+                                    };
+                                    let tie_breaker = TieBreaker::new(policy, Some(&contest.name), &tie_breaks);
+                                    outcome.break_tie(tie_breaker.resolve(&outcome.leading_options, &history));
+                                }
+                                (contest.name.clone(), outcome)
+                            })
+                            .collect();
+                        extra_results.insert("contest_outcomes".to_string(), json!(outcomes));
+                    }
+                    extra_results.insert("contest_turnout".to_string(), json!(contest_turnout));
+                    json!(counts)
                 },
-                Some(Poll::Normal { blockchain, .. }) => {
-                    json!(blockchain.get_vote_counts())
+                Some(Poll::Normal { metadata, blockchain }) => {
+                    let turnout = blockchain.get_turnout();
+                    let counts = blockchain.get_vote_counts();
+                    // Motions are decided on headcounts of the options chosen
+                    if let (Some(rules), "normal" | "weighted") = (&rules, metadata.poll_type.as_deref().unwrap_or("normal")) {
+                        let mut tally: BTreeMap<String, u64> = metadata.options.iter()
+                            .map(|option| (option.clone(), 0))
+                            .collect();
+                        for (option, votes) in &counts {
+                            tally.insert(option.clone(), Blockchain::headcount(votes));
+                        }
+                        let mut outcome = outcome::decide(rules, &tally, &turnout, members);
+                        if outcome.outcome == OutcomeStatus::Tie {
+                            let policy = metadata.settings.tie_break.unwrap_or_default();
+                            let history = if policy == TieBreakPolicy::EarlierRound {
+                                blockchain.running_tallies(&outcome.leading_options)
+                            } else {
+                                Vec::new()
+                            };
+                            let tie_breaker = TieBreaker::new(policy, None, &blockchain.get_tie_break_records());
+                            outcome.break_tie(tie_breaker.resolve(&outcome.leading_options, &history));
+                        }
+                        extra_results.insert("outcome".to_string(), json!(outcome));
+                    }
+                    extra_results.insert("turnout".to_string(), json!(turnout));
+                    match metadata.poll_type.as_deref() {
+                        Some("quadratic") => {
+                            extra_results.insert("quadratic_results".to_string(), json!(quadratic::tally(blockchain)));
+                        },
+                        Some("budgeting") => {
+                            let costs = metadata.settings.costs.clone().unwrap_or_default();
+                            let projects = budgeting::projects_from_options(&metadata.options, &costs)
+                                .map_err(VotingError::ValidationError)?;
+                            let budget = metadata.settings.budget.unwrap_or(0);
+                            extra_results.insert("budget_results".to_string(), json!(budgeting::tally(blockchain, &projects, budget)));
+                        },
+                        _ => {},
+                    }
+                    json!(counts)
                 },
                 None => return Err(VotingError::ValidationError(format!("Poll {} does not exist", poll_id))),
             }
         };
     
+        // Encrypted polls have no plaintext choices to count; their results are the decrypted
+        // tally once the poll has closed
+        let blockchain_counts_json = if settings.encrypted && !settings.mixnet {
+            let pm = self.poll_manager.lock().await;
+            let poll = pm.get_poll(poll_id).ok_or_else(|| VotingError::PollNotFound(poll_id.to_string()))?;
+            let (layout, tally, decrypted) = Self::encrypted_state(poll)?;
+            extra_results.insert("encrypted_tally".to_string(), json!(tally));
+            decrypted.map(|decrypted| layout.results(&decrypted.counts)).unwrap_or_else(|| json!({}))
+        } else {
+            blockchain_counts_json
+        };
+
         let database_counts = self.vote_service.get_vote_counts(poll_id).await?;
+        let database_turnout = self.vote_service.get_turnout(poll_id).await?;
+        if settings.demographics {
+            let ballots = self.vote_service.get_demographic_ballots(poll_id).await?;
+            let k = settings.min_cell_size.unwrap_or(DEFAULT_MIN_CELL_SIZE);
+            extra_results.insert("demographics".to_string(), json!(statistics::demographic_breakdown(&ballots, k)));
+        }
     
-        Ok(json!({
+        let mut results = json!({
             "blockchain_results": blockchain_counts_json,
             "database_results": database_counts,
+            "database_turnout": database_turnout,
             "blockchain_data_type": match &blockchain_counts_json {
                 Value::Object(map) => {
                     if map.values().any(|v| v.is_object()) {
@@ -220,6 +1492,10 @@ impl VotingIntegration {
                 },
                 _ => "unknown"
             }
-        }))
+        });
+        if let Some(obj) = results.as_object_mut() {
+            obj.extend(extra_results);
+        }
+        Ok(results)
     }
 }
\ No newline at end of file
@@ -1,10 +1,34 @@
 #[cfg(test)]
 mod tests {
     use serde_json::json;
+    use backend::ballot::{BallotStatus, Turnout, BALLOT_TAG_FIELD};
     use backend::block::Block;
     use backend::blockchain::Blockchain;
-    use backend::election_blockchain::ElectionBlockchain;
-    use backend::poll_manager::{PollManager, PollInput, Poll};
+    use backend::election_blockchain::{ElectionBlockchain, RECORD_TYPE_FIELD};
+    use backend::apportionment::{us_electoral_college, StateElectors};
+    use backend::contest::{parse_contests, ContestMethod};
+    use backend::stv::{count_stv, count_stv_with_tie_break, SurplusTransfer};
+    use backend::timeline::Bucket;
+    use std::collections::BTreeMap;
+    use backend::tie_break::{draw_lots, TieBreakPolicy, TieBreaker};
+    use backend::outcome::{decide, DecisionRules, Fraction, OutcomeStatus, Quorum, Threshold, ThresholdBase};
+    use backend::quadratic;
+    use backend::statistics::{self, DemographicBreakdown, Demographics};
+    use chrono::TimeZone;
+    use backend::budgeting;
+    use backend::credential::{self, Credential};
+    use backend::crypto;
+    use backend::elgamal::{self, Ciphertext, KeyPair};
+    use backend::encrypted_tally::{self, EncryptedLayout};
+    use backend::proofs::RangeProof;
+    use backend::mixnet;
+    use backend::pseudonym;
+    use backend::commit_reveal;
+    use backend::challenge::{self, BallotOpening};
+    use backend::tracker::{self, TrackedEntry};
+    use backend::trustee::{TrusteeConfig, TrusteeState};
+    use num_bigint_dig::prime::probably_prime;
+    use backend::poll_manager::{ChainPosition, PollManager, PollInput, Poll};
     use sqlx::postgres::PgPoolOptions;
     use sqlx::Error as SqlxError;
     use std::env;
@@ -13,8 +37,8 @@ mod tests {
 
     // Import modules from your backend.
     use backend::user::{UserLogin, UserManager, UserRegistration};
-    use backend::vote_service::{VoteService, VoteRequest};
-    use backend::voting_integration::VotingIntegration;
+    use backend::vote_service::{self, VoteService, VoteRequest, VoterWeight};
+    use backend::voting_integration::{BallotReveal, TieBreakRequest, VotingError, VotingIntegration, WriteInAdjudication};
 
     // ==============================
     // Test Setup
@@ -42,8 +66,21 @@ mod tests {
         println!("Database connection verified: {}", result.0);
         
         // First check if tables exist and drop them with IF EXISTS
+        sqlx::query("DROP TABLE IF EXISTS prepared_ballots CASCADE").execute(&pool).await?;
+        sqlx::query("DROP TABLE IF EXISTS mix_decryptions CASCADE").execute(&pool).await?;
+        sqlx::query("DROP TABLE IF EXISTS partial_decryptions CASCADE").execute(&pool).await?;
+        sqlx::query("DROP TABLE IF EXISTS trustee_shares CASCADE").execute(&pool).await?;
+        sqlx::query("DROP TABLE IF EXISTS trustee_commitments CASCADE").execute(&pool).await?;
+        sqlx::query("DROP TABLE IF EXISTS encryption_keys CASCADE").execute(&pool).await?;
+        sqlx::query("DROP TABLE IF EXISTS spent_credentials CASCADE").execute(&pool).await?;
+        sqlx::query("DROP TABLE IF EXISTS credential_issuance CASCADE").execute(&pool).await?;
+        sqlx::query("DROP TABLE IF EXISTS credential_keys CASCADE").execute(&pool).await?;
+        sqlx::query("DROP TABLE IF EXISTS pseudonym_salts CASCADE").execute(&pool).await?;
+        sqlx::query("DROP TABLE IF EXISTS chain_records CASCADE").execute(&pool).await?;
+        sqlx::query("DROP TABLE IF EXISTS voter_weights CASCADE").execute(&pool).await?;
         sqlx::query("DROP TABLE IF EXISTS blocks CASCADE").execute(&pool).await?;
-        sqlx::query("DROP TABLE IF EXISTS votes CASCADE").execute(&pool).await?;
+        sqlx::query("DROP TABLE IF EXISTS ballots CASCADE").execute(&pool).await?;
+        sqlx::query("DROP TABLE IF EXISTS participation CASCADE").execute(&pool).await?;
         sqlx::query("DROP TABLE IF EXISTS polls CASCADE").execute(&pool).await?;
         sqlx::query("DROP TABLE IF EXISTS users CASCADE").execute(&pool).await?;
         sqlx::query("DROP TABLE IF EXISTS voters CASCADE").execute(&pool).await?;
@@ -56,6 +93,7 @@ mod tests {
                 id SERIAL PRIMARY KEY,
                 voter_id TEXT UNIQUE NOT NULL,
                 name TEXT NOT NULL,
+                email TEXT UNIQUE,
                 zip_code TEXT NOT NULL,
                 birth_date DATE NOT NULL,
                 password_hash TEXT NOT NULL DEFAULT '',
@@ -86,20 +124,32 @@ mod tests {
                 options JSONB NOT NULL,
                 is_public BOOLEAN NOT NULL DEFAULT TRUE,
                 poll_type TEXT NOT NULL DEFAULT 'normal',
+                settings JSONB NOT NULL DEFAULT '{}',
                 created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
             )"
         ).execute(&pool).await?;
         
-        // Create votes table with both vote and vote_data columns
+        // Create the participation table and, apart from it, the ballot box
         sqlx::query(
-            "CREATE TABLE IF NOT EXISTS votes (
-                id SERIAL PRIMARY KEY,
+            "CREATE TABLE IF NOT EXISTS participation (
                 poll_id TEXT NOT NULL,
                 voter_id TEXT NOT NULL,
-                vote JSONB,
-                vote_data JSONB,
-                created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
-                UNIQUE(poll_id, voter_id)
+                ballots_cast INTEGER NOT NULL DEFAULT 1,
+                voted_hour TIMESTAMP WITH TIME ZONE NOT NULL,
+                PRIMARY KEY (poll_id, voter_id)
+            )"
+        ).execute(&pool).await?;
+        
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS ballots (
+                ballot_id TEXT PRIMARY KEY,
+                poll_id TEXT NOT NULL,
+                vote JSONB NOT NULL,
+                demographics JSONB,
+                superseded BOOLEAN NOT NULL DEFAULT FALSE,
+                chain_seq BIGINT NOT NULL,
+                chained_at BIGINT NOT NULL,
+                UNIQUE (poll_id, chain_seq)
             )"
         ).execute(&pool).await?;
         
@@ -119,6 +169,135 @@ mod tests {
             )"
         ).execute(&pool).await?;
         
+        // Create voter weight registry table
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS voter_weights (
+                id SERIAL PRIMARY KEY,
+                poll_id TEXT NOT NULL,
+                voter_id TEXT NOT NULL,
+                weight DOUBLE PRECISION NOT NULL CHECK (weight >= 0),
+                effective_from TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (poll_id) REFERENCES polls(poll_id),
+                FOREIGN KEY (voter_id) REFERENCES voters(voter_id)
+            )"
+        ).execute(&pool).await?;
+        
+        // Create table for non-ballot chain records
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS chain_records (
+                id SERIAL PRIMARY KEY,
+                poll_id TEXT NOT NULL,
+                record JSONB NOT NULL,
+                chain_seq BIGINT NOT NULL,
+                chained_at BIGINT NOT NULL,
+                created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+                UNIQUE (poll_id, chain_seq),
+                FOREIGN KEY (poll_id) REFERENCES polls(poll_id)
+            )"
+        ).execute(&pool).await?;
+        
+        // Create table for per-poll pseudonym salts
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS pseudonym_salts (
+                poll_id TEXT PRIMARY KEY,
+                salt TEXT NOT NULL,
+                rotated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (poll_id) REFERENCES polls(poll_id)
+            )"
+        ).execute(&pool).await?;
+        
+        // Create tables for blind-signed ballot credentials
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS credential_keys (
+                poll_id TEXT PRIMARY KEY,
+                key JSONB NOT NULL,
+                created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (poll_id) REFERENCES polls(poll_id)
+            )"
+        ).execute(&pool).await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS credential_issuance (
+                poll_id TEXT NOT NULL,
+                voter_id TEXT NOT NULL,
+                PRIMARY KEY (poll_id, voter_id)
+            )"
+        ).execute(&pool).await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS spent_credentials (
+                poll_id TEXT NOT NULL,
+                nullifier TEXT NOT NULL,
+                PRIMARY KEY (poll_id, nullifier)
+            )"
+        ).execute(&pool).await?;
+        
+        // Create table for encrypted polls' election keys
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS encryption_keys (
+                poll_id TEXT PRIMARY KEY,
+                key JSONB NOT NULL,
+                created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (poll_id) REFERENCES polls(poll_id)
+            )"
+        ).execute(&pool).await?;
+        
+        // Create tables for trustee key ceremonies and partial decryptions
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS trustee_commitments (
+                poll_id TEXT NOT NULL,
+                trustee TEXT NOT NULL,
+                commitments JSONB NOT NULL,
+                published_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+                PRIMARY KEY (poll_id, trustee),
+                FOREIGN KEY (poll_id) REFERENCES polls(poll_id)
+            )"
+        ).execute(&pool).await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS trustee_shares (
+                poll_id TEXT NOT NULL,
+                sender TEXT NOT NULL,
+                recipient TEXT NOT NULL,
+                share JSONB NOT NULL,
+                PRIMARY KEY (poll_id, sender, recipient),
+                FOREIGN KEY (poll_id) REFERENCES polls(poll_id)
+            )"
+        ).execute(&pool).await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS partial_decryptions (
+                poll_id TEXT NOT NULL,
+                trustee TEXT NOT NULL,
+                decryption JSONB NOT NULL,
+                submitted_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+                PRIMARY KEY (poll_id, trustee),
+                FOREIGN KEY (poll_id) REFERENCES polls(poll_id)
+            )"
+        ).execute(&pool).await?;
+        
+        // Create table for trustees' partial decryptions of mixed ballots
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS mix_decryptions (
+                poll_id TEXT NOT NULL,
+                trustee TEXT NOT NULL,
+                decryption JSONB NOT NULL,
+                submitted_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+                PRIMARY KEY (poll_id, trustee),
+                FOREIGN KEY (poll_id) REFERENCES polls(poll_id)
+            )"
+        ).execute(&pool).await?;
+        
+        // Create table for ballots prepared for voters to cast or challenge
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS prepared_ballots (
+                tracking_code TEXT PRIMARY KEY,
+                poll_id TEXT NOT NULL,
+                voter_id TEXT NOT NULL,
+                ballot JSONB NOT NULL,
+                opening JSONB NOT NULL,
+                prepared_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (poll_id) REFERENCES polls(poll_id)
+            )"
+        ).execute(&pool).await?;
+        
         println!("Database setup complete");
         
         Ok(pool)
@@ -142,17 +321,18 @@ mod tests {
     #[tokio::test]
     async fn test_normal_blockchain_validity_and_vote_search() {
         let mut blockchain = Blockchain::new();
-        blockchain.add_block(json!({"voter_id": "A", "candidate": "X"}));
-        blockchain.add_block(json!({"voter_id": "B", "candidate": "Y"}));
+        blockchain.add_block(json!({BALLOT_TAG_FIELD: "tag-a", "candidate": "X"}));
+        blockchain.add_block(json!({BALLOT_TAG_FIELD: "tag-b", "candidate": "Y"}));
         assert!(blockchain.is_valid(), "Blockchain should be valid after adding blocks");
 
-        // Test vote search.
-        if let Some((index, hash)) = blockchain.find_vote("A") {
-            assert!(index > 0, "Vote for 'A' should not be in the genesis block");
+        // Test ballot search.
+        if let Some((index, hash)) = blockchain.find_tagged_ballot("tag-a") {
+            assert!(index > 0, "Ballot 'tag-a' should not be in the genesis block");
             assert!(!hash.is_empty(), "Hash should be non-empty");
         } else {
-            panic!("Vote for voter 'A' not found");
+            panic!("Ballot 'tag-a' not found");
         }
+        assert!(blockchain.find_tagged_ballot("tag-c").is_none());
 
         // Test vote counts.
         let counts = blockchain.get_vote_counts();
@@ -175,9 +355,9 @@ mod tests {
         let mut election_chain = ElectionBlockchain::new();
         assert_eq!(election_chain.chain.len(), 1, "Chain should start with only the genesis block");
 
-        let vote1 = json!({"voter_id": "A", "contest": "election", "candidate": "Candidate A"});
-        let vote2 = json!({"voter_id": "B", "contest": "election", "candidate": "Candidate B"});
-        let vote3 = json!({"voter_id": "C", "contest": "election", "candidate": "Candidate A"});
+        let vote1 = json!({BALLOT_TAG_FIELD: "tag-a", "contest": "election", "candidate": "Candidate A"});
+        let vote2 = json!({BALLOT_TAG_FIELD: "tag-b", "contest": "election", "candidate": "Candidate B"});
+        let vote3 = json!({BALLOT_TAG_FIELD: "tag-c", "contest": "election", "candidate": "Candidate A"});
 
         election_chain.add_vote(vote1).unwrap();
         assert_eq!(election_chain.chain.len(), 2, "After first vote, chain length should be 2");
@@ -201,11 +381,417 @@ mod tests {
             "Candidate B should have 1 vote in election poll"
         );
 
-        if let Some((block_index, _)) = election_chain.find_vote("A") {
-            assert!(block_index > 0, "Found vote should not be in the genesis block");
+        if let Some((block_index, _)) = election_chain.find_tagged_ballot("tag-a") {
+            assert!(block_index > 0, "Found ballot should not be in the genesis block");
         } else {
-            panic!("Vote for voter 'A' should be found");
+            panic!("Ballot 'tag-a' should be found");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_weighted_vote_counts_sum_weights_and_headcounts() {
+        let mut blockchain = Blockchain::new();
+        blockchain.add_block(json!({"voter_id": "A", "choice": "Yes", "weight": 100.0}));
+        blockchain.add_block(json!({"voter_id": "B", "choice": "No", "weight": 30.0}));
+        blockchain.add_block(json!({"voter_id": "C", "choice": "No", "weight": 25.5}));
+
+        let weighted = blockchain.get_vote_counts();
+        assert_eq!(weighted.get("Yes"), Some(&json!({"weight": 100.0, "headcount": 1})));
+        assert_eq!(weighted.get("No"), Some(&json!({"weight": 55.5, "headcount": 2})));
+        assert_eq!(weighted.get("No").map(Blockchain::headcount), Some(2));
+
+        let mut unweighted = Blockchain::new();
+        unweighted.add_block(json!({"voter_id": "A", "choice": "No"}));
+        assert_eq!(unweighted.get_vote_counts().get("No"), Some(&json!(1)), "Unweighted polls count heads");
+    }
+
+    #[tokio::test]
+    async fn test_quadratic_ballot_budget_and_tally() {
+        let options = vec!["Park".to_string(), "Library".to_string(), "Road".to_string()];
+
+        // 3 votes for the park and 2 against the road cost 9 + 4 = 13 credits.
+        let ballot = json!({"allocations": {"Park": 3, "Road": -2}});
+        let allocations = quadratic::validate_ballot(&options, 13, &ballot).expect("Ballot within budget");
+        assert_eq!(quadratic::credits_for(&allocations), 13);
+        assert!(quadratic::validate_ballot(&options, 12, &ballot).is_err(), "Ballot over budget should be rejected");
+        assert!(
+            quadratic::validate_ballot(&options, 100, &json!({"allocations": {"Pool": 1}})).is_err(),
+            "Unknown options should be rejected"
+        );
+
+        let mut blockchain = Blockchain::new();
+        blockchain.add_block(json!({"voter_id": "A", "allocations": {"Park": 3, "Road": -2}}));
+        blockchain.add_block(json!({"voter_id": "B", "allocations": {"Park": -1, "Library": 2}}));
+        let tally = quadratic::tally(&blockchain);
+        assert_eq!(tally["Park"].net_votes, 2);
+        assert_eq!(tally["Park"].credits_spent, 10);
+        assert_eq!(tally["Road"].net_votes, -2);
+        assert_eq!(tally["Library"].credits_spent, 4);
+    }
+
+    #[tokio::test]
+    async fn test_budgeting_greedy_and_equal_shares() {
+        let options: Vec<String> = ["A1", "A2", "A3", "B"].iter().map(|s| s.to_string()).collect();
+        let projects = budgeting::projects_from_options(&options, &[30, 30, 30, 30]).unwrap();
+        assert!(
+            budgeting::validate_ballot(&projects, 100, &json!({"projects": ["A1", "A2", "A3", "B"]})).is_err(),
+            "Selections over budget should be rejected"
+        );
+
+        let mut blockchain = Blockchain::new();
+        for i in 0..6 {
+            blockchain.add_block(json!({"voter_id": format!("a{}", i), "projects": ["A1", "A2", "A3"]}));
+        }
+        for i in 0..4 {
+            blockchain.add_block(json!({"voter_id": format!("b{}", i), "projects": ["B"]}));
+        }
+
+        let results = budgeting::tally(&blockchain, &projects, 100);
+        assert_eq!(results.approvals["A1"], 6);
+        // Greedy lets the majority take the whole budget...
+        assert_eq!(results.greedy.funded, vec!["A1", "A2", "A3"]);
+        // ...while equal shares funds the minority's project from their share.
+        assert_eq!(results.equal_shares.funded, vec!["A1", "A2", "B"]);
+        assert_eq!(results.equal_shares.unspent, 10);
+    }
+
+    #[test]
+    fn test_elgamal_homomorphic_sum_decrypts_to_count() {
+        let group = elgamal::group();
+        assert!(probably_prime(&group.p, 10) && probably_prime(&group.q, 10), "The group is a safe-prime group");
+
+        let key = KeyPair::generate();
+        let ciphertexts: Vec<Ciphertext> = [1, 0, 1, 1].iter().map(|m| key.public.encrypt(*m)).collect();
+        assert!(ciphertexts.iter().all(Ciphertext::is_valid));
+        assert_ne!(ciphertexts[0], key.public.encrypt(1), "Encryption is randomized");
+        let sum = ciphertexts.iter().fold(Ciphertext::identity(), |total, c| total.combine(c));
+        assert_eq!(key.decrypt(&sum, 4), Some(3));
+        assert_eq!(key.decrypt(&sum, 2), None, "Counts above the bound are not found");
+    }
+
+    #[test]
+    fn test_encrypted_ballot_proofs_bound_selections() {
+        let key = KeyPair::generate().public;
+        let (two, r) = key.encrypt_with(2, &elgamal::group().random_exponent());
+        assert!(!RangeProof::prove(&key, &two, 1, &r, 1).verify(&key, &two, 1), "A ciphertext of 2 cannot be proven 0 or 1");
+        let range = RangeProof::prove(&key, &two, 2, &r, 2);
+        assert!(range.verify(&key, &two, 2));
+        assert!(!range.verify(&key, &two, 1), "A proof only covers the range it was made for");
+
+        let poll = PollInput {
+            options: vec!["Yes".to_string(), "No".to_string()],
+            ..Default::default()
+        };
+        let layout = EncryptedLayout::for_poll(&poll).expect("Layout failed");
+        let choose = |options: &[&str]| BTreeMap::from([(
+            encrypted_tally::DEFAULT_CONTEST.to_string(),
+            options.iter().map(|o| o.to_string()).collect::<Vec<_>>(),
+        )]);
+        let ballot = layout.encrypt_ballot(&key, &choose(&["Yes"])).expect("Encryption failed");
+        assert!(layout.parse_ballot(&ballot, &key).is_ok());
+        assert!(layout.parse_ballot(&ballot, &KeyPair::generate().public).is_err(), "Proofs are bound to the election key");
+        assert!(layout.encrypt_ballot(&key, &choose(&["Yes", "No"])).is_err(), "Honest clients respect the limit");
+
+        // Each selection is 0 or 1, but a ballot choosing both exceeds the poll's limit of one
+        let mut lax = layout.clone();
+        lax.selection_limits.insert(encrypted_tally::DEFAULT_CONTEST.to_string(), 2);
+        let overvote = lax.encrypt_ballot(&key, &choose(&["Yes", "No"])).expect("Encryption failed");
+        assert!(layout.parse_ballot(&overvote, &key).is_err());
+        let mut inflated = ballot.clone();
+        inflated["encrypted"]["Yes"] = json!(key.encrypt(5));
+        assert!(layout.parse_ballot(&inflated, &key).is_err(), "Proofs do not carry over to other ciphertexts");
+
+        // The offline check re-verifies every ballot on the chain
+        let mut blockchain = Blockchain::new();
+        for (voter, choice) in [("a", &["Yes"][..]), ("b", &["No"][..]), ("c", &[][..])] {
+            let mut transaction = layout.encrypt_ballot(&key, &choose(choice)).expect("Encryption failed");
+            transaction["voter_id"] = json!(voter);
+            blockchain.add_block(transaction);
+        }
+        let verified = Poll::Normal { metadata: poll.clone(), blockchain: blockchain.clone() };
+        assert_eq!(encrypted_tally::verify_chain(&verified, &key).expect("Verification failed").ballots, 3);
+        let mut transaction = inflated;
+        transaction["voter_id"] = json!("d");
+        blockchain.add_block(transaction);
+        assert!(encrypted_tally::verify_chain(&Poll::Normal { metadata: poll, blockchain }, &key).is_err());
+    }
+
+    #[test]
+    fn test_mixnet_shuffle_proof_verifies_only_re_encryptions() {
+        let key = KeyPair::generate();
+        let plaintexts = [json!({"mayor": ["Alice", "Bob"]}), json!({"mayor": [{"write_in": "carol"}]}), json!({"mayor": ["Bob"]})];
+        let ballots: Vec<mixnet::MixedBallot> = plaintexts.iter()
+            .map(|plaintext| {
+                let sealed = mixnet::seal(&key.public, plaintext).expect("Sealing failed");
+                mixnet::parse_ballot(&sealed).expect("Sealed ballot is well-formed")
+            })
+            .collect();
+        assert!(mixnet::seal(&key.public, &json!("x".repeat(1000))).is_err(), "Ballots have a fixed capacity");
+
+        let (mixed, proof) = mixnet::shuffle(&key.public, &ballots).expect("Shuffle failed");
+        assert!(proof.verify(&key.public, &ballots, &mixed));
+        assert!(mixed.iter().all(|ballot| !ballots.contains(ballot)), "Every ciphertext is re-randomized");
+        let decrypt = |ballot: &mixnet::MixedBallot| -> serde_json::Value {
+            let elements: Vec<_> = ballot.iter().map(|c| c.message_with(&key.decryption_factor(c)).unwrap()).collect();
+            serde_json::from_slice(&mixnet::decode(&elements).unwrap()).unwrap()
+        };
+        let mut decrypted: Vec<String> = mixed.iter().map(|ballot| decrypt(ballot).to_string()).collect();
+        let mut expected: Vec<String> = plaintexts.iter().map(|p| p.to_string()).collect();
+        decrypted.sort();
+        expected.sort();
+        assert_eq!(decrypted, expected, "Mixing preserves the multiset of ballots");
+
+        // Replacing a ballot, even with a valid encryption, breaks the proof
+        let mut forged = mixed.clone();
+        forged[0] = mixnet::parse_ballot(&mixnet::seal(&key.public, &json!({"mayor": ["Alice"]})).unwrap()).unwrap();
+        assert!(!proof.verify(&key.public, &ballots, &forged));
+        let mut reordered = mixed.clone();
+        reordered.swap(0, 1);
+        assert!(!proof.verify(&key.public, &ballots, &reordered));
+        assert!(!proof.verify(&KeyPair::generate().public, &ballots, &mixed));
+    }
+
+    // ==============================
+    // STV Contest Tests
+    // ==============================
+
+    fn stv_fixture() -> (Vec<String>, Vec<Vec<String>>) {
+        let candidates: Vec<String> = ["A", "B", "C", "D"].iter().map(|s| s.to_string()).collect();
+        let mut ballots = Vec::new();
+        for _ in 0..6 {
+            ballots.push(vec!["A".to_string(), "B".to_string()]);
+        }
+        ballots.push(vec!["C".to_string()]);
+        for _ in 0..3 {
+            ballots.push(vec!["D".to_string(), "C".to_string()]);
+        }
+        (candidates, ballots)
+    }
+
+    #[tokio::test]
+    async fn test_stv_gregory_transfers_surplus_and_exclusions() {
+        let (candidates, ballots) = stv_fixture();
+        let result = count_stv(&candidates, &ballots, 2, SurplusTransfer::Gregory);
+
+        assert_eq!(result.quota, 4.0, "Droop quota for 10 ballots and 2 seats should be 4");
+        assert_eq!(result.elected, vec!["A".to_string(), "D".to_string()]);
+
+        // A's surplus of 2 moves to B at a transfer value of 1/3.
+        let surplus_round = result.rounds.iter()
+            .find(|r| matches!(r.action, backend::stv::StvAction::SurplusTransfer { .. }))
+            .expect("A surplus transfer round should be reported");
+        assert_eq!(surplus_round.transfers.get("B"), Some(&2.0));
+        assert_eq!(surplus_round.tallies.get("A"), Some(&4.0));
+    }
+
+    #[tokio::test]
+    async fn test_stv_meek_matches_gregory_on_simple_count() {
+        let (candidates, ballots) = stv_fixture();
+        let result = count_stv(&candidates, &ballots, 2, SurplusTransfer::Meek);
+        assert_eq!(result.elected, vec!["A".to_string(), "D".to_string()]);
+        assert!(!result.rounds.is_empty(), "Meek count should report its rounds");
+    }
+
+    #[test]
+    fn test_stv_exclusion_tie_broken_by_earlier_round() {
+        // D is listed before C, so without a tie-break D is excluded when they tie on 2 votes
+        let candidates: Vec<String> = ["A", "B", "D", "C"].iter().map(|s| s.to_string()).collect();
+        let mut ballots: Vec<Vec<String>> = vec![vec!["A".to_string(), "C".to_string()]; 5];
+        ballots.extend(vec![vec!["B".to_string()]; 3]);
+        ballots.push(vec!["C".to_string()]);
+        ballots.extend(vec![vec!["D".to_string()]; 2]);
+
+        let excluded_first = |result: &backend::StvResult| {
+            result.rounds.iter().find_map(|round| match &round.action {
+                backend::stv::StvAction::Exclusion { candidate } => Some(candidate.clone()),
+                _ => None,
+            })
+        };
+
+        let declared = count_stv(&candidates, &ballots, 2, SurplusTransfer::Gregory);
+        assert_eq!(excluded_first(&declared), Some("D".to_string()));
+        assert!(declared.ties.iter().all(|tie| !tie.resolved));
+
+        let tie_breaker = TieBreaker { policy: TieBreakPolicy::EarlierRound, decisions: Vec::new() };
+        let earlier = count_stv_with_tie_break(&candidates, &ballots, 2, SurplusTransfer::Gregory, &tie_breaker);
+        assert_eq!(excluded_first(&earlier), Some("C".to_string()), "C had fewer first preferences than D");
+        assert!(earlier.ties[0].resolved);
+        assert_eq!(earlier.elected, vec!["A".to_string(), "B".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_election_stv_contest_results_from_chain() {
+        let options = vec![r#"{
+            "presidency": ["Candidate A", "Candidate B"],
+            "council": {"candidates": ["A", "B", "C", "D"], "method": "stv", "seats": 2}
+        }"#.to_string()];
+        let contests = parse_contests(&options).expect("Contests should parse");
+        let council = contests.iter().find(|c| c.name == "council").unwrap();
+        assert_eq!(council.method, ContestMethod::Stv);
+        assert!(council.validate_selection(&json!(["A", "A"])).is_err(), "Duplicate rankings should be rejected");
+
+        let (_, ballots) = stv_fixture();
+        let mut election_chain = ElectionBlockchain::new();
+        for (i, ballot) in ballots.iter().enumerate() {
+            election_chain.add_vote(json!({
+                "voter_id": format!("voter{}", i),
+                "presidency": "Candidate A",
+                "council": ballot
+            })).unwrap();
+        }
+
+        let results = election_chain.get_stv_results(&contests);
+        assert_eq!(results.get("council").map(|r| r.elected.clone()), Some(vec!["A".to_string(), "D".to_string()]));
+        assert_eq!(
+            election_chain.get_vote_counts().get("presidency").and_then(|m| m.get("Candidate A")),
+            Some(&10),
+            "Plurality contests should still be counted alongside STV contests"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_electoral_college_winner_take_all_and_district_split() {
+        assert_eq!(us_electoral_college().values().map(StateElectors::total).sum::<u32>(), 538);
+
+        let options = vec![r#"{
+            "presidency": {
+                "candidates": ["Candidate A", "Candidate B"],
+                "electors": {"NY": 28, "ME": {"at_large": 2, "districts": {"ME-1": 1, "ME-2": 1}}, "AK": 3}
+            }
+        }"#.to_string()];
+        let contests = parse_contests(&options).expect("Contests should parse");
+        assert!(
+            parse_contests(&[r#"{"council": {"candidates": ["A", "B"], "method": "stv", "electors": "us_electoral_college"}}"#.to_string()]).is_err(),
+            "Only plurality contests apportion electors"
+        );
+
+        let mut election_chain = ElectionBlockchain::new();
+        let ballots = [
+            ("NY", None, "Candidate A"), ("NY", None, "Candidate B"), ("NY", None, "Candidate A"),
+            ("ME", Some("ME-1"), "Candidate A"), ("ME", Some("ME-1"), "Candidate A"),
+            ("ME", Some("ME-2"), "Candidate B"),
+            ("AK", Some("AK-AL"), "Candidate A"), ("AK", Some("AK-AL"), "Candidate B"),
+        ];
+        for (i, (state, district, choice)) in ballots.iter().enumerate() {
+            election_chain.add_vote(json!({
+                "voter_id": format!("voter{}", i),
+                "state": state,
+                "district": district,
+                "presidency": choice
+            })).unwrap();
+        }
+
+        let results = election_chain.get_electoral_results(&contests);
+        let presidency = &results["presidency"];
+        assert_eq!(presidency.total_electors, 35);
+        assert_eq!(presidency.majority, 18);
+        assert_eq!(presidency.electoral_votes.get("Candidate A"), Some(&31), "NY, Maine at large and ME-1");
+        assert_eq!(presidency.electoral_votes.get("Candidate B"), Some(&1), "ME-2");
+        assert_eq!(presidency.unallocated, 3, "Alaska is tied");
+        assert_eq!(presidency.projected_winner.as_deref(), Some("Candidate A"));
+    }
+
+    #[tokio::test]
+    async fn test_results_timeline_buckets_cumulative_counts() {
+        let at = |hour: u32, minute: u32| chrono::Utc.with_ymd_and_hms(2024, 11, 5, hour, minute, 0).unwrap().timestamp_millis();
+        let options = vec!["Yes".to_string(), "No".to_string()];
+
+        let mut chain = Blockchain::new();
+        chain.add_block_at(json!({"voter_id": "A", "choice": "Yes"}), at(20, 10));
+        chain.add_block_at(json!({"voter_id": "B", "choice": "No"}), at(20, 40));
+        chain.add_block_at(json!({"voter_id": "A", "choice": "No", "supersedes": {"block_index": 1, "block_hash": ""}}), at(21, 5));
+
+        let hourly = chain.timeline(&options, Bucket::parse("hour").unwrap());
+        assert_eq!(hourly.len(), 2);
+        assert_eq!(hourly[0].bucket_start, chrono::Utc.with_ymd_and_hms(2024, 11, 5, 20, 0, 0).unwrap());
+        assert_eq!(hourly[0].counts, BTreeMap::from([("Yes".to_string(), 1), ("No".to_string(), 1)]));
+        assert_eq!(hourly[1].counts, BTreeMap::from([("Yes".to_string(), 0), ("No".to_string(), 2)]), "The recast moves A's vote");
+        assert_eq!(chain.timeline(&options, Bucket::Day).len(), 1);
+        assert!(Bucket::parse("fortnight").is_err());
+
+        let mut election_chain = ElectionBlockchain::new();
+        election_chain.add_vote_at(json!({"voter_id": "A", "presidency": "Candidate A", "senate": "X"}), at(20, 10)).unwrap();
+        election_chain.add_vote_at(json!({"voter_id": "B", "presidency": "Candidate B"}), at(21, 30)).unwrap();
+        let timeline = election_chain.timeline(Bucket::Hour);
+        assert_eq!(timeline.len(), 2);
+        assert_eq!(timeline[0].counts["presidency"]["Candidate A"], 1);
+        assert_eq!(timeline[1].counts["presidency"]["Candidate B"], 1);
+        assert_eq!(timeline[1].counts["senate"]["X"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_election_contest_turnout_separates_abstain_blank_and_spoiled() {
+        let options = vec![r#"{
+            "mayor": {"candidates": ["Alice", "Bob"], "write_in_slots": 1},
+            "measure": ["Yes", "No"]
+        }"#.to_string()];
+        let contests = parse_contests(&options).expect("Contests should parse");
+
+        let mut election_chain = ElectionBlockchain::new();
+        let ballots = [
+            json!({"voter_id": "v1", "mayor": "Alice", "measure": "Yes", "ballot_status": "valid"}),
+            json!({"voter_id": "v2", "mayor": {"abstain": true}, "measure": "No", "ballot_status": "valid"}),
+            json!({"voter_id": "v3", "mayor": {"write_in": "nobody"}, "ballot_status": "valid"}),
+            json!({"voter_id": "v4", "ballot_status": "spoiled"}),
+            json!({"voter_id": "v5", "ballot_status": "abstain"}),
+        ];
+        for ballot in ballots {
+            election_chain.add_vote(ballot).unwrap();
         }
+        election_chain.add_vote(json!({
+            "record_type": "adjudication", "contest": "mayor", "write_in": "nobody", "resolution": "invalid", "official": "clerk"
+        })).unwrap();
+
+        let turnout = election_chain.get_contest_turnout(&contests);
+        assert_eq!(turnout["mayor"], Turnout { ballots_cast: 5, valid_votes: 1, abstained: 2, blank: 0, spoiled: 2 });
+        assert_eq!(turnout["measure"], Turnout { ballots_cast: 5, valid_votes: 2, abstained: 1, blank: 1, spoiled: 1 });
+        assert_eq!(election_chain.get_turnout().ballots_cast, 5, "Adjudication records are not ballots");
+        assert!(
+            !election_chain.get_vote_counts().contains_key("ballot_status"),
+            "Ballot status must not be counted as a contest"
+        );
+    }
+
+    #[test]
+    fn test_outcome_quorum_supermajority_and_ties() {
+        let counts = |yes: u64, no: u64| -> std::collections::BTreeMap<String, u64> {
+            [("Yes".to_string(), yes), ("No".to_string(), no)].into_iter().collect()
+        };
+        let turnout = |valid: u32, abstained: u32| Turnout {
+            ballots_cast: valid + abstained,
+            valid_votes: valid,
+            abstained,
+            ..Default::default()
+        };
+        let two_thirds = DecisionRules {
+            quorum: Some(Quorum::Percent(50.0)),
+            threshold: Threshold { fraction: Fraction::TwoThirds, base: ThresholdBase::VotesCast },
+            ..Default::default()
+        };
+
+        // 20 of 30 is exactly two thirds; 31 of 60 members present meets a 50% quorum
+        let outcome = decide(&two_thirds, &counts(20, 10), &turnout(30, 1), 60);
+        assert_eq!(outcome.outcome, OutcomeStatus::Passed);
+        assert_eq!(outcome.threshold.required, 20);
+        assert_eq!(outcome.quorum.as_ref().map(|q| (q.present, q.required)), Some((30, 30)));
+
+        // Counting the abstention raises the base to 31, so 20 votes no longer suffice
+        let with_abstentions = DecisionRules { abstentions_count: true, ..two_thirds.clone() };
+        let outcome = decide(&with_abstentions, &counts(20, 10), &turnout(30, 1), 60);
+        assert_eq!(outcome.outcome, OutcomeStatus::Failed);
+        assert_eq!((outcome.threshold.base, outcome.threshold.required), (31, 21));
+
+        assert_eq!(decide(&two_thirds, &counts(20, 9), &turnout(29, 0), 60).outcome, OutcomeStatus::NoQuorum);
+        assert_eq!(decide(&DecisionRules::default(), &counts(5, 5), &turnout(10, 0), 0).outcome, OutcomeStatus::Tie);
+
+        let of_members = DecisionRules {
+            threshold: Threshold { fraction: Fraction::SimpleMajority, base: ThresholdBase::Members },
+            members: Some(100),
+            ..Default::default()
+        };
+        let outcome = decide(&of_members, &counts(40, 5), &turnout(45, 0), 0);
+        assert_eq!(outcome.outcome, OutcomeStatus::Failed, "A majority of members needs 51 votes");
+        assert!(!outcome.arithmetic.is_empty());
     }
 
     // ==============================
@@ -223,6 +809,7 @@ mod tests {
             options: vec!["Yes".to_string(), "No".to_string()],
             is_public: true,
             poll_type: Some("normal".to_string()),
+            ..Default::default()
         };
 
         let poll_id = pm.create_poll(poll_input.clone()).await.expect("Poll creation failed");
@@ -251,6 +838,7 @@ mod tests {
             options: vec![r#"{"election": ["Candidate A", "Candidate B"]}"#.to_string()],
             is_public: true,
             poll_type: Some("election".to_string()),
+            ..Default::default()
         };
 
         let poll_id = pm.create_poll(poll_input.clone()).await.expect("Failed to create election poll");
@@ -357,23 +945,122 @@ mod tests {
             poll_id: "test_poll".to_string(),
             voter_id: "voter_test".to_string(),
             vote_data: json!({"candidate": "Candidate X"}),
+            demographics: None,
+            position: ChainPosition { sequence: 1, chained_at: 0 },
         };
 
         // Record a vote.
-        let vote_record = vote_service.record_vote(vote_request.clone()).await
+        let participation = vote_service.record_vote(vote_request.clone()).await
             .expect("Failed to record vote");
-        assert!(vote_record.id > 0, "Vote record should have a valid ID");
+        assert_eq!(participation.ballots_cast, 1);
+        assert!(participation.receipt.is_some(), "Recording a vote should return a receipt");
+
+        // The ballot box holds the ballot without the voter's id.
+        let ballots = vote_service.get_poll_votes("test_poll").await.expect("Failed to fetch ballots");
+        assert_eq!(ballots.len(), 1);
+        assert!(!serde_json::to_string(&ballots).unwrap().contains("voter_test"));
 
         // Attempt to record a duplicate vote should return an error.
         let duplicate_result = vote_service.record_vote(vote_request).await;
         assert!(duplicate_result.is_err(), "Duplicate vote should fail");
     }
 
+    #[tokio::test]
+    async fn test_vote_service_get_vote_returns_only_participation() {
+        let pool = setup_test_pool().await.expect("Failed to set up test pool");
+        let vote_service = VoteService::new(pool.clone());
+
+        let vote = json!({"candidate": "Candidate X"});
+        let cast = vote_service.record_vote(VoteRequest {
+            poll_id: "test_poll".to_string(),
+            voter_id: "voter_test".to_string(),
+            vote_data: vote.clone(),
+            demographics: None,
+            position: ChainPosition { sequence: 1, chained_at: 0 },
+        }).await.expect("Failed to record vote");
+        assert_eq!(cast.receipt, Some(vote_service::ballot_receipt(&vote)), "The receipt is issued when the ballot is cast");
+
+        // Looking the voter up returns that they took part and nothing about their ballot
+        let participation = vote_service.get_vote("test_poll", "voter_test").await.expect("Lookup failed")
+            .expect("The voter took part");
+        assert_eq!(participation.ballots_cast, 1);
+        assert_eq!(participation.receipt, None, "Ballots cannot be found from a voter id");
+        let looked_up = serde_json::to_value(&participation).unwrap();
+        let mut fields: Vec<&String> = looked_up.as_object().unwrap().keys().collect();
+        fields.sort();
+        assert_eq!(fields, ["ballots_cast", "poll_id", "receipt", "voted_hour", "voter_id"]);
+        assert!(!looked_up.to_string().contains("Candidate X"));
+
+        assert!(vote_service.get_vote("test_poll", "someone_else").await.expect("Lookup failed").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_voting_integration_double_vote_rejected_across_split_tables() {
+        let pool = setup_test_pool().await.expect("Failed to set up test pool");
+        let vote_service = Arc::new(VoteService::new(pool.clone()));
+        let poll_manager = Arc::new(Mutex::new(PollManager::new(pool.clone())));
+        let voting_integration = VotingIntegration::new(poll_manager.clone(), vote_service.clone());
+
+        let mut input = PollInput {
+            title: "Once".to_string(),
+            question: "Agree?".to_string(),
+            options: vec!["Yes".to_string(), "No".to_string()],
+            is_public: true,
+            poll_type: Some("normal".to_string()),
+            ..Default::default()
+        };
+        let poll_id = poll_manager.lock().await.create_poll(input.clone()).await.expect("Poll creation failed");
+        input.settings.revote_until = Some(chrono::Utc::now() + chrono::Duration::hours(1));
+        let revote_poll = poll_manager.lock().await.create_poll(input).await.expect("Poll creation failed");
+
+        let count = |query: &'static str, poll_id: String| {
+            let pool = pool.clone();
+            async move { sqlx::query_scalar::<_, i64>(query).bind(poll_id).fetch_one(&pool).await.expect("Count failed") }
+        };
+        let participants = "SELECT COUNT(*) FROM participation WHERE poll_id = $1";
+        let ballots = "SELECT COUNT(*) FROM ballots WHERE poll_id = $1";
+        let current = "SELECT COUNT(*) FROM ballots WHERE poll_id = $1 AND NOT superseded";
+
+        voting_integration.cast_vote(&poll_id, "voter-a", json!({"choice": "Yes"})).await.expect("Vote failed");
+        assert!(matches!(
+            voting_integration.cast_vote(&poll_id, "voter-a", json!({"choice": "No"})).await,
+            Err(VotingError::AlreadyVoted(_))
+        ));
+        // Recording straight into the ballot box is refused too, and leaves no orphan ballot
+        let duplicate = vote_service.record_vote(VoteRequest {
+            poll_id: poll_id.clone(),
+            voter_id: "voter-a".to_string(),
+            vote_data: json!({"choice": "No"}),
+            demographics: None,
+            position: ChainPosition { sequence: 99, chained_at: 0 },
+        }).await;
+        assert!(duplicate.is_err());
+        assert_eq!(count(participants, poll_id.clone()).await, 1);
+        assert_eq!(count(ballots, poll_id.clone()).await, 1);
+        let results = voting_integration.get_poll_results(&poll_id).await.expect("Failed to get results");
+        assert_eq!(results["blockchain_results"], json!({"Yes": 1}));
+        assert_eq!(results["database_results"], json!({"Yes": 1}));
+
+        // With revoting open a second ballot needs the handle and replaces the first
+        let handle = voting_integration.cast_vote(&revote_poll, "voter-a", json!({"choice": "Yes"})).await.expect("Vote failed")
+            .ballot_handle.expect("Revote polls issue a ballot handle");
+        assert!(voting_integration.cast_vote(&revote_poll, "voter-a", json!({"choice": "No"})).await.is_err());
+        assert!(voting_integration.recast_vote(&revote_poll, "voter-b", &handle, json!({"choice": "No"})).await.is_err(),
+            "Only a voter who has voted can recast");
+        voting_integration.recast_vote(&revote_poll, "voter-a", &handle, json!({"choice": "No"})).await.expect("Revote failed");
+        assert_eq!(count(participants, revote_poll.clone()).await, 1);
+        assert_eq!(count(ballots, revote_poll.clone()).await, 2);
+        assert_eq!(count(current, revote_poll.clone()).await, 1);
+        let results = voting_integration.get_poll_results(&revote_poll).await.expect("Failed to get results");
+        assert_eq!(results["blockchain_results"], json!({"No": 1}));
+        assert_eq!(results["database_results"], json!({"No": 1}));
+    }
+
     // ==============================
     // Voting Integration Tests
     // ==============================
     #[tokio::test]
-    async fn test_voting_integration_cast_and_verify_vote() {
+    async fn test_voting_integration_cast_and_track_vote() {
         let pool = setup_test_pool().await.expect("Failed to set up test pool");
 
         // Create managers and wrap PollManager in an async Mutex.
@@ -388,6 +1075,7 @@ mod tests {
             options: vec!["Yes".to_string(), "No".to_string()],
             is_public: true,
             poll_type: Some("normal".to_string()),
+            ..Default::default()
         };
 
         let poll_id = {
@@ -408,19 +1096,15 @@ mod tests {
             "contest": "default"
         });
 
-        voting_integration.cast_vote(&poll_id, "integration_voter", vote_data.clone())
+        let receipt = voting_integration.cast_vote(&poll_id, "integration_voter", vote_data.clone())
             .await
             .expect("Failed to cast vote");
 
-        // Verify the vote exists in both systems.
-        let verify_result = voting_integration.verify_vote(&poll_id, "integration_voter")
+        // Follow the ballot on the chain with its tracker code.
+        let tracked = voting_integration.track_ballot(&poll_id, &receipt.tracker_code)
             .await
-            .expect("Vote verification failed");
-        
-        let verified = verify_result.get("verified")
-            .and_then(|v| v.as_bool())
-            .unwrap_or(false);
-        assert!(verified, "Vote should be verified in both blockchain and database");
+            .expect("Ballot tracking failed");
+        assert!(tracked.counted, "Ballot should be counted on the chain");
 
         // Here's the part that's failing:
         let results = voting_integration.get_poll_results(&poll_id)
@@ -463,4 +1147,1232 @@ mod tests {
         
         assert!(candidate_count >= 1, "Candidate Yes should have at least 1 vote");
     }
+
+    #[tokio::test]
+    async fn test_voting_integration_weighted_poll() {
+        let pool = setup_test_pool().await.expect("Failed to set up test pool");
+        let user_manager = UserManager::new(pool.clone());
+        let vote_service = Arc::new(VoteService::new(pool.clone()));
+        let poll_manager = Arc::new(Mutex::new(PollManager::new(pool.clone())));
+        let voting_integration = VotingIntegration::new(poll_manager.clone(), vote_service.clone());
+
+        let poll_id = {
+            let mut pm = poll_manager.lock().await;
+            pm.create_poll(PollInput {
+                title: "Shareholder Vote".to_string(),
+                question: "Approve the merger?".to_string(),
+                options: vec!["Yes".to_string(), "No".to_string()],
+                is_public: true,
+                poll_type: Some("weighted".to_string()),
+                ..Default::default()
+            }).await.expect("Poll creation failed")
+        };
+
+        let mut voter_ids = Vec::new();
+        for (i, email) in ["w1@a.com", "w2@a.com", "w3@a.com"].iter().enumerate() {
+            let user = user_manager.register_user(UserRegistration {
+                name: format!("Shareholder {}", i),
+                email: email.to_string(),
+                zip_code: "12345".to_string(),
+                birth_date: "1980-01-01".to_string(),
+                password: "password".to_string(),
+            }).await.expect("User registration failed");
+            voter_ids.push(user.voter_id);
+        }
+
+        voting_integration.import_voter_weights(&poll_id, &[
+            VoterWeight { voter_id: voter_ids[0].clone(), weight: 70.0 },
+            VoterWeight { voter_id: voter_ids[1].clone(), weight: 20.0 },
+        ]).await.expect("Weight import failed");
+
+        voting_integration.cast_vote(&poll_id, &voter_ids[0], json!({"choice": "No"})).await.expect("Vote failed");
+        voting_integration.cast_vote(&poll_id, &voter_ids[1], json!({"choice": "Yes"})).await.expect("Vote failed");
+        assert!(
+            voting_integration.cast_vote(&poll_id, &voter_ids[2], json!({"choice": "Yes"})).await.is_err(),
+            "Voters without a registry weight should not be able to vote"
+        );
+
+        let results = voting_integration.get_poll_results(&poll_id).await.expect("Failed to get results");
+        assert_eq!(results["blockchain_results"]["No"], json!({"weight": 70.0, "headcount": 1}));
+        assert_eq!(results["blockchain_results"]["Yes"], json!({"weight": 20.0, "headcount": 1}));
+    }
+
+    #[tokio::test]
+    async fn test_voting_integration_write_in_adjudication() {
+        let pool = setup_test_pool().await.expect("Failed to set up test pool");
+        let user_manager = UserManager::new(pool.clone());
+        let vote_service = Arc::new(VoteService::new(pool.clone()));
+        let poll_manager = Arc::new(Mutex::new(PollManager::new(pool.clone())));
+        let voting_integration = VotingIntegration::new(poll_manager.clone(), vote_service.clone());
+
+        let poll_id = {
+            let mut pm = poll_manager.lock().await;
+            pm.create_poll(PollInput {
+                title: "Municipal Election".to_string(),
+                question: "Choose a mayor".to_string(),
+                options: vec![r#"{"mayor": {"candidates": ["Alice", "Bob"], "write_in_slots": 1, "qualified_write_ins": ["Carol"]}}"#.to_string()],
+                is_public: true,
+                poll_type: Some("election".to_string()),
+                ..Default::default()
+            }).await.expect("Poll creation failed")
+        };
+
+        let ballots = [json!("Alice"), json!({"write_in": "  Carol "}), json!({"write_in": "CAROL"}), json!({"write_in": "Mickey   Mouse"})];
+        for (i, selection) in ballots.iter().enumerate() {
+            let user = user_manager.register_user(UserRegistration {
+                name: format!("Resident {}", i),
+                email: format!("resident{}@a.com", i),
+                zip_code: "12345".to_string(),
+                birth_date: "1980-01-01".to_string(),
+                password: "password".to_string(),
+            }).await.expect("User registration failed");
+            voting_integration.cast_vote(&poll_id, &user.voter_id, json!({"mayor": selection})).await.expect("Vote failed");
+        }
+
+        let adjudicate = |write_in: &str, resolution: &str| WriteInAdjudication {
+            contest: "mayor".to_string(),
+            write_in: write_in.to_string(),
+            resolution: resolution.to_string(),
+            official: "clerk".to_string(),
+        };
+        assert!(
+            voting_integration.adjudicate_write_in(&poll_id, &adjudicate("carol", "Dave")).await.is_err(),
+            "Write-ins can only be mapped to eligible candidates"
+        );
+        voting_integration.adjudicate_write_in(&poll_id, &adjudicate("Carol", "Carol")).await.expect("Adjudication failed");
+        voting_integration.adjudicate_write_in(&poll_id, &adjudicate("mickey mouse", "invalid")).await.expect("Adjudication failed");
+
+        let results = voting_integration.get_poll_results(&poll_id).await.expect("Failed to get results");
+        assert_eq!(results["blockchain_results"]["mayor"], json!({"Alice": 1, "Carol": 2}));
+        assert_eq!(results["write_ins"]["mayor"]["carol"], json!({"ballots": 2, "resolution": "Carol"}));
+        assert_eq!(results["write_ins"]["mayor"]["mickey mouse"], json!({"ballots": 1, "resolution": "invalid"}));
+    }
+
+    #[tokio::test]
+    async fn test_voting_integration_counts_abstain_blank_and_spoiled_separately() {
+        let pool = setup_test_pool().await.expect("Failed to set up test pool");
+        let user_manager = UserManager::new(pool.clone());
+        let vote_service = Arc::new(VoteService::new(pool.clone()));
+        let poll_manager = Arc::new(Mutex::new(PollManager::new(pool.clone())));
+        let voting_integration = VotingIntegration::new(poll_manager.clone(), vote_service.clone());
+
+        let poll_id = {
+            let mut pm = poll_manager.lock().await;
+            pm.create_poll(PollInput {
+                title: "Referendum".to_string(),
+                question: "Adopt the proposal?".to_string(),
+                options: vec!["Yes".to_string(), "No".to_string()],
+                is_public: true,
+                poll_type: Some("normal".to_string()),
+                ..Default::default()
+            }).await.expect("Poll creation failed")
+        };
+
+        let ballots = [json!("Yes"), json!({"choice": "No"}), json!(""), json!({"abstain": true}), json!(42)];
+        for (i, ballot) in ballots.iter().enumerate() {
+            let user = user_manager.register_user(UserRegistration {
+                name: format!("Citizen {}", i),
+                email: format!("citizen{}@a.com", i),
+                zip_code: "12345".to_string(),
+                birth_date: "1980-01-01".to_string(),
+                password: "password".to_string(),
+            }).await.expect("User registration failed");
+            voting_integration.cast_vote(&poll_id, &user.voter_id, ballot.clone()).await.expect("Vote failed");
+        }
+
+        let results = voting_integration.get_poll_results(&poll_id).await.expect("Failed to get results");
+        let expected_turnout = json!({"ballots_cast": 5, "valid_votes": 2, "abstained": 1, "blank": 1, "spoiled": 1});
+        assert_eq!(results["blockchain_results"], json!({"Yes": 1, "No": 1}));
+        assert_eq!(results["database_results"], json!({"Yes": 1, "No": 1}));
+        assert_eq!(results["turnout"], expected_turnout);
+        assert_eq!(results["database_turnout"], expected_turnout);
+    }
+
+    #[tokio::test]
+    async fn test_voting_integration_tie_broken_by_recorded_lot() {
+        let pool = setup_test_pool().await.expect("Failed to set up test pool");
+        let user_manager = UserManager::new(pool.clone());
+        let vote_service = Arc::new(VoteService::new(pool.clone()));
+        let poll_manager = Arc::new(Mutex::new(PollManager::new(pool.clone())));
+        let voting_integration = VotingIntegration::new(poll_manager.clone(), vote_service.clone());
+
+        let mut input = PollInput {
+            title: "Club Mascot".to_string(),
+            question: "Which mascot?".to_string(),
+            options: vec!["Owl".to_string(), "Fox".to_string()],
+            is_public: true,
+            poll_type: Some("normal".to_string()),
+            ..Default::default()
+        };
+        input.settings.rules = Some(DecisionRules::default());
+        input.settings.tie_break = Some(TieBreakPolicy::Lot);
+        let poll_id = {
+            let mut pm = poll_manager.lock().await;
+            pm.create_poll(input).await.expect("Poll creation failed")
+        };
+
+        for (i, choice) in ["Owl", "Fox"].iter().enumerate() {
+            let user = user_manager.register_user(UserRegistration {
+                name: format!("Member {}", i),
+                email: format!("member{}@a.com", i),
+                zip_code: "12345".to_string(),
+                birth_date: "1980-01-01".to_string(),
+                password: "password".to_string(),
+            }).await.expect("User registration failed");
+            voting_integration.cast_vote(&poll_id, &user.voter_id, json!({"choice": choice})).await.expect("Vote failed");
+        }
+
+        let results = voting_integration.get_poll_results(&poll_id).await.expect("Failed to get results");
+        assert_eq!(results["outcome"]["outcome"], json!("tie"));
+        assert_eq!(results["outcome"]["tie_break"]["resolved"], json!(false));
+
+        let request = TieBreakRequest {
+            contest: None,
+            tied: vec!["Owl".to_string(), "Fox".to_string()],
+            ranking: None,
+            official: "secretary".to_string(),
+        };
+        let record = voting_integration.record_tie_break(&poll_id, &request).await.expect("Tie-break failed");
+        let seed = record.seed.clone().expect("Lots record their seed");
+        assert_eq!(record.ranking, draw_lots(&seed, None, &record.tied), "Anyone can redraw the lots from the seed");
+
+        let results = voting_integration.get_poll_results(&poll_id).await.expect("Failed to get results");
+        assert_eq!(results["outcome"]["leading_options"], json!([record.ranking[0]]));
+        assert_eq!(results["outcome"]["tie_break"]["resolved"], json!(true));
+        assert_eq!(results["outcome"]["outcome"], json!("failed"), "One vote of two is not a majority");
+        assert_eq!(results["turnout"]["ballots_cast"], json!(2), "Tie-break records are not ballots");
+    }
+
+    #[tokio::test]
+    async fn test_voting_integration_revote_supersedes_earlier_ballot() {
+        let pool = setup_test_pool().await.expect("Failed to set up test pool");
+        let user_manager = UserManager::new(pool.clone());
+        let vote_service = Arc::new(VoteService::new(pool.clone()));
+        let poll_manager = Arc::new(Mutex::new(PollManager::new(pool.clone())));
+        let voting_integration = VotingIntegration::new(poll_manager.clone(), vote_service.clone());
+
+        let create = |revote_until: chrono::DateTime<chrono::Utc>| {
+            let mut input = PollInput {
+                title: "Budget Motion".to_string(),
+                question: "Approve the budget?".to_string(),
+                options: vec!["Yes".to_string(), "No".to_string()],
+                is_public: true,
+                poll_type: Some("normal".to_string()),
+                ..Default::default()
+            };
+            input.settings.revote_until = Some(revote_until);
+            input
+        };
+        let (open_poll, closed_poll) = {
+            let mut pm = poll_manager.lock().await;
+            let open_poll = pm.create_poll(create(chrono::Utc::now() + chrono::Duration::hours(1))).await.expect("Poll creation failed");
+            let closed_poll = pm.create_poll(create(chrono::Utc::now() - chrono::Duration::hours(1))).await.expect("Poll creation failed");
+            (open_poll, closed_poll)
+        };
+
+        let mut voter_ids = Vec::new();
+        for i in 0..2 {
+            let user = user_manager.register_user(UserRegistration {
+                name: format!("Delegate {}", i),
+                email: format!("delegate{}@a.com", i),
+                zip_code: "12345".to_string(),
+                birth_date: "1980-01-01".to_string(),
+                password: "password".to_string(),
+            }).await.expect("User registration failed");
+            voter_ids.push(user.voter_id);
+        }
+
+        let first = voting_integration.cast_vote(&open_poll, &voter_ids[0], json!({"choice": "Yes"})).await.expect("Vote failed");
+        let second = voting_integration.cast_vote(&open_poll, &voter_ids[1], json!({"choice": "Yes"})).await.expect("Vote failed");
+        let handle = first.ballot_handle.expect("Revote polls issue a ballot handle");
+        assert!(voting_integration.cast_vote(&open_poll, &voter_ids[0], json!({"choice": "No"})).await.is_err(),
+            "Recasting requires the ballot handle");
+        assert!(voting_integration.recast_vote(&open_poll, &voter_ids[0], &second.ballot_handle.unwrap(), json!({"choice": "No"})).await.is_err(),
+            "A handle recasts only its own voter's ballot");
+        let recast = voting_integration.recast_vote(&open_poll, &voter_ids[0], &handle, json!({"choice": "No"})).await.expect("Revote failed");
+        assert_eq!(recast.ballot_handle.as_deref(), Some(handle.as_str()));
+
+        let results = voting_integration.get_poll_results(&open_poll).await.expect("Failed to get results");
+        assert_eq!(results["blockchain_results"], json!({"Yes": 1, "No": 1}));
+        assert_eq!(results["database_results"], json!({"Yes": 1, "No": 1}));
+        assert_eq!(results["turnout"]["ballots_cast"], json!(2));
+
+        {
+            let pm = poll_manager.lock().await;
+            let Some(Poll::Normal { blockchain, .. }) = pm.get_poll(&open_poll) else { panic!("Expected a normal poll") };
+            assert_eq!(blockchain.chain.len(), 4, "The chain keeps the superseded ballot");
+            assert_eq!(blockchain.chain[3].transactions[0]["supersedes"]["block_index"], json!(1));
+            assert_eq!(blockchain.chain[3].transactions[0]["ballot_tag"], blockchain.chain[1].transactions[0]["ballot_tag"]);
+            assert_ne!(blockchain.chain[2].transactions[0]["ballot_tag"], blockchain.chain[1].transactions[0]["ballot_tag"]);
+            let chain = serde_json::to_string(&blockchain.chain).unwrap();
+            assert!(!chain.contains("voter_id") && !chain.contains(&handle), "Ballots carry neither voter ids nor handles");
+            assert_eq!(
+                recast.receipt,
+                vote_service::ballot_receipt(&blockchain.chain[3].transactions[0]),
+                "The receipt matches the voter's current ballot on the chain"
+            );
+        }
+
+        let participation = vote_service.get_vote(&open_poll, &voter_ids[0]).await.expect("Lookup failed")
+            .expect("The voter took part");
+        assert_eq!(participation.ballots_cast, 2);
+
+        let closed = voting_integration.cast_vote(&closed_poll, &voter_ids[0], json!({"choice": "Yes"})).await.expect("Vote failed");
+        assert!(closed.ballot_handle.is_none(), "Handles are only issued while revoting is open");
+        assert!(
+            voting_integration.cast_vote(&closed_poll, &voter_ids[0], json!({"choice": "No"})).await.is_err(),
+            "Recasting after the revote deadline should be rejected"
+        );
+        assert!(voting_integration.recast_vote(&closed_poll, &voter_ids[0], &handle, json!({"choice": "No"})).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_voting_integration_ballot_style_from_zip_code() {
+        let pool = setup_test_pool().await.expect("Failed to set up test pool");
+        let user_manager = UserManager::new(pool.clone());
+        let vote_service = Arc::new(VoteService::new(pool.clone()));
+        let poll_manager = Arc::new(Mutex::new(PollManager::new(pool.clone())));
+        let voting_integration = VotingIntegration::new(poll_manager.clone(), vote_service.clone());
+
+        let poll_id = {
+            let mut pm = poll_manager.lock().await;
+            pm.create_poll(PollInput {
+                title: "General Election".to_string(),
+                question: "Vote in the contests on your ballot".to_string(),
+                options: vec![r#"{
+                    "president": ["Candidate A", "Candidate B"],
+                    "ny_governor": {"candidates": ["Hochul", "Zeldin"], "jurisdictions": ["NY"]},
+                    "ak_house": {"candidates": ["Peltola", "Begich"], "jurisdictions": ["AK-AL"]}
+                }"#.to_string()],
+                is_public: true,
+                poll_type: Some("election".to_string()),
+                ..Default::default()
+            }).await.expect("Poll creation failed")
+        };
+
+        let register = |i: usize, zip_code: &str| UserRegistration {
+            name: format!("Voter {}", i),
+            email: format!("styled{}@a.com", i),
+            zip_code: zip_code.to_string(),
+            birth_date: "1980-01-01".to_string(),
+            password: "password".to_string(),
+        };
+        assert!(user_manager.register_user(register(0, "00000")).await.is_err(), "Zip codes outside the dataset are rejected");
+        let new_yorker = user_manager.register_user(register(1, "12345")).await.expect("User registration failed");
+        let alaskan = user_manager.register_user(register(2, "99501")).await.expect("User registration failed");
+
+        let style = voting_integration.get_ballot_style(&poll_id, &alaskan.voter_id).await.expect("Ballot style failed");
+        assert_eq!(style.jurisdiction.state, "AK");
+        assert_eq!(style.jurisdiction.district.as_deref(), Some("AK-AL"));
+        assert_eq!(style.contests, vec!["ak_house".to_string(), "president".to_string()]);
+
+        assert!(
+            voting_integration.cast_vote(&poll_id, &new_yorker.voter_id, json!({"president": "Candidate A", "ak_house": "Begich"})).await.is_err(),
+            "Contests outside the voter's ballot style are rejected"
+        );
+        voting_integration.cast_vote(&poll_id, &new_yorker.voter_id, json!({"president": "Candidate A", "ny_governor": "Hochul", "state": "CA"})).await.expect("Vote failed");
+        voting_integration.cast_vote(&poll_id, &alaskan.voter_id, json!({"president": "Candidate B", "ak_house": "Peltola"})).await.expect("Vote failed");
+
+        let pm = poll_manager.lock().await;
+        let Some(Poll::Election { blockchain, .. }) = pm.get_poll(&poll_id) else { panic!("Expected an election poll") };
+        assert_eq!(blockchain.chain[1].transactions["state"], json!("NY"), "The client-sent state is replaced");
+        assert_eq!(blockchain.chain[2].transactions["district"], json!("AK-AL"));
+        let by_state = blockchain.get_vote_counts_by_state();
+        assert_eq!(by_state["NY"]["ny_governor: Hochul"], 1);
+        assert_eq!(by_state["AK"]["ak_house: Peltola"], 1);
+        assert!(!by_state.contains_key("CA"));
+    }
+
+    #[tokio::test]
+    async fn test_voting_integration_turnout_report_suppresses_small_cells() {
+        let pool = setup_test_pool().await.expect("Failed to set up test pool");
+        let user_manager = UserManager::new(pool.clone());
+        let vote_service = Arc::new(VoteService::new(pool.clone()));
+        let poll_manager = Arc::new(Mutex::new(PollManager::new(pool.clone())));
+        let voting_integration = VotingIntegration::new(poll_manager.clone(), vote_service.clone());
+
+        let poll_id = {
+            let mut pm = poll_manager.lock().await;
+            let mut input = PollInput {
+                title: "Library Levy".to_string(),
+                question: "Approve the levy?".to_string(),
+                options: vec!["Yes".to_string(), "No".to_string()],
+                is_public: true,
+                poll_type: Some("normal".to_string()),
+                ..Default::default()
+            };
+            input.settings.min_cell_size = Some(2);
+            pm.create_poll(input).await.expect("Poll creation failed")
+        };
+
+        // Four New York voters, two of whom vote; two Wisconsin voters, one of whom votes
+        for (i, (zip_code, votes)) in [("12345", true), ("12345", true), ("12345", false), ("12345", false), ("54321", true), ("54321", false)].iter().enumerate() {
+            let user = user_manager.register_user(UserRegistration {
+                name: format!("Resident {}", i),
+                email: format!("roll{}@a.com", i),
+                zip_code: zip_code.to_string(),
+                birth_date: "1980-01-01".to_string(),
+                password: "password".to_string(),
+            }).await.expect("User registration failed");
+            if *votes {
+                voting_integration.cast_vote(&poll_id, &user.voter_id, json!("Yes")).await.expect("Vote failed");
+            }
+        }
+
+        let report = voting_integration.get_turnout_report(&poll_id).await.expect("Turnout report failed");
+        assert_eq!((report.registered, report.voted, report.turnout_percent), (6, 3, 50.0));
+        let new_york = report.by_state.iter().find(|cell| cell.key == "NY").unwrap();
+        assert_eq!((new_york.registered, new_york.voted, new_york.turnout_percent), (Some(4), Some(2), Some(50.0)));
+        let wisconsin = report.by_state.iter().find(|cell| cell.key == "WI").unwrap();
+        assert!(wisconsin.suppressed && wisconsin.voted.is_none(), "One voter out of two would reveal who voted");
+        assert!(report.by_zip_prefix.iter().any(|cell| cell.key == "543" && cell.suppressed));
+
+        // Hours with fewer than k arrivals are folded into the next published hour
+        let at = |hour: u32| Some(chrono::Utc.with_ymd_and_hms(2024, 11, 5, hour, 30, 0).unwrap());
+        let zip = Some("12345".to_string());
+        let roll: Vec<_> = [at(8), at(8), at(9), at(10), at(10), at(11)].into_iter().map(|voted_at| (zip.clone(), voted_at)).collect();
+        let hourly = statistics::turnout_report(&roll, 2).hourly;
+        assert_eq!(
+            hourly.iter().map(|bucket| bucket.cumulative_voted).collect::<Vec<_>>(),
+            vec![Some(2), None, Some(5), None]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_voting_integration_demographic_breakdown_with_k_anonymity() {
+        let pool = setup_test_pool().await.expect("Failed to set up test pool");
+        let user_manager = UserManager::new(pool.clone());
+        let vote_service = Arc::new(VoteService::new(pool.clone()));
+        let poll_manager = Arc::new(Mutex::new(PollManager::new(pool.clone())));
+        let voting_integration = VotingIntegration::new(poll_manager.clone(), vote_service.clone());
+
+        let day = |y, m, d| chrono::NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        assert_eq!(Demographics::derive(Some(day(2000, 3, 1)), Some("12345"), day(2024, 2, 29)).age_bracket, "18-24");
+        assert_eq!(Demographics::derive(Some(day(2000, 3, 1)), Some("12345"), day(2025, 3, 1)).age_bracket, "25-34");
+        assert_eq!(Demographics::derive(None, Some("00000"), day(2025, 3, 1)).region, "Unknown");
+
+        let create = |demographics: bool| {
+            let mut input = PollInput {
+                title: "Park Renewal".to_string(),
+                question: "Renew the park?".to_string(),
+                options: vec!["Yes".to_string(), "No".to_string()],
+                is_public: true,
+                poll_type: Some("normal".to_string()),
+                ..Default::default()
+            };
+            input.settings.demographics = demographics;
+            input.settings.min_cell_size = Some(2);
+            input
+        };
+        let (poll_id, private_poll) = {
+            let mut pm = poll_manager.lock().await;
+            (pm.create_poll(create(true)).await.expect("Poll creation failed"), pm.create_poll(create(false)).await.expect("Poll creation failed"))
+        };
+
+        let ballots = [("12345", "Yes"), ("12345", "Yes"), ("12345", "Yes"), ("12345", "No"), ("12345", "No"), ("54321", "Yes")];
+        for (i, (zip_code, choice)) in ballots.iter().enumerate() {
+            let user = user_manager.register_user(UserRegistration {
+                name: format!("Resident {}", i),
+                email: format!("demo{}@a.com", i),
+                zip_code: zip_code.to_string(),
+                birth_date: "1990-06-15".to_string(),
+                password: "password".to_string(),
+            }).await.expect("User registration failed");
+            voting_integration.cast_vote(&poll_id, &user.voter_id, json!(choice)).await.expect("Vote failed");
+            voting_integration.cast_vote(&private_poll, &user.voter_id, json!(choice)).await.expect("Vote failed");
+        }
+
+        let results = voting_integration.get_poll_results(&poll_id).await.expect("Failed to get results");
+        let breakdown: DemographicBreakdown = serde_json::from_value(results["demographics"].clone()).expect("Breakdown should deserialize");
+        let cell = |option: &str, region: &str| breakdown.cells.iter().find(|c| c.option == option && c.region == region).unwrap();
+        assert_eq!(cell("No", "NY").votes, Some(2));
+        assert!(cell("Yes", "WI").suppressed, "A single voter's ballot must not be shown");
+        assert!(cell("Yes", "NY").suppressed, "The other Yes cell is suppressed so the total cannot reveal the hidden one");
+
+        let results = voting_integration.get_poll_results(&private_poll).await.expect("Failed to get results");
+        assert!(results.get("demographics").is_none(), "Breakdowns are only computed when a poll opts in");
+    }
+
+    #[tokio::test]
+    async fn test_voting_integration_cross_tab_enforces_minimum_cell_size() {
+        let pool = setup_test_pool().await.expect("Failed to set up test pool");
+        let user_manager = UserManager::new(pool.clone());
+        let vote_service = Arc::new(VoteService::new(pool.clone()));
+        let poll_manager = Arc::new(Mutex::new(PollManager::new(pool.clone())));
+        let voting_integration = VotingIntegration::new(poll_manager.clone(), vote_service.clone());
+
+        let poll_id = {
+            let mut pm = poll_manager.lock().await;
+            let mut input = PollInput {
+                title: "General Election".to_string(),
+                question: "Vote in every contest".to_string(),
+                options: vec![r#"{"presidency": ["A", "B"], "prop_1": ["Yes", "No"]}"#.to_string()],
+                is_public: true,
+                poll_type: Some("election".to_string()),
+                ..Default::default()
+            };
+            input.settings.min_cell_size = Some(2);
+            pm.create_poll(input).await.expect("Poll creation failed")
+        };
+
+        // A: 3 Yes, 3 No, 2 skipped the proposition; B: 3 Yes, 3 No, 1 skipped it
+        let mut ballots = Vec::new();
+        for (presidency, yes, no, skipped) in [("A", 3, 3, 2), ("B", 3, 3, 1)] {
+            ballots.extend((0..yes).map(|_| json!({"presidency": presidency, "prop_1": "Yes"})));
+            ballots.extend((0..no).map(|_| json!({"presidency": presidency, "prop_1": "No"})));
+            ballots.extend((0..skipped).map(|_| json!({"presidency": presidency})));
+        }
+        for (i, ballot) in ballots.iter().enumerate() {
+            let user = user_manager.register_user(UserRegistration {
+                name: format!("Voter {}", i),
+                email: format!("crosstab{}@a.com", i),
+                zip_code: "12345".to_string(),
+                birth_date: "1980-01-01".to_string(),
+                password: "password".to_string(),
+            }).await.expect("User registration failed");
+            voting_integration.cast_vote(&poll_id, &user.voter_id, ballot.clone()).await.expect("Vote failed");
+        }
+
+        let cross_tab = voting_integration.get_cross_tab(&poll_id, "presidency", "prop_1").await.expect("Cross-tab failed");
+        assert_eq!(cross_tab.cells.len(), 6);
+        let cell = |first: &str, second: &str| cross_tab.cells.iter().find(|c| c.first == first && c.second == second).unwrap();
+        assert!(cell("B", "(none)").suppressed && cell("B", "(none)").ballots.is_none(), "A lone ballot is suppressed");
+        assert_eq!((cell("A", "Yes").ballots, cell("B", "Yes").ballots), (Some(3), Some(3)));
+        // No row or column may hide exactly one cell, or its published total would reveal it
+        for line in ["A", "B", "Yes", "No", "(none)"] {
+            let hidden = cross_tab.cells.iter().filter(|c| (c.first == line || c.second == line) && c.suppressed).count();
+            assert_ne!(hidden, 1, "{} hides a single cell", line);
+        }
+
+        assert!(voting_integration.get_cross_tab(&poll_id, "presidency", "presidency").await.is_err());
+        assert!(voting_integration.get_cross_tab(&poll_id, "presidency", "senate").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_voting_integration_revote_and_reveal_after_pseudonym_rotation() {
+        let pool = setup_test_pool().await.expect("Failed to set up test pool");
+        let vote_service = Arc::new(VoteService::with_pseudonym_secret(pool.clone(), b"test-secret".to_vec()));
+        let poll_manager = Arc::new(Mutex::new(PollManager::new(pool.clone())));
+        let voting_integration = VotingIntegration::new(poll_manager.clone(), vote_service.clone());
+
+        let mut revote = PollInput {
+            title: "Revote".to_string(),
+            question: "Agree?".to_string(),
+            options: vec!["Yes".to_string(), "No".to_string()],
+            is_public: true,
+            poll_type: Some("normal".to_string()),
+            ..Default::default()
+        };
+        revote.settings.revote_until = Some(chrono::Utc::now() + chrono::Duration::hours(1));
+        let mut sealed = PollInput {
+            title: "Sealed".to_string(),
+            question: "Pick a colour".to_string(),
+            options: vec!["Red".to_string(), "Blue".to_string()],
+            is_public: true,
+            ..Default::default()
+        };
+        sealed.settings.closes_at = Some(chrono::Utc::now() + chrono::Duration::hours(1));
+        sealed.settings.reveal_until = Some(chrono::Utc::now() + chrono::Duration::hours(2));
+        let (revote_poll, sealed_poll) = {
+            let mut pm = poll_manager.lock().await;
+            (pm.create_poll(revote).await.expect("Poll creation failed"), pm.create_poll(sealed).await.expect("Poll creation failed"))
+        };
+
+        let cast = voting_integration.cast_vote(&revote_poll, "voter-a", json!({"choice": "Yes"})).await.expect("Vote failed");
+        let handle = cast.ballot_handle.expect("Revote polls issue a ballot handle");
+        let salt = "a-long-random-salt";
+        let commitment = commit_reveal::commit(&sealed_poll, &json!("Red"), salt);
+        let committed = voting_integration.cast_vote(&sealed_poll, "voter-a", json!({"commitment": commitment})).await
+            .expect("Commitment failed");
+        let sealed_handle = committed.ballot_handle.expect("Commitments issue a ballot handle");
+        let before = vote_service.voter_pseudonym(&sealed_poll, "voter-a").await.expect("Pseudonym failed");
+
+        voting_integration.rotate_pseudonyms(&revote_poll).await.expect("Rotation failed");
+        voting_integration.rotate_pseudonyms(&sealed_poll).await.expect("Rotation failed");
+        assert!(voting_integration.rotate_pseudonyms("no-such-poll").await.is_err());
+        let after = vote_service.voter_pseudonym(&sealed_poll, "voter-a").await.expect("Pseudonym failed");
+        assert_ne!(before, after, "Rotation replaces the key");
+        let entries = vote_service.get_chain_entries(&sealed_poll).await.expect("Entries failed");
+        assert!(entries[0].0.get("voter_id").is_none(), "Commitments carry no voter identifier");
+        assert!(entries[0].0.get("ballot_tag").is_some());
+
+        // Rotation leaves the chain alone, so tracker codes issued before it still resolve
+        let tracked = voting_integration.track_ballot(&revote_poll, &cast.tracker_code).await.expect("Ballot not found after rotation");
+        assert!(tracked.counted);
+        voting_integration.track_ballot(&sealed_poll, &committed.tracker_code).await.expect("Commitment not found after rotation");
+
+        // The revote still supersedes the ballot cast before the rotation
+        voting_integration.recast_vote(&revote_poll, "voter-a", &handle, json!({"choice": "No"})).await.expect("Revote failed");
+        let results = voting_integration.get_poll_results(&revote_poll).await.expect("Failed to get results");
+        assert_eq!(results["blockchain_results"], json!({"No": 1}));
+        assert_eq!(results["database_results"], json!({"No": 1}));
+
+        // The reveal still finds the commitment made before the rotation
+        {
+            let mut pm = poll_manager.lock().await;
+            let Some(Poll::Normal { metadata, .. }) = pm.polls.get_mut(&sealed_poll) else { panic!("Expected a normal poll") };
+            metadata.settings.closes_at = Some(chrono::Utc::now() - chrono::Duration::seconds(1));
+        }
+        let reveal = BallotReveal {
+            voter_id: "voter-a".to_string(),
+            vote: json!("Red"),
+            salt: salt.to_string(),
+            ballot_handle: sealed_handle,
+        };
+        voting_integration.reveal_vote(&sealed_poll, &reveal).await.expect("Reveal failed");
+        let results = voting_integration.get_poll_results(&sealed_poll).await.expect("Failed to get results");
+        assert_eq!(results["blockchain_results"], json!({"Red": 1}));
+    }
+
+    #[test]
+    fn test_pseudonym_secret_rejects_missing_and_placeholder_values() {
+        let previous = env::var(pseudonym::PSEUDONYM_SECRET_VAR).ok();
+
+        env::remove_var(pseudonym::PSEUDONYM_SECRET_VAR);
+        assert!(pseudonym::server_secret().is_err());
+        for placeholder in ["", "  ", "change-me", "CHANGE-ME", "changeme"] {
+            env::set_var(pseudonym::PSEUDONYM_SECRET_VAR, placeholder);
+            assert!(pseudonym::server_secret().is_err(), "accepted {:?}", placeholder);
+        }
+        env::set_var(pseudonym::PSEUDONYM_SECRET_VAR, "3f9c1d2e8a7b6c5d4e3f2a1b0c9d8e7f");
+        assert_eq!(pseudonym::server_secret().unwrap(), b"3f9c1d2e8a7b6c5d4e3f2a1b0c9d8e7f".to_vec());
+
+        match previous {
+            Some(secret) => env::set_var(pseudonym::PSEUDONYM_SECRET_VAR, secret),
+            None => env::remove_var(pseudonym::PSEUDONYM_SECRET_VAR),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_voting_integration_pseudonymous_voter_ids() {
+        let pool = setup_test_pool().await.expect("Failed to set up test pool");
+        let vote_service = Arc::new(VoteService::with_pseudonym_secret(pool.clone(), b"test-secret".to_vec()));
+        let poll_manager = Arc::new(Mutex::new(PollManager::new(pool.clone())));
+        let voting_integration = VotingIntegration::new(poll_manager.clone(), vote_service.clone());
+
+        let mut poll_ids = Vec::new();
+        for title in ["First", "Second"] {
+            let mut pm = poll_manager.lock().await;
+            poll_ids.push(pm.create_poll(PollInput {
+                title: title.to_string(),
+                question: "Agree?".to_string(),
+                options: vec!["Yes".to_string(), "No".to_string()],
+                is_public: true,
+                poll_type: Some("normal".to_string()),
+                ..Default::default()
+            }).await.expect("Poll creation failed"));
+        }
+
+        for poll_id in &poll_ids {
+            voting_integration.cast_vote(poll_id, "voter-a", json!({"choice": "Yes"})).await.expect("Vote failed");
+        }
+        let first = vote_service.voter_pseudonym(&poll_ids[0], "voter-a").await.expect("Pseudonym failed");
+        let second = vote_service.voter_pseudonym(&poll_ids[1], "voter-a").await.expect("Pseudonym failed");
+        assert_ne!(first, second, "Pseudonyms differ between polls");
+
+        {
+            let pm = poll_manager.lock().await;
+            let Some(Poll::Normal { blockchain, .. }) = pm.get_poll(&poll_ids[0]) else { panic!("Expected a normal poll") };
+            let chain = serde_json::to_string(&blockchain.chain).unwrap();
+            assert!(!chain.contains("voter-a") && !chain.contains(&first), "Neither the voter id nor their pseudonym reaches a ballot");
+            assert!(blockchain.chain[1].transactions[0].get("voter_id").is_none());
+        }
+
+        // Rotation replaces the key without touching the chain
+        let hash = {
+            let pm = poll_manager.lock().await;
+            let Some(Poll::Normal { blockchain, .. }) = pm.get_poll(&poll_ids[0]) else { panic!("Expected a normal poll") };
+            blockchain.chain[1].hash.clone()
+        };
+        voting_integration.rotate_pseudonyms(&poll_ids[0]).await.expect("Rotation failed");
+        let rotated = vote_service.voter_pseudonym(&poll_ids[0], "voter-a").await.expect("Pseudonym failed");
+        assert_ne!(rotated, first);
+        {
+            let pm = poll_manager.lock().await;
+            let Some(Poll::Normal { blockchain, .. }) = pm.get_poll(&poll_ids[0]) else { panic!("Expected a normal poll") };
+            assert!(blockchain.is_valid());
+            assert_eq!(blockchain.chain.len(), 2);
+            assert_eq!(blockchain.chain[1].hash, hash);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_voting_integration_anonymous_credentials() {
+        let pool = setup_test_pool().await.expect("Failed to set up test pool");
+        let vote_service = Arc::new(VoteService::with_pseudonym_secret(pool.clone(), b"test-secret".to_vec()));
+        let poll_manager = Arc::new(Mutex::new(PollManager::new(pool.clone())));
+        let voting_integration = VotingIntegration::new(poll_manager.clone(), vote_service.clone());
+
+        let mut poll = PollInput {
+            title: "Anonymous".to_string(),
+            question: "Agree?".to_string(),
+            options: vec!["Yes".to_string(), "No".to_string()],
+            is_public: true,
+            poll_type: Some("weighted".to_string()),
+            ..Default::default()
+        };
+        poll.settings.anonymous_credentials = true;
+        assert!(poll_manager.lock().await.create_poll(poll.clone()).await.is_err(), "Weighted polls cannot be anonymous");
+        poll.poll_type = Some("normal".to_string());
+        let poll_id = poll_manager.lock().await.create_poll(poll).await.expect("Poll creation failed");
+
+        assert!(voting_integration.cast_vote(&poll_id, "voter-a", json!({"choice": "Yes"})).await.is_err(),
+            "Anonymous polls do not take identified ballots");
+
+        // The voter blinds a token, has it signed and unblinds the signature
+        let key = voting_integration.get_credential_key(&poll_id).await.expect("Key failed");
+        let token = "c0ffee-token";
+        let (blinded, factor) = key.blind(&poll_id, token);
+        let issued = voting_integration.issue_credential(&poll_id, "voter-a", &crypto::to_hex(&blinded)).await
+            .expect("Issuance failed");
+        assert!(voting_integration.issue_credential(&poll_id, "voter-a", &crypto::to_hex(&blinded)).await.is_err(),
+            "A voter is issued one credential per poll");
+        let blind_signature = crypto::parse_hex(issued["blind_signature"].as_str().unwrap()).unwrap();
+        let signature = key.unblind(&blind_signature, &factor).expect("Unblinding failed");
+        let credential = Credential { token: token.to_string(), signature: crypto::to_hex(&signature) };
+        assert!(key.verify(&poll_id, &credential));
+
+        let forged = Credential { token: "other-token".to_string(), signature: credential.signature.clone() };
+        assert!(voting_integration.cast_anonymous_vote(&poll_id, &forged, json!({"choice": "No"})).await.is_err(),
+            "A signature only covers its own token");
+
+        let receipt = voting_integration.cast_anonymous_vote(&poll_id, &credential, json!({"choice": "Yes"})).await
+            .expect("Anonymous vote failed");
+        assert!(voting_integration.cast_anonymous_vote(&poll_id, &credential, json!({"choice": "No"})).await.is_err(),
+            "A credential can be spent once");
+
+        let pm = poll_manager.lock().await;
+        let Some(Poll::Normal { blockchain, .. }) = pm.get_poll(&poll_id) else { panic!("Expected a normal poll") };
+        assert_eq!(blockchain.chain.len(), 2);
+        let ballot = &blockchain.chain[1].transactions[0];
+        assert_eq!(ballot["nullifier"], json!(credential::nullifier(&poll_id, token)));
+        assert!(ballot.get("voter_id").is_none(), "Anonymous ballots carry no voter id");
+        assert_eq!(receipt.receipt, vote_service::ballot_receipt(ballot));
+        assert_eq!(receipt.tracker_code, tracker::tracker_code(&blockchain.chain[1].hash));
+    }
+
+    #[tokio::test]
+    async fn test_voting_integration_encrypted_tally() {
+        let pool = setup_test_pool().await.expect("Failed to set up test pool");
+        let vote_service = Arc::new(VoteService::with_pseudonym_secret(pool.clone(), b"test-secret".to_vec()));
+        let poll_manager = Arc::new(Mutex::new(PollManager::new(pool.clone())));
+        let voting_integration = VotingIntegration::new(poll_manager.clone(), vote_service.clone());
+
+        let mut poll = PollInput {
+            title: "Encrypted".to_string(),
+            question: "Agree?".to_string(),
+            options: vec!["Yes".to_string(), "No".to_string()],
+            is_public: true,
+            poll_type: Some("normal".to_string()),
+            ..Default::default()
+        };
+        poll.settings.encrypted = true;
+        assert!(poll_manager.lock().await.create_poll(poll.clone()).await.is_err(), "Encrypted polls need a closing time");
+        poll.settings.closes_at = Some(chrono::Utc::now() + chrono::Duration::hours(1));
+        let layout = EncryptedLayout::for_poll(&poll).expect("Layout failed");
+        let poll_id = poll_manager.lock().await.create_poll(poll).await.expect("Poll creation failed");
+
+        let key = voting_integration.get_encryption_key(&poll_id).await.expect("Key failed");
+        let ballot = |choice: &str| {
+            let choices = BTreeMap::from([("default".to_string(), vec![choice.to_string()])]);
+            layout.encrypt_ballot(&key, &choices).expect("Encryption failed")
+        };
+        for (voter, choice) in [("voter-a", "Yes"), ("voter-b", "No"), ("voter-c", "Yes")] {
+            voting_integration.cast_vote(&poll_id, voter, ballot(choice)).await.expect("Vote failed");
+        }
+        voting_integration.cast_vote(&poll_id, "voter-d", json!({"abstain": true})).await.expect("Abstention failed");
+        assert!(voting_integration.cast_vote(&poll_id, "voter-e", json!({"choice": "Yes"})).await.is_err(),
+            "Plaintext choices are rejected");
+        assert!(voting_integration.cast_vote(&poll_id, "voter-e", json!({"encrypted": {"Yes": key.encrypt(1)}})).await.is_err(),
+            "Every option needs a ciphertext");
+        assert!(voting_integration.cast_vote(&poll_id, "voter-e", json!({"encrypted": {"Yes": key.encrypt(1), "No": key.encrypt(0)}})).await.is_err(),
+            "Ciphertexts need proofs");
+        assert!(voting_integration.decrypt_tally(&poll_id).await.is_err(), "The tally stays encrypted until close");
+
+        // The published aggregate is the product of the ciphertexts on the chain
+        let published = voting_integration.get_encrypted_tally(&poll_id).await.expect("Tally failed");
+        assert_eq!(published["ballots"], json!(4));
+        {
+            let pm = poll_manager.lock().await;
+            let Some(Poll::Normal { blockchain, .. }) = pm.get_poll(&poll_id) else { panic!("Expected a normal poll") };
+            let mut yes = Ciphertext::identity();
+            for block in &blockchain.chain[1..] {
+                let transaction = &block.transactions[0];
+                assert!(transaction.get("choice").is_none());
+                if let Some(ciphertext) = transaction.pointer("/encrypted/Yes") {
+                    yes = yes.combine(&serde_json::from_value(ciphertext.clone()).unwrap());
+                }
+            }
+            assert_eq!(published["aggregates"]["default"]["Yes"], json!(yes));
+        }
+
+        // Close the poll
+        {
+            let mut pm = poll_manager.lock().await;
+            let Some(Poll::Normal { metadata, .. }) = pm.polls.get_mut(&poll_id) else { panic!("Expected a normal poll") };
+            metadata.settings.closes_at = Some(chrono::Utc::now() - chrono::Duration::seconds(1));
+        }
+        assert!(voting_integration.cast_vote(&poll_id, "voter-e", ballot("No")).await.is_err(), "Closed polls take no ballots");
+
+        let decrypted = voting_integration.decrypt_tally(&poll_id).await.expect("Decryption failed");
+        assert_eq!(decrypted.counts["default"]["Yes"], 2);
+        assert_eq!(decrypted.counts["default"]["No"], 1);
+        assert_eq!(json!(decrypted.aggregates), published["aggregates"], "The published aggregates were decrypted");
+        assert!(voting_integration.decrypt_tally(&poll_id).await.is_err(), "The tally is decrypted once");
+
+        let results = voting_integration.get_poll_results(&poll_id).await.expect("Results failed");
+        assert_eq!(results["blockchain_results"], json!({"Yes": 2, "No": 1}));
+        assert_eq!(results["encrypted_tally"]["ballots"], json!(4));
+        assert!(results.get("turnout").is_none() && results.get("outcome").is_none(), "Nothing is counted from encrypted ballots");
+
+        let pm = poll_manager.lock().await;
+        let verified = encrypted_tally::verify_chain(pm.get_poll(&poll_id).unwrap(), &key).expect("Verification failed");
+        assert_eq!(json!(verified.aggregates), published["aggregates"]);
+    }
+
+    #[tokio::test]
+    async fn test_voting_integration_trustee_threshold_decryption() {
+        let pool = setup_test_pool().await.expect("Failed to set up test pool");
+        let vote_service = Arc::new(VoteService::with_pseudonym_secret(pool.clone(), b"test-secret".to_vec()));
+        let poll_manager = Arc::new(Mutex::new(PollManager::new(pool.clone())));
+        let voting_integration = VotingIntegration::new(poll_manager.clone(), vote_service.clone());
+
+        let names = ["alice", "bob", "carol"];
+        let mut poll = PollInput {
+            title: "Trustees".to_string(),
+            question: "Pick a colour".to_string(),
+            options: vec![r#"{"Colour": ["Red", "Blue"]}"#.to_string()],
+            is_public: true,
+            poll_type: Some("election".to_string()),
+            ..Default::default()
+        };
+        poll.settings.encrypted = true;
+        poll.settings.closes_at = Some(chrono::Utc::now() + chrono::Duration::hours(1));
+        assert!(poll_manager.lock().await.create_poll(poll.clone()).await.is_err(), "Encrypted elections need trustees");
+        poll.options = vec!["Red".to_string(), "Blue".to_string()];
+        poll.poll_type = None;
+        poll.settings.trustees = Some(TrusteeConfig { trustees: names.iter().map(|n| n.to_string()).collect(), threshold: 2 });
+        let layout = EncryptedLayout::for_poll(&poll).expect("Layout failed");
+        let poll_id = poll_manager.lock().await.create_poll(poll).await.expect("Poll creation failed");
+
+        // Key ceremony: commitments, then shares relayed through the server
+        let ceremony = voting_integration.get_ceremony(&poll_id).await.expect("Ceremony failed");
+        let mut states: Vec<TrusteeState> = ceremony.trustees.iter()
+            .map(|t| TrusteeState::new(&poll_id, &t.trustee, t.index, ceremony.threshold))
+            .collect();
+        for state in &states {
+            voting_integration.publish_trustee_commitments(&poll_id, &state.trustee, &state.commitments()).await
+                .expect("Commitments failed");
+        }
+        assert!(voting_integration.get_encryption_key(&poll_id).await.is_err(), "No key until every share is out");
+        let ceremony = voting_integration.get_ceremony(&poll_id).await.expect("Ceremony failed");
+        for state in &states {
+            let shares = state.shares_for(&ceremony).expect("Shares failed");
+            voting_integration.distribute_trustee_shares(&poll_id, &state.trustee, &shares).await.expect("Distribution failed");
+        }
+        let ceremony = voting_integration.get_ceremony(&poll_id).await.expect("Ceremony failed");
+        for state in states.iter_mut() {
+            let shares = voting_integration.get_trustee_shares(&poll_id, &state.trustee).await.expect("Shares failed");
+            state.receive_shares(&ceremony, &shares).expect("Shares do not match commitments");
+        }
+        let key = voting_integration.get_encryption_key(&poll_id).await.expect("Key failed");
+        assert_eq!(Some(key.clone()), ceremony.election_key);
+
+        for (voter, choice) in [("voter-a", "Red"), ("voter-b", "Blue"), ("voter-c", "Red")] {
+            let choices = BTreeMap::from([("default".to_string(), vec![choice.to_string()])]);
+            let ballot = layout.encrypt_ballot(&key, &choices).expect("Encryption failed");
+            voting_integration.cast_vote(&poll_id, voter, ballot).await.expect("Vote failed");
+        }
+        {
+            let mut pm = poll_manager.lock().await;
+            let Some(Poll::Normal { metadata, .. }) = pm.polls.get_mut(&poll_id) else { panic!("Expected a normal poll") };
+            metadata.settings.closes_at = Some(chrono::Utc::now() - chrono::Duration::seconds(1));
+        }
+
+        let tally = voting_integration.get_encrypted_tally(&poll_id).await.expect("Tally failed");
+        let aggregates = serde_json::from_value(tally["aggregates"].clone()).unwrap();
+        let alice = states[0].partially_decrypt(&aggregates).expect("Partial decryption failed");
+        voting_integration.submit_partial_decryption(&poll_id, "alice", &alice).await.expect("Submission failed");
+        assert!(voting_integration.submit_partial_decryption(&poll_id, "carol", &alice).await.is_err(),
+            "A partial decryption is checked against the submitting trustee's key");
+        assert!(voting_integration.decrypt_tally(&poll_id).await.is_err(), "One trustee cannot decrypt alone");
+
+        let carol = states[2].partially_decrypt(&aggregates).expect("Partial decryption failed");
+        voting_integration.submit_partial_decryption(&poll_id, "carol", &carol).await.expect("Submission failed");
+        let decrypted = voting_integration.decrypt_tally(&poll_id).await.expect("Decryption failed");
+        assert_eq!(decrypted.counts["default"]["Red"], 2);
+        assert_eq!(decrypted.counts["default"]["Blue"], 1);
+        assert_eq!(decrypted.partial_decryptions.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_voting_integration_mixnet_decrypts_write_ins_anonymously() {
+        let pool = setup_test_pool().await.expect("Failed to set up test pool");
+        let user_manager = UserManager::new(pool.clone());
+        let vote_service = Arc::new(VoteService::new(pool.clone()));
+        let poll_manager = Arc::new(Mutex::new(PollManager::new(pool.clone())));
+        let voting_integration = VotingIntegration::new(poll_manager.clone(), vote_service.clone());
+
+        let mut poll = PollInput {
+            title: "Mixed Election".to_string(),
+            question: "Choose a mayor".to_string(),
+            options: vec![r#"{"mayor": {"candidates": ["Alice", "Bob"], "write_in_slots": 1}, "measure": {"candidates": ["Yes", "No"]}}"#.to_string()],
+            is_public: true,
+            poll_type: Some("election".to_string()),
+            ..Default::default()
+        };
+        poll.settings.encrypted = true;
+        poll.settings.mixnet = true;
+        poll.settings.closes_at = Some(chrono::Utc::now() + chrono::Duration::hours(1));
+        poll.settings.trustees = Some(TrusteeConfig { trustees: vec!["alice".to_string(), "bob".to_string()], threshold: 2 });
+        let poll_id = poll_manager.lock().await.create_poll(poll).await.expect("Poll creation failed");
+
+        let ceremony = voting_integration.get_ceremony(&poll_id).await.expect("Ceremony failed");
+        let mut states: Vec<TrusteeState> = ceremony.trustees.iter()
+            .map(|t| TrusteeState::new(&poll_id, &t.trustee, t.index, ceremony.threshold))
+            .collect();
+        for state in &states {
+            voting_integration.publish_trustee_commitments(&poll_id, &state.trustee, &state.commitments()).await
+                .expect("Commitments failed");
+        }
+        let ceremony = voting_integration.get_ceremony(&poll_id).await.expect("Ceremony failed");
+        for state in &states {
+            let shares = state.shares_for(&ceremony).expect("Shares failed");
+            voting_integration.distribute_trustee_shares(&poll_id, &state.trustee, &shares).await.expect("Distribution failed");
+        }
+        let ceremony = voting_integration.get_ceremony(&poll_id).await.expect("Ceremony failed");
+        for state in states.iter_mut() {
+            let shares = voting_integration.get_trustee_shares(&poll_id, &state.trustee).await.expect("Shares failed");
+            state.receive_shares(&ceremony, &shares).expect("Shares do not match commitments");
+        }
+        let key = voting_integration.get_encryption_key(&poll_id).await.expect("Key failed");
+
+        let ballots = [json!({"mayor": "Alice"}), json!({"mayor": {"write_in": " Carol "}}), json!({"mayor": "Zed"})];
+        for (i, selections) in ballots.iter().enumerate() {
+            let user = user_manager.register_user(UserRegistration {
+                name: format!("Resident {}", i),
+                email: format!("mixed{}@a.com", i),
+                zip_code: "12345".to_string(),
+                birth_date: "1980-01-01".to_string(),
+                password: "password".to_string(),
+            }).await.expect("User registration failed");
+            assert!(voting_integration.cast_vote(&poll_id, &user.voter_id, selections.clone()).await.is_err(),
+                "Mixed polls only accept sealed ballots");
+            let sealed = mixnet::seal(&key, selections).expect("Sealing failed");
+            voting_integration.cast_vote(&poll_id, &user.voter_id, sealed).await.expect("Vote failed");
+        }
+        assert!(voting_integration.mix_ballots(&poll_id).await.is_err(), "Ballots are mixed once the poll closes");
+        {
+            let mut pm = poll_manager.lock().await;
+            let Some(Poll::Election { metadata, .. }) = pm.polls.get_mut(&poll_id) else { panic!("Expected an election") };
+            metadata.settings.closes_at = Some(chrono::Utc::now() - chrono::Duration::seconds(1));
+        }
+        assert!(voting_integration.get_encrypted_tally(&poll_id).await.is_err(), "Mixed polls have no homomorphic tally");
+        let sealed_results = voting_integration.get_poll_results(&poll_id).await.expect("Failed to get results");
+        assert_eq!(sealed_results["blockchain_results"], json!({}));
+        for field in ["turnout", "contest_turnout", "contest_outcomes", "write_ins", "stv_results"] {
+            assert!(sealed_results.get(field).is_none(), "Sealed ballots yield no {}", field);
+        }
+        assert!(voting_integration.get_cross_tab(&poll_id, "mayor", "measure").await.is_err(),
+            "Sealed ballots cannot be cross-tabulated");
+
+        let first = voting_integration.mix_ballots(&poll_id).await.expect("Mix failed");
+        let second = voting_integration.mix_ballots(&poll_id).await.expect("Mix failed");
+        assert_eq!((first.pass, second.pass), (0, 1));
+        let mixed = {
+            let pm = poll_manager.lock().await;
+            mixnet::verify_chain(pm.get_poll(&poll_id).unwrap(), &key, &ceremony).expect("Mix does not verify")
+        };
+        assert_eq!(mixed, second.ballots);
+
+        let alice = states[0].partially_decrypt_ballots(&mixed).expect("Partial decryption failed");
+        assert!(voting_integration.submit_mix_decryption(&poll_id, "bob", &alice).await.is_err(),
+            "A partial decryption is checked against the submitting trustee's key");
+        voting_integration.submit_mix_decryption(&poll_id, "alice", &alice).await.expect("Submission failed");
+        assert!(voting_integration.decrypt_mix(&poll_id).await.is_err(), "One trustee cannot decrypt alone");
+        let bob = states[1].partially_decrypt_ballots(&mixed).expect("Partial decryption failed");
+        voting_integration.submit_mix_decryption(&poll_id, "bob", &bob).await.expect("Submission failed");
+        let decrypted = voting_integration.decrypt_mix(&poll_id).await.expect("Decryption failed");
+        assert_eq!(decrypted.ballots.len(), 3);
+        assert!(decrypted.ballots.iter().all(|ballot| ballot.get("voter_id").is_none()), "Decrypted ballots are unlinked from voters");
+        assert!(voting_integration.mix_ballots(&poll_id).await.is_err(), "Decrypted ballots are not mixed again");
+
+        let adjudication = WriteInAdjudication {
+            contest: "mayor".to_string(),
+            write_in: "carol".to_string(),
+            resolution: "Bob".to_string(),
+            official: "clerk".to_string(),
+        };
+        voting_integration.adjudicate_write_in(&poll_id, &adjudication).await.expect("Adjudication failed");
+        let results = voting_integration.get_poll_results(&poll_id).await.expect("Failed to get results");
+        assert_eq!(results["blockchain_results"]["mayor"], json!({"Alice": 1, "Bob": 1}));
+        assert_eq!(results["turnout"]["spoiled"], 1, "A sealed ballot that breaks the rules is spoiled");
+        assert_eq!(results["contest_turnout"]["measure"]["ballots_cast"], json!(3));
+        assert!(results["write_ins"]["mayor"].get("carol").is_some());
+        let cross_tab = voting_integration.get_cross_tab(&poll_id, "mayor", "measure").await.expect("Cross-tab failed");
+        assert_eq!(cross_tab.first_contest, "mayor");
+        let pm = poll_manager.lock().await;
+        let poll = pm.get_poll(&poll_id).unwrap();
+        assert!(mixnet::verify_chain(poll, &key, &ceremony).is_ok());
+
+        // A chain whose decryption was tampered with, its blocks rehashed, must not verify
+        let Poll::Election { metadata, blockchain } = poll else { panic!("Expected an election") };
+        let rebuilt = |tamper: &dyn Fn(&mut serde_json::Value)| {
+            let mut forged = ElectionBlockchain::new();
+            for block in blockchain.chain.iter().skip(1) {
+                let mut transactions = block.transactions.clone();
+                if transactions[RECORD_TYPE_FIELD] == mixnet::MIX_DECRYPTION_RECORD {
+                    tamper(&mut transactions);
+                }
+                forged.add_vote_at(transactions, block.timestamp).expect("Rebuild failed");
+            }
+            Poll::Election { metadata: metadata.clone(), blockchain: forged }
+        };
+        assert!(mixnet::verify_chain(&rebuilt(&|_| {}), &key, &ceremony).is_ok());
+        let swapped = rebuilt(&|record| record["ballots"].as_array_mut().unwrap().swap(0, 1));
+        assert!(mixnet::verify_chain(&swapped, &key, &ceremony).is_err(), "Decrypted ballots must match the mix in order");
+        let recounted = rebuilt(&|record| record["ballots"][2] = record["ballots"][0].clone());
+        assert!(mixnet::verify_chain(&recounted, &key, &ceremony).is_err(), "A decrypted ballot cannot be replaced");
+        let misattributed = rebuilt(&|record| {
+            let alice = record["partial_decryptions"]["alice"].clone();
+            record["partial_decryptions"]["bob"] = alice;
+        });
+        assert!(mixnet::verify_chain(&misattributed, &key, &ceremony).is_err(),
+            "Each partial decryption is checked against its trustee's verification key");
+    }
+
+    #[tokio::test]
+    async fn test_voting_integration_commit_reveal_counts_only_matching_reveals() {
+        let pool = setup_test_pool().await.expect("Failed to set up test pool");
+        let vote_service = Arc::new(VoteService::new(pool.clone()));
+        let poll_manager = Arc::new(Mutex::new(PollManager::new(pool.clone())));
+        let voting_integration = VotingIntegration::new(poll_manager.clone(), vote_service.clone());
+
+        let mut poll = PollInput {
+            title: "Sealed Poll".to_string(),
+            question: "Pick a colour".to_string(),
+            options: vec!["Red".to_string(), "Blue".to_string()],
+            is_public: true,
+            ..Default::default()
+        };
+        poll.settings.closes_at = Some(chrono::Utc::now() + chrono::Duration::hours(1));
+        poll.settings.reveal_until = poll.settings.closes_at;
+        assert!(poll_manager.lock().await.create_poll(poll.clone()).await.is_err(), "The reveal window follows the voting window");
+        poll.settings.reveal_until = Some(chrono::Utc::now() + chrono::Duration::hours(2));
+        let poll_id = poll_manager.lock().await.create_poll(poll).await.expect("Poll creation failed");
+
+        let salt = "a-long-random-salt";
+        let ballots = [("voter-a", json!("Red")), ("voter-b", json!("Blue")), ("voter-c", json!("Blue"))];
+        assert!(voting_integration.cast_vote(&poll_id, "voter-a", json!("Red")).await.is_err(), "Only commitments are cast");
+        let mut handles = BTreeMap::new();
+        for (voter, vote) in &ballots {
+            let commitment = commit_reveal::commit(&poll_id, vote, salt);
+            let cast = voting_integration.cast_vote(&poll_id, voter, json!({"commitment": commitment})).await.expect("Commitment failed");
+            handles.insert(voter.to_string(), cast.ballot_handle.expect("Commitments issue a ballot handle"));
+        }
+        assert!(voting_integration.cast_vote(&poll_id, "voter-a", json!({"commitment": "0".repeat(64)})).await.is_err(),
+            "Each voter commits once");
+        {
+            let pm = poll_manager.lock().await;
+            let Some(Poll::Normal { blockchain, .. }) = pm.get_poll(&poll_id) else { panic!("Expected a normal poll") };
+            let block = &blockchain.chain[1];
+            assert!(block.transactions.iter().all(|tx| tx.get("voter_id").is_none()), "Commitments carry no voter identifier");
+            assert_eq!(block.timestamp % 3_600_000, 0, "Commitments are timestamped to the hour");
+        }
+        let reveal = |voter: &str, vote: serde_json::Value, salt: &str| BallotReveal {
+            voter_id: voter.to_string(),
+            vote,
+            salt: salt.to_string(),
+            ballot_handle: handles.get(voter).cloned().unwrap_or_default(),
+        };
+        assert!(voting_integration.reveal_vote(&poll_id, &reveal("voter-a", json!("Red"), salt)).await.is_err(),
+            "Ballots are revealed after the poll closes");
+        let results = voting_integration.get_poll_results(&poll_id).await.expect("Failed to get results");
+        assert_eq!(results["blockchain_results"], json!({}), "Commitments reveal nothing");
+        {
+            let mut pm = poll_manager.lock().await;
+            let Some(Poll::Normal { metadata, .. }) = pm.polls.get_mut(&poll_id) else { panic!("Expected a normal poll") };
+            metadata.settings.closes_at = Some(chrono::Utc::now() - chrono::Duration::seconds(1));
+        }
+
+        voting_integration.reveal_vote(&poll_id, &reveal("voter-a", json!("Red"), salt)).await.expect("Reveal failed");
+        assert!(voting_integration.reveal_vote(&poll_id, &reveal("voter-a", json!("Red"), salt)).await.is_err(),
+            "A commitment is revealed once");
+        assert!(voting_integration.reveal_vote(&poll_id, &reveal("voter-b", json!("Red"), salt)).await.is_err(),
+            "The revealed ballot must match the commitment");
+        assert!(voting_integration.reveal_vote(&poll_id, &reveal("voter-b", json!("Blue"), "another-salt-value")).await.is_err(),
+            "The salt must match the commitment");
+        let mut misdirected = reveal("voter-b", json!("Blue"), salt);
+        misdirected.ballot_handle = handles["voter-c"].clone();
+        assert!(voting_integration.reveal_vote(&poll_id, &misdirected).await.is_err(),
+            "A ballot handle opens only its own voter's commitment");
+        voting_integration.reveal_vote(&poll_id, &reveal("voter-b", json!("Blue"), salt)).await.expect("Reveal failed");
+        assert!(voting_integration.reveal_vote(&poll_id, &reveal("voter-d", json!("Blue"), salt)).await.is_err(),
+            "Only committed voters can reveal");
+
+        let results = voting_integration.get_poll_results(&poll_id).await.expect("Failed to get results");
+        assert_eq!(results["blockchain_results"], json!({"Red": 1, "Blue": 1}), "voter-c never revealed");
+        assert_eq!(results["turnout"]["ballots_cast"], json!(2));
+        assert_eq!(results["database_results"], json!({"Red": 1, "Blue": 1}));
+    }
+
+    #[tokio::test]
+    async fn test_voting_integration_reveal_replaces_client_jurisdiction() {
+        let pool = setup_test_pool().await.expect("Failed to set up test pool");
+        let user_manager = UserManager::new(pool.clone());
+        let vote_service = Arc::new(VoteService::new(pool.clone()));
+        let poll_manager = Arc::new(Mutex::new(PollManager::new(pool.clone())));
+        let voting_integration = VotingIntegration::new(poll_manager.clone(), vote_service.clone());
+
+        let mut poll = PollInput {
+            title: "Sealed Election".to_string(),
+            question: "Vote in the contests on your ballot".to_string(),
+            options: vec![r#"{"president": ["Candidate A", "Candidate B"]}"#.to_string()],
+            is_public: true,
+            poll_type: Some("election".to_string()),
+            ..Default::default()
+        };
+        poll.settings.closes_at = Some(chrono::Utc::now() + chrono::Duration::hours(1));
+        poll.settings.reveal_until = Some(chrono::Utc::now() + chrono::Duration::hours(2));
+        let poll_id = poll_manager.lock().await.create_poll(poll).await.expect("Poll creation failed");
+        let voter = user_manager.register_user(UserRegistration {
+            name: "Sealed Voter".to_string(),
+            email: "sealed@a.com".to_string(),
+            zip_code: "12345".to_string(),
+            birth_date: "1980-01-01".to_string(),
+            password: "password".to_string(),
+        }).await.expect("User registration failed");
+
+        let salt = "a-long-random-salt";
+        let vote = json!({"president": "Candidate A", "state": "CA", "district": "CA-12"});
+        let commitment = commit_reveal::commit(&poll_id, &vote, salt);
+        let ballot_handle = voting_integration.cast_vote(&poll_id, &voter.voter_id, json!({"commitment": commitment})).await
+            .expect("Commitment failed")
+            .ballot_handle.expect("Commitments issue a ballot handle");
+        {
+            let mut pm = poll_manager.lock().await;
+            let Some(Poll::Election { metadata, .. }) = pm.polls.get_mut(&poll_id) else { panic!("Expected an election") };
+            metadata.settings.closes_at = Some(chrono::Utc::now() - chrono::Duration::seconds(1));
+        }
+        let reveal = BallotReveal { voter_id: voter.voter_id.clone(), vote, salt: salt.to_string(), ballot_handle };
+        voting_integration.reveal_vote(&poll_id, &reveal).await.expect("Reveal failed");
+
+        let pm = poll_manager.lock().await;
+        let Some(Poll::Election { blockchain, .. }) = pm.get_poll(&poll_id) else { panic!("Expected an election poll") };
+        let revealed = &blockchain.chain.last().unwrap().transactions;
+        assert_eq!(revealed["president"], json!("Candidate A"));
+        assert_eq!(revealed["state"], json!("NY"), "The client-sent state is replaced");
+        assert_ne!(revealed["district"], json!("CA-12"), "The client-sent district is dropped");
+        assert!(!blockchain.get_vote_counts_by_state().contains_key("CA"));
+    }
+
+    #[tokio::test]
+    async fn test_voting_integration_challenged_ballot_is_opened_and_spoiled() {
+        let pool = setup_test_pool().await.expect("Failed to set up test pool");
+        let vote_service = Arc::new(VoteService::with_pseudonym_secret(pool.clone(), b"test-secret".to_vec()));
+        let poll_manager = Arc::new(Mutex::new(PollManager::new(pool.clone())));
+        let voting_integration = VotingIntegration::new(poll_manager.clone(), vote_service.clone());
+
+        let mut poll = PollInput {
+            title: "Audited".to_string(),
+            question: "Agree?".to_string(),
+            options: vec!["Yes".to_string(), "No".to_string()],
+            is_public: true,
+            ..Default::default()
+        };
+        poll.settings.encrypted = true;
+        poll.settings.closes_at = Some(chrono::Utc::now() + chrono::Duration::hours(1));
+        let metadata = poll.clone();
+        let poll_id = poll_manager.lock().await.create_poll(poll).await.expect("Poll creation failed");
+        let key = voting_integration.get_encryption_key(&poll_id).await.expect("Key failed");
+
+        assert!(voting_integration.prepare_ballot(&poll_id, "voter-a", &json!("Maybe")).await.is_err(), "Only options can be encrypted");
+        let challenged = voting_integration.prepare_ballot(&poll_id, "voter-a", &json!("Yes")).await.expect("Preparation failed");
+        assert_eq!(challenged.tracking_code, challenge::tracking_code(&challenged.ballot));
+        let opened = voting_integration.challenge_ballot(&poll_id, "voter-a", &challenged.tracking_code).await.expect("Challenge failed");
+        assert_eq!(opened.ballot, challenged.ballot);
+        assert_eq!(challenge::verify(&metadata, &key, &opened), Ok(json!({"default": ["Yes"]})), "The ballot encoded the voter's choice");
+        let mut forged = opened.clone();
+        if let BallotOpening::Selections(openings) = &mut forged.opening {
+            let yes = openings.get_mut("default").and_then(|options| options.get_mut("Yes")).unwrap();
+            yes.m = 0;
+        }
+        assert!(challenge::verify(&metadata, &key, &forged).is_err(), "An opening must re-encrypt to the ciphertexts");
+        assert!(voting_integration.cast_prepared_ballot(&poll_id, "voter-a", &challenged.tracking_code).await.is_err(),
+            "A challenged ballot cannot be cast");
+
+        let prepared = voting_integration.prepare_ballot(&poll_id, "voter-a", &json!("No")).await.expect("Preparation failed");
+        assert!(voting_integration.cast_prepared_ballot(&poll_id, "voter-b", &prepared.tracking_code).await.is_err(),
+            "Only the voter a ballot was prepared for can cast it");
+        voting_integration.cast_prepared_ballot(&poll_id, "voter-a", &prepared.tracking_code).await.expect("Cast failed");
+        assert!(voting_integration.challenge_ballot(&poll_id, "voter-a", &prepared.tracking_code).await.is_err(),
+            "A cast ballot cannot be challenged");
+
+        let late = voting_integration.prepare_ballot(&poll_id, "voter-b", &json!("Yes")).await.expect("Preparation failed");
+        {
+            let mut pm = poll_manager.lock().await;
+            let Some(Poll::Normal { metadata, .. }) = pm.polls.get_mut(&poll_id) else { panic!("Expected a normal poll") };
+            metadata.settings.closes_at = Some(chrono::Utc::now() - chrono::Duration::seconds(1));
+        }
+        assert!(voting_integration.challenge_ballot(&poll_id, "voter-b", &late.tracking_code).await.is_err(),
+            "A ballot cannot be challenged once the poll has closed");
+        let decrypted = voting_integration.decrypt_tally(&poll_id).await.expect("Decryption failed");
+        assert_eq!(decrypted.ballots, 1, "The challenged ballot is not counted");
+        assert_eq!(decrypted.counts["default"]["No"], 1);
+        let pm = poll_manager.lock().await;
+        assert_eq!(challenge::verify_challenges(pm.get_poll(&poll_id).unwrap(), &key), Ok(1));
+    }
+
+    #[tokio::test]
+    async fn test_voting_integration_tracker_code_confirms_counted_ballot() {
+        let pool = setup_test_pool().await.expect("Failed to set up test pool");
+        let vote_service = Arc::new(VoteService::with_pseudonym_secret(pool.clone(), b"test-secret".to_vec()));
+        let poll_manager = Arc::new(Mutex::new(PollManager::new(pool.clone())));
+        let voting_integration = VotingIntegration::new(poll_manager.clone(), vote_service.clone());
+
+        let mut poll = PollInput {
+            title: "Tracked".to_string(),
+            question: "Agree?".to_string(),
+            options: vec!["Yes".to_string(), "No".to_string()],
+            is_public: true,
+            poll_type: Some("normal".to_string()),
+            ..Default::default()
+        };
+        poll.settings.revote_until = Some(chrono::Utc::now() + chrono::Duration::hours(1));
+        let poll_id = poll_manager.lock().await.create_poll(poll).await.expect("Poll creation failed");
+
+        let first = voting_integration.cast_vote(&poll_id, "voter-a", json!({"choice": "Yes"})).await.expect("Vote failed");
+        let abstained = voting_integration.cast_vote(&poll_id, "voter-b", json!({"abstain": true})).await.expect("Vote failed").tracker_code;
+        let handle = first.ballot_handle.expect("Revote polls issue a ballot handle");
+        let recast = voting_integration.recast_vote(&poll_id, "voter-a", &handle, json!({"choice": "No"})).await.expect("Revote failed").tracker_code;
+        let first = first.tracker_code;
+        assert_eq!(first.split('-').count(), tracker::TRACKER_CODE_WORDS);
+        assert_ne!(first, recast, "Every ballot gets its own code");
+        {
+            let pm = poll_manager.lock().await;
+            let Some(Poll::Normal { blockchain, .. }) = pm.get_poll(&poll_id) else { panic!("Expected a normal poll") };
+            assert_eq!(first, tracker::tracker_code(&blockchain.chain[1].hash), "The code is derived from the block hash");
+        }
+
+        let typed = recast.to_uppercase().replace('-', " ");
+        let tracked = voting_integration.track_ballot(&poll_id, &typed).await.expect("Lookup failed");
+        assert_eq!(tracked.tracker_code, recast, "Codes are read regardless of case and separators");
+        assert_eq!((tracked.entry, tracked.block_index), (TrackedEntry::Ballot, 3));
+        assert!(tracked.counted && !tracked.superseded);
+        assert!(!tracked.tally_final, "The poll has not closed");
+        let tracked_json = serde_json::to_value(&tracked).unwrap();
+        assert!(tracked_json.get("voter_id").is_none() && !tracked_json.to_string().contains("No"),
+            "The tracker reveals neither the voter nor their choice");
+
+        let replaced = voting_integration.track_ballot(&poll_id, &first).await.expect("Lookup failed");
+        assert!(replaced.superseded && !replaced.counted, "A recast ballot replaces the earlier one");
+        let abstention = voting_integration.track_ballot(&poll_id, &abstained).await.expect("Lookup failed");
+        assert_eq!(abstention.ballot_status, Some(BallotStatus::Abstain));
+        assert!(!abstention.counted, "Abstentions are on the chain but not tallied");
+
+        assert!(tracker::parse_tracker_code("acid-acorn-actor").is_err(), "Codes have a fixed number of words");
+        assert!(voting_integration.track_ballot(&poll_id, "acid-acid-acid-acid-acid-xylophone").await.is_err());
+        assert!(voting_integration.track_ballot(&poll_id, "acid-acid-acid-acid-acid-acid").await.is_err(),
+            "Codes that match no ballot are not found");
+    }
 }
@@ -81,14 +81,13 @@ export const castVote = async (voteData) => {
   }
 };
 
-export const verifyVote = async (pollId, voterId) =>
-  api.get(`/vote/${pollId}/${voterId}/verify`).then((res) => res.data);
+export const trackBallot = async (pollId, trackerCode) =>
+  api.get(`/poll/${pollId}/tracker/${trackerCode}`).then((res) => res.data);
 
 export const getPollResults = async (pollId) =>
   api.get(`/poll/${pollId}/results`).then((res) => res.data);
 
-// Legacy verification method (maintained for backward compatibility)
-export const getVoteVerification = async (pollId, voterId) =>
-  api.get(`/poll/${pollId}/verify_vote/${voterId}`).then((res) => res.data);
+export const getBallotStyle = async (pollId, voterId) =>
+  api.get(`/poll/${pollId}/ballot_style/${voterId}`).then((res) => res.data);
 
 export default api;
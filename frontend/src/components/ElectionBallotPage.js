@@ -12,7 +12,6 @@ import {
   List,
   ListItem,
   ListItemText,
-  TextField,
   Divider,
   Card,
   CardContent
@@ -22,7 +21,8 @@ import {
   castVote,
   getBlockchain,
   checkValidity,
-  getVoteCounts
+  getVoteCounts,
+  getBallotStyle
 } from "../api/api";
 import { VoterContext } from "../context/VoterContext";
 import StateResultsMap from "./StateResultsMap";
@@ -47,8 +47,8 @@ const ElectionBallotPage = ({ pollId = "election" }) => {
   const [showMap, setShowMap] = useState(false);
   const [showBlockchain, setShowBlockchain] = useState(false);
 
-  // A "state" text, so user can pick what state they're voting from
-  const [voterState, setVoterState] = useState("");
+  // The contests this voter may vote in, derived by the server from their registered zip code
+  const [ballotStyle, setBallotStyle] = useState(null);
 
   // Handle a user selecting a candidate in a given "contest"
   const handleVoteChange = (contest, choice) => {
@@ -103,25 +103,15 @@ const ElectionBallotPage = ({ pollId = "election" }) => {
       setError("You have not selected any choices.");
       return;
     }
-    if (!voterState.trim()) {
-      setError("Please enter the state code (e.g. CA, NY).");
-      return;
-    }
 
     setLoading(true);
     setError(null);
     setSuccess(null);
 
     try {
-      // Simply combine the selected votes with the state information
+      // The server stamps the voter's state and district from their registration
       // NO redundant "candidate" field is added
-      const finalVoteData = {
-        // Include all contest-specific votes
-        ...selectedVotes,
-        
-        // Add the state information
-        state: voterState.trim().toUpperCase()
-      };
+      const finalVoteData = { ...selectedVotes };
 
       await castVote({
         poll_id: pollId,
@@ -202,6 +192,23 @@ const ElectionBallotPage = ({ pollId = "election" }) => {
     fetchElection();
   }, [pollId, fetchVoteCounts]);
 
+  // Load the voter's ballot style
+  useEffect(() => {
+    if (!pollId || !voter?.voterId) {
+      return;
+    }
+    getBallotStyle(pollId, voter.voterId)
+      .then(setBallotStyle)
+      .catch((err) => console.error("Error loading ballot style:", err));
+  }, [pollId, voter]);
+
+  // Contests shown on this voter's ballot
+  const ballotContests = election
+    ? Object.keys(election.options).filter(
+        (contest) => !ballotStyle || ballotStyle.contests.includes(contest)
+      )
+    : [];
+
   return (
     <Paper elevation={3} sx={{ maxWidth: 800, margin: "auto", padding: 4, mt: 4, mb: 4 }}>
       <Typography variant="h4" align="center" gutterBottom>
@@ -290,18 +297,15 @@ const ElectionBallotPage = ({ pollId = "election" }) => {
             Voter ID: {voter?.voterId || "Not logged in"}
           </Typography>
 
-          {/* Let the user type in their state code for by-state aggregator */}
-          <TextField
-            label="Your State (e.g. CA, NY)"
-            value={voterState}
-            onChange={(e) => setVoterState(e.target.value)}
-            fullWidth
-            required
-            sx={{ mb: 3 }}
-          />
+          {/* Show the jurisdiction the ballot is styled for */}
+          {ballotStyle && (
+            <Typography variant="body1" sx={{ mb: 3 }}>
+              Ballot for: {ballotStyle.jurisdiction.district || ballotStyle.jurisdiction.state}
+            </Typography>
+          )}
 
           {/* Build a radio group for each contest in the poll's options object */}
-          {Object.keys(election.options).map((contest) => (
+          {ballotContests.map((contest) => (
             <Box key={contest} sx={{ mb: 3, p: 2, border: "1px solid #ccc", borderRadius: 2 }}>
               <Typography variant="h6" gutterBottom>
                 {contest.charAt(0).toUpperCase() + contest.slice(1)}
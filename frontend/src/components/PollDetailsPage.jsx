@@ -25,7 +25,7 @@ import {
   castVote,
   getPollDetails,
   getVoteCounts,
-  getVoteVerification,
+  trackBallot,
   getBlockchain,
 } from "../api/api";
 
@@ -50,19 +50,20 @@ const PollDetailsPage = () => {
 
   // Store the entire blockchain for this poll
   const [blockchain, setBlockchain] = useState([]);
+  // Block holding the ballot cast in this session, found by its tracker code; the chain
+  // carries no voter ids
+  const [voteBlockIndex, setVoteBlockIndex] = useState(null);
 
-  // Fetch poll details, counts, verification, and chain
+  // Fetch poll details, counts, and chain
   useEffect(() => {
     const fetchAllData = async () => {
       setLoadingPoll(true);
       setError(null);
 
       try {
-        const userId = voter?.voterId;
-        const [pollData, countsData, verifyData] = await Promise.all([
+        const [pollData, countsData] = await Promise.all([
           getPollDetails(pollId),
           getVoteCounts(pollId),
-          userId ? getVoteVerification(pollId, userId) : Promise.resolve(null),
         ]);
 
         if (!pollData || !pollData.options) {
@@ -82,10 +83,6 @@ const PollDetailsPage = () => {
           setChartData(formatted);
         }
 
-        if (verifyData && verifyData.hasVoted) {
-          setHasVoted(true);
-        }
-
         // Fetch chain for the Blockchain Explorer
         const chain = await getBlockchain(pollId);
         if (Array.isArray(chain)) {
@@ -99,15 +96,21 @@ const PollDetailsPage = () => {
     };
 
     fetchAllData();
-  }, [pollId, voter]);
+  }, [pollId]);
 
-  // Re-fetch the chain after each vote
-  const refreshChain = async () => {
+  // Re-fetch the chain after each vote and find the ballot by its tracker code
+  const refreshChain = async (code) => {
     try {
       const chain = await getBlockchain(pollId);
       if (Array.isArray(chain)) {
         setBlockchain(chain);
       }
+      if (code) {
+        const tracked = await trackBallot(pollId, code);
+        if (tracked) {
+          setVoteBlockIndex(tracked.block_index);
+        }
+      }
     } catch (err) {
       console.error("Failed to refresh blockchain:", err);
     }
@@ -125,7 +128,7 @@ const PollDetailsPage = () => {
     setSuccess(null);
   
     try {
-      await castVote({
+      const cast = await castVote({
         poll_id: pollId,
         voter_id: voter.voterId,
         vote: {
@@ -133,8 +136,8 @@ const PollDetailsPage = () => {
           state: "Unknown"
         }
       });
-      
-      setSuccess("Vote submitted successfully!");
+
+      setSuccess(`Vote submitted successfully! Your tracker code is ${cast.tracker_code}.`);
   
       // Refresh counts
       const countsData = await getVoteCounts(pollId);
@@ -152,7 +155,7 @@ const PollDetailsPage = () => {
   
       setHasVoted(true);
       // Refresh chain to show the new block
-      await refreshChain();
+      await refreshChain(cast.tracker_code);
     } catch (err) {
       // Handle error gracefully
       if (err.response && err.response.data && err.response.data.error) {
@@ -502,10 +505,7 @@ const PollDetailsPage = () => {
           )}
           {blockchain.map((block, idx) => {
             // Check if this block contains the user's transaction
-            const userHasTransaction = block.transactions.some((tx) => {
-              if (!tx || typeof tx !== "object") return false;
-              return tx.voter_id === voter?.voterId;
-            });
+            const userHasTransaction = block.index === voteBlockIndex;
 
             return (
               <Card